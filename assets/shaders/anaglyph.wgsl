@@ -0,0 +1,16 @@
+#import bevy_ui::ui_vertex_output::UiVertexOutput
+
+@group(1) @binding(0) var left_texture: texture_2d<f32>;
+@group(1) @binding(1) var left_sampler: sampler;
+@group(1) @binding(2) var right_texture: texture_2d<f32>;
+@group(1) @binding(3) var right_sampler: sampler;
+
+@fragment
+fn fragment(in: UiVertexOutput) -> @location(0) vec4<f32> {
+    let left = textureSample(left_texture, left_sampler, in.uv).rgb;
+    let right = textureSample(right_texture, right_sampler, in.uv).rgb;
+    // Half-color anaglyph: the red channel carries the left eye's brightness rather than its
+    // red, so red trails don't vanish for one eye.
+    let left_luminance = dot(left, vec3<f32>(0.299, 0.587, 0.114));
+    return vec4<f32>(left_luminance, right.g, right.b, 1.0);
+}
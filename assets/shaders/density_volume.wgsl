@@ -0,0 +1,46 @@
+#import bevy_pbr::{forward_io::VertexOutput, mesh_view_bindings::view}
+
+@group(2) @binding(0) var volume: texture_3d<f32>;
+@group(2) @binding(1) var volume_sampler: sampler;
+@group(2) @binding(2) var<uniform> box_min: vec4<f32>;
+@group(2) @binding(3) var<uniform> box_max: vec4<f32>;
+// x: opacity per unit of length where the volume is densest.
+@group(2) @binding(4) var<uniform> settings: vec4<f32>;
+
+const STEPS: u32 = 128u;
+
+// Black-red-yellow-white, the ramp of the 2D density histogram.
+fn heat(density: f32) -> vec3<f32> {
+    return clamp(vec3(density * 3.0) - vec3(0.0, 1.0, 2.0), vec3(0.0), vec3(1.0));
+}
+
+@fragment
+fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
+    // Only back faces are drawn, each fragment marches from where the view ray enters the box,
+    // or from the camera while it is inside, to the fragment itself.
+    let origin = view.world_position.xyz;
+    let exit = in.world_position.xyz;
+    let direction = normalize(exit - origin);
+    let t0 = (box_min.xyz - origin) / direction;
+    let t1 = (box_max.xyz - origin) / direction;
+    let near = min(t0, t1);
+    let t_enter = max(max(max(near.x, near.y), near.z), 0.0);
+    let t_exit = distance(origin, exit);
+    let step = max(t_exit - t_enter, 0.0) / f32(STEPS);
+
+    var color = vec3(0.0);
+    var transmittance = 1.0;
+    for (var i = 0u; i < STEPS; i++) {
+        let position = origin + direction * (t_enter + (f32(i) + 0.5) * step);
+        let uvw = (position - box_min.xyz) / (box_max.xyz - box_min.xyz);
+        let density = textureSampleLevel(volume, volume_sampler, uvw, 0.0).r;
+        let alpha = 1.0 - exp(-density * settings.x * step);
+        color += transmittance * alpha * heat(density);
+        transmittance *= 1.0 - alpha;
+        if transmittance < 0.01 {
+            break;
+        }
+    }
+    // Premultiplied, the color is already weighted by the opacity.
+    return vec4(color, 1.0 - transmittance);
+}
@@ -0,0 +1,14 @@
+#import bevy_pbr::{forward_io::VertexOutput, mesh_view_bindings::view}
+
+@group(2) @binding(0) var<uniform> outline_color: vec4<f32>;
+@group(2) @binding(1) var<uniform> rim: f32;
+
+@fragment
+fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
+    let V = normalize(view.world_position.xyz - in.world_position.xyz);
+    // Back faces in front of the head itself are dropped so only the ring remains.
+    if abs(dot(normalize(in.world_normal), V)) > rim {
+        discard;
+    }
+    return outline_color;
+}
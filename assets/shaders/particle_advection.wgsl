@@ -0,0 +1,31 @@
+@group(0) @binding(0) var<storage, read_write> particles: array<vec4<f32>>;
+// x: sigma, y: rho, z: beta, w: time step.
+@group(0) @binding(1) var<uniform> system: vec4<f32>;
+// x: steps to take, y: number of particles.
+@group(0) @binding(2) var<uniform> dispatch: vec2<u32>;
+
+fn lorenz(p: vec3<f32>) -> vec3<f32> {
+    return vec3<f32>(
+        system.x * (p.y - p.x),
+        p.x * (system.y - p.z) - p.y,
+        p.x * p.y - system.z * p.z,
+    );
+}
+
+@compute @workgroup_size(256)
+fn advect(@builtin(global_invocation_id) id: vec3<u32>) {
+    if id.x >= dispatch.y {
+        return;
+    }
+
+    let dt = system.w;
+    var p = particles[id.x].xyz;
+    for (var i = 0u; i < dispatch.x; i++) {
+        let k1 = lorenz(p);
+        let k2 = lorenz(p + k1 * (dt / 2.));
+        let k3 = lorenz(p + k2 * (dt / 2.));
+        let k4 = lorenz(p + k3 * dt);
+        p += (k1 + 2. * k2 + 2. * k3 + k4) * (dt / 6.);
+    }
+    particles[id.x] = vec4<f32>(p, 1.);
+}
@@ -0,0 +1,22 @@
+#import bevy_pbr::view_transformations::position_world_to_clip
+
+@group(2) @binding(0) var<storage, read> particles: array<vec4<f32>>;
+// Premultiplied by the exposure, accumulated additively.
+@group(2) @binding(1) var<uniform> color: vec4<f32>;
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+}
+
+// The mesh only provides one point per particle, positions come from the storage buffer.
+@vertex
+fn vertex(@builtin(vertex_index) index: u32) -> VertexOutput {
+    var out: VertexOutput;
+    out.clip_position = position_world_to_clip(particles[index].xyz);
+    return out;
+}
+
+@fragment
+fn fragment() -> @location(0) vec4<f32> {
+    return color;
+}
@@ -1,6 +1,80 @@
+#import bevy_pbr::{
+    forward_io::VertexOutput,
+    mesh_view_bindings::{fog, view},
+    mesh_view_types,
+    pbr_functions,
+    pbr_types,
+}
+#ifdef OIT_ENABLED
+#import bevy_core_pipeline::oit::oit_draw
+#endif
+
 @group(2) @binding(0) var<uniform> material_color: vec4<f32>;
+// xyz: plane normal, w: offset along the normal. A zero normal disables slicing.
+@group(2) @binding(1) var<uniform> slice_plane: vec4<f32>;
+// x: highlight half-width, y: dim factor, z: 1 to clip the far side.
+@group(2) @binding(2) var<uniform> slice_settings: vec4<f32>;
+// x: 1 to enable lighting, y: perceptual roughness, z: metallic.
+@group(2) @binding(3) var<uniform> lighting: vec4<f32>;
+// x: amount to brighten (positive) or darken (negative) the color.
+@group(2) @binding(4) var<uniform> emphasis: vec4<f32>;
+// x: emissive strength, only meaningful with an HDR camera.
+@group(2) @binding(5) var<uniform> glow: vec4<f32>;
 
 @fragment
-fn fragment() -> @location(0) vec4<f32> {
-    return material_color;
+fn fragment(
+    in: VertexOutput,
+    @builtin(front_facing) is_front: bool,
+) -> @location(0) vec4<f32> {
+    var color = material_color;
+    if emphasis.x > 0. {
+        color = vec4<f32>(mix(color.rgb, vec3<f32>(1.), emphasis.x), color.a);
+    } else {
+        color = vec4<f32>(color.rgb * (1. + emphasis.x), color.a);
+    }
+
+    if any(slice_plane.xyz != vec3<f32>(0.)) {
+        let signed_distance = dot(in.world_position.xyz, slice_plane.xyz) - slice_plane.w;
+        if abs(signed_distance) < slice_settings.x {
+            return vec4<f32>(mix(color.rgb, vec3<f32>(1.), 0.8), color.a);
+        }
+        if signed_distance > 0. {
+            if slice_settings.z > 0.5 {
+                discard;
+            }
+            color = vec4<f32>(color.rgb * slice_settings.y, color.a);
+        }
+    }
+
+    if lighting.x > 0.5 {
+        var pbr_input = pbr_types::pbr_input_new();
+        pbr_input.material.base_color = color;
+        pbr_input.material.perceptual_roughness = lighting.y;
+        pbr_input.material.metallic = lighting.z;
+        pbr_input.material.flags |= pbr_types::STANDARD_MATERIAL_FLAGS_FOG_ENABLED_BIT;
+        pbr_input.frag_coord = in.position;
+        pbr_input.world_position = in.world_position;
+        pbr_input.world_normal = pbr_functions::prepare_world_normal(in.world_normal, false, is_front);
+        pbr_input.is_orthographic = view.clip_from_view[3].w == 1.0;
+        pbr_input.N = normalize(pbr_input.world_normal);
+        pbr_input.V = pbr_functions::calculate_view(in.world_position, pbr_input.is_orthographic);
+
+        color = pbr_functions::apply_pbr_lighting(pbr_input);
+        color = pbr_functions::main_pass_post_lighting_processing(pbr_input, color);
+    } else if fog.mode != mesh_view_types::FOG_MODE_OFF {
+        color = pbr_functions::apply_fog(fog, color, in.world_position.xyz, view.world_position.xyz);
+    }
+
+    color = vec4<f32>(color.rgb + material_color.rgb * glow.x, color.a);
+
+#ifdef OIT_ENABLED
+    // Translucent fragments are stored per pixel and blended in depth order by the resolve
+    // pass, instead of being drawn here.
+    if material_color.a < 1. {
+        oit_draw(in.position, color);
+        discard;
+    }
+#endif
+
+    return color;
 }
@@ -0,0 +1,88 @@
+use bevy::math::{DMat3, DVec3};
+
+/// A point where the vector field vanishes, with the eigenvalues of the Jacobian there.
+pub struct Equilibrium {
+    pub name: &'static str,
+    pub position: DVec3,
+    /// Real and imaginary parts.
+    pub eigenvalues: [(f64, f64); 3],
+}
+
+impl Equilibrium {
+    /// Whether every nearby trajectory decays into it, i.e. no eigenvalue has a non-negative
+    /// real part.
+    pub fn is_stable(&self) -> bool {
+        self.eigenvalues.iter().all(|&(re, _)| re < 0.)
+    }
+}
+
+/// The origin and, for rho > 1, the two symmetric spiral points C+ and C- at
+/// (±sqrt(beta (rho - 1)), ±sqrt(beta (rho - 1)), rho - 1).
+pub fn lorenz_equilibria(sigma: f64, rho: f64, beta: f64) -> Vec<Equilibrium> {
+    let equilibrium = |name, position: DVec3| Equilibrium {
+        name,
+        position,
+        eigenvalues: eigenvalues(lorenz_jacobian(sigma, rho, beta, position)),
+    };
+
+    let mut equilibria = vec![equilibrium("Origin", DVec3::ZERO)];
+    if rho > 1. {
+        let r = (beta * (rho - 1.)).sqrt();
+        equilibria.push(equilibrium("C+", DVec3::new(r, r, rho - 1.)));
+        equilibria.push(equilibrium("C-", DVec3::new(-r, -r, rho - 1.)));
+    }
+    equilibria
+}
+
+fn lorenz_jacobian(sigma: f64, rho: f64, beta: f64, DVec3 { x, y, z }: DVec3) -> DMat3 {
+    DMat3::from_cols(
+        DVec3::new(-sigma, rho - z, y),
+        DVec3::new(sigma, -1., x),
+        DVec3::new(0., -x, -beta),
+    )
+}
+
+/// Roots of the characteristic polynomial of `m`.
+fn eigenvalues(m: DMat3) -> [(f64, f64); 3] {
+    let trace = m.x_axis.x + m.y_axis.y + m.z_axis.z;
+    // Sum of the principal 2x2 minors.
+    let minors = m.x_axis.x * m.y_axis.y - m.y_axis.x * m.x_axis.y + m.x_axis.x * m.z_axis.z
+        - m.z_axis.x * m.x_axis.z
+        + m.y_axis.y * m.z_axis.z
+        - m.z_axis.y * m.y_axis.z;
+    solve_cubic(-trace, minors, -m.determinant())
+}
+
+/// Roots of x³ + a x² + b x + c by Cardano's method, real roots first.
+fn solve_cubic(a: f64, b: f64, c: f64) -> [(f64, f64); 3] {
+    // Substituting x = t - a/3 leaves t³ + p t + q.
+    let shift = -a / 3.;
+    let p = b - a * a / 3.;
+    let q = 2. * a.powi(3) / 27. - a * b / 3. + c;
+    let discriminant = (q / 2.).powi(2) + (p / 3.).powi(3);
+
+    if discriminant > 0. {
+        // One real root and a complex conjugate pair.
+        let u = (-q / 2. + discriminant.sqrt()).cbrt();
+        let v = (-q / 2. - discriminant.sqrt()).cbrt();
+        let im = 3_f64.sqrt() / 2. * (u - v);
+        [
+            (u + v + shift, 0.),
+            (-(u + v) / 2. + shift, im),
+            (-(u + v) / 2. + shift, -im),
+        ]
+    } else if p == 0. {
+        [(shift, 0.); 3]
+    } else {
+        // Three real roots, trigonometrically.
+        let radius = 2. * (-p / 3.).sqrt();
+        let angle = (3. * q / (2. * p) * (-3. / p).sqrt()).clamp(-1., 1.).acos() / 3.;
+        let root = |k: f64| {
+            (
+                radius * (angle - 2. * std::f64::consts::PI * k / 3.).cos() + shift,
+                0.,
+            )
+        };
+        [root(0.), root(1.), root(2.)]
+    }
+}
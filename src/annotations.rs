@@ -0,0 +1,167 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+use bevy_panorbit_camera::PanOrbitCamera;
+
+use crate::{
+    analysis::{self, Equilibrium},
+    attractor::AttractorSystem,
+    Configuration,
+};
+
+const STABLE_COLOR: Color = Color::srgb(0.3, 0.9, 0.4);
+const UNSTABLE_COLOR: Color = Color::srgb(1., 0.3, 0.25);
+
+pub struct AnnotationsPlugin;
+
+impl Plugin for AnnotationsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                (draw_fixed_points, fixed_point_labels).run_if(|config: Res<Configuration>| {
+                    (config.annotations || config.fixed_point_markers)
+                        && config.attractor == AttractorSystem::Lorenz
+                }),
+                annotations_ui.run_if(|config: Res<Configuration>| {
+                    config.annotations && config.attractor == AttractorSystem::Lorenz
+                }),
+            ),
+        );
+    }
+}
+
+/// Recomputed every frame, which is cheap enough to follow parameter changes live.
+fn fixed_points(config: &Configuration) -> Vec<Equilibrium> {
+    analysis::lorenz_equilibria(config.sigma as f64, config.rho as f64, config.beta as f64)
+}
+
+fn stability_color(equilibrium: &Equilibrium) -> Color {
+    if equilibrium.is_stable() {
+        STABLE_COLOR
+    } else {
+        UNSTABLE_COLOR
+    }
+}
+
+/// Value of rho above which C+ and C- lose stability in a subcritical Hopf bifurcation, if
+/// they do at all.
+fn hopf_rho(config: &Configuration) -> Option<f32> {
+    let (sigma, beta) = (config.sigma, config.beta);
+    (sigma > beta + 1.).then(|| sigma * (sigma + beta + 3.) / (sigma - beta - 1.))
+}
+
+fn regime(config: &Configuration) -> String {
+    match hopf_rho(config) {
+        _ if config.rho < 1. => {
+            "rho < 1: the origin is the only fixed point and every trajectory decays into it."
+                .into()
+        }
+        Some(hopf) if config.rho > hopf => format!(
+            "rho > {hopf:.2}: all three fixed points are unstable, so trajectories never \
+             settle. They keep switching between orbiting C+ and C- on the strange \
+             attractor. With the classic sigma = 10, beta = 8/3 this threshold is about \
+             24.74, which is why rho = 28 is chaotic."
+        ),
+        Some(hopf) => format!(
+            "1 < rho < {hopf:.2}: C+ and C- are stable, trajectories spiral into one of them, \
+             possibly after a chaotic transient."
+        ),
+        None => "1 < rho and sigma <= beta + 1: C+ and C- stay stable for every rho.".into(),
+    }
+}
+
+fn draw_fixed_points(mut gizmos: Gizmos, config: Res<Configuration>) {
+    for equilibrium in fixed_points(&config) {
+        gizmos.sphere(
+            Isometry3d::from_translation(equilibrium.position.as_vec3()),
+            0.8,
+            stability_color(&equilibrium),
+        );
+    }
+}
+
+fn fixed_point_labels(
+    mut contexts: EguiContexts,
+    cameras: Query<(&Camera, &GlobalTransform), With<PanOrbitCamera>>,
+    config: Res<Configuration>,
+) {
+    if !config.fixed_point_labels {
+        return;
+    }
+    let Ok((camera, camera_transform)) = cameras.get_single() else {
+        return;
+    };
+    let ctx = contexts.ctx_mut();
+
+    for equilibrium in fixed_points(&config) {
+        let point = equilibrium.position;
+        let Ok(screen) = camera.world_to_viewport(camera_transform, point.as_vec3()) else {
+            continue;
+        };
+        let eigenvalues: Vec<String> = equilibrium
+            .eigenvalues
+            .iter()
+            .map(|&(re, im)| {
+                if im == 0. {
+                    format!("{re:.2}")
+                } else {
+                    format!("{re:.2}{im:+.2}i")
+                }
+            })
+            .collect();
+        let [r, g, b, _] = stability_color(&equilibrium).to_srgba().to_u8_array();
+        let stability = if equilibrium.is_stable() {
+            "stable"
+        } else {
+            "unstable"
+        };
+
+        egui::Area::new(egui::Id::new(("fixed_point", equilibrium.name)))
+            .fixed_pos([screen.x + 12., screen.y - 8.])
+            .interactable(false)
+            .show(ctx, |ui| {
+                ui.colored_label(
+                    egui::Color32::from_rgb(r, g, b),
+                    format!(
+                        "{} ({:.1}, {:.1}, {:.1}), {stability}\nλ = {}",
+                        equilibrium.name,
+                        point.x,
+                        point.y,
+                        point.z,
+                        eigenvalues.join(", ")
+                    ),
+                );
+            });
+    }
+}
+
+fn annotations_ui(mut contexts: EguiContexts, config: Res<Configuration>) {
+    let ctx = contexts.ctx_mut();
+
+    egui::Window::new("About the Lorenz system")
+        .default_width(360.)
+        .show(ctx, |ui| {
+            ui.monospace(format!(
+                "dx/dt = σ (y − x)     σ = {:.2}\n\
+                 dy/dt = x (ρ − z) − y ρ = {:.2}\n\
+                 dz/dt = x y − β z     β = {:.3}",
+                config.sigma, config.rho, config.beta
+            ));
+            ui.separator();
+            ui.label(
+                "σ (sigma) is the Prandtl number: how quickly the convection speed x follows \
+                 the temperature difference y.",
+            );
+            ui.label(
+                "ρ (rho) is the Rayleigh number: how strongly the fluid is heated from below. \
+                 It drives the system from rest into convection and then into chaos.",
+            );
+            ui.label("β (beta) is a geometric factor of the convection cells.");
+            ui.separator();
+            ui.label(
+                "The spheres mark the fixed points where the flow stands still, green where \
+                 nearby trajectories are drawn in and red where some are pushed away.",
+            );
+            ui.label(regime(&config));
+        });
+}
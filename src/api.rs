@@ -0,0 +1,368 @@
+use std::{
+    fmt::Write as _,
+    io::{self, BufRead, BufReader, ErrorKind, Read, Write},
+    net::{TcpListener, TcpStream},
+    time::{Duration, Instant},
+};
+
+use bevy::prelude::*;
+use bevy_egui::egui;
+use serde::Serialize;
+
+use crate::{
+    integrator::Integrator, screenshot::take_screenshot, Configuration, HeadIndex, TrailHead,
+};
+
+const DEFAULT_ADDRESS: &str = "127.0.0.1:8080";
+/// Requests are handled on the main thread, so a stalled or trickling client must not freeze
+/// the app. This bounds the time spent reading each request as a whole.
+const REQUEST_TIMEOUT: Duration = Duration::from_millis(100);
+/// Largest request line and headers accepted together.
+const MAX_HEAD_LENGTH: usize = 8 * 1024;
+/// Largest request body accepted, far more than any configuration patch needs.
+const MAX_BODY_LENGTH: usize = 64 * 1024;
+/// Hosts a request may be addressed to. Anything else is a web page reaching the API through
+/// the browser, e.g. by DNS rebinding.
+const LOOPBACK_HOSTS: [&str; 3] = ["127.0.0.1", "localhost", "[::1]"];
+
+pub struct ApiPlugin;
+
+impl Plugin for ApiPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<HttpApi>()
+            .add_systems(Update, serve_requests);
+    }
+}
+
+/// Tiny HTTP server for scripts and notebooks.
+///
+/// | Method | Path          | Effect                                                    |
+/// |--------|---------------|-----------------------------------------------------------|
+/// | GET    | `/config`     | Current configuration as JSON                             |
+/// | POST   | `/config`     | Merges the JSON object in the body into the configuration |
+/// | GET    | `/heads`      | Head positions as JSON                                    |
+/// | GET    | `/heads.csv`  | Head positions as `head,t,x,y,z` CSV                      |
+/// | POST   | `/screenshot` | Saves a screenshot as a PNG named after the elapsed time  |
+///
+/// Only connections from this machine are accepted, and of those only requests addressed to
+/// and, for browsers, sent from a loopback host are served, so neither other machines nor web
+/// pages the user visits can change the configuration or write files.
+#[derive(Resource)]
+pub struct HttpApi {
+    address: String,
+    listener: Option<TcpListener>,
+    status: Option<String>,
+}
+
+impl Default for HttpApi {
+    fn default() -> Self {
+        Self {
+            address: DEFAULT_ADDRESS.into(),
+            listener: None,
+            status: None,
+        }
+    }
+}
+
+impl HttpApi {
+    fn listen(&mut self) -> Result<(), String> {
+        let listener = TcpListener::bind(&self.address)
+            .and_then(|listener| {
+                listener.set_nonblocking(true)?;
+                Ok(listener)
+            })
+            .map_err(|err| format!("{}: {err}", self.address))?;
+        self.listener = Some(listener);
+        Ok(())
+    }
+}
+
+struct Request {
+    method: String,
+    path: String,
+    body: String,
+}
+
+struct Response {
+    status: &'static str,
+    content_type: &'static str,
+    body: String,
+}
+
+impl Response {
+    fn new(status: &'static str, content_type: &'static str, body: impl Into<String>) -> Self {
+        Self {
+            status,
+            content_type,
+            body: body.into(),
+        }
+    }
+
+    fn text(status: &'static str, body: impl Into<String>) -> Self {
+        Self::new(status, "text/plain", body)
+    }
+
+    fn json(body: impl Into<String>) -> Self {
+        Self::new("200 OK", "application/json", body)
+    }
+}
+
+#[derive(Serialize)]
+struct HeadState {
+    index: u16,
+    integrator: Integrator,
+    position: Vec3,
+}
+
+/// Reads from a stream until `deadline`, however the reads are spread out until then.
+struct DeadlineReader<'a> {
+    stream: &'a TcpStream,
+    deadline: Instant,
+}
+
+impl Read for DeadlineReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let remaining = self.deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return Err(io::Error::new(ErrorKind::TimedOut, "Request took too long"));
+        }
+        self.stream.set_read_timeout(Some(remaining))?;
+        self.stream.read(buf)
+    }
+}
+
+fn bad_request(err: String) -> Response {
+    Response::text("400 Bad Request", err)
+}
+
+/// Reads one line of the request line and headers, counting it against `budget`.
+fn read_head_line(reader: &mut impl BufRead, budget: &mut usize) -> Result<String, Response> {
+    let mut line = String::new();
+    let length = Read::take(&mut *reader, *budget as u64)
+        .read_line(&mut line)
+        .map_err(|err| bad_request(err.to_string()))?;
+    if line.ends_with('\n') {
+        *budget -= length;
+        Ok(line)
+    } else if length == *budget {
+        Err(Response::text(
+            "431 Request Header Fields Too Large",
+            format!("Request lines and headers are limited to {MAX_HEAD_LENGTH} bytes"),
+        ))
+    } else {
+        Err(bad_request("Incomplete request".into()))
+    }
+}
+
+fn read_request(stream: &mut TcpStream) -> Result<Request, Response> {
+    stream
+        .set_nonblocking(false)
+        .map_err(|err| bad_request(err.to_string()))?;
+    let mut reader = BufReader::new(DeadlineReader {
+        stream,
+        deadline: Instant::now() + REQUEST_TIMEOUT,
+    });
+    let mut budget = MAX_HEAD_LENGTH;
+
+    let request_line = read_head_line(&mut reader, &mut budget)?;
+    let mut parts = request_line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return Err(bad_request("Malformed request line".into()));
+    };
+    let path = target.split_once('?').map_or(target, |(path, _)| path);
+
+    let mut content_length = 0;
+    let mut host = None;
+    let mut origin = None;
+    loop {
+        let header = read_head_line(&mut reader, &mut budget)?;
+        let header = header.trim();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            let value = value.trim();
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value
+                    .parse()
+                    .map_err(|_| bad_request("Bad Content-Length".into()))?;
+            } else if name.eq_ignore_ascii_case("host") {
+                host = Some(value.to_string());
+            } else if name.eq_ignore_ascii_case("origin") {
+                origin = Some(value.to_string());
+            }
+        }
+    }
+
+    let host_is_loopback = host.as_deref().is_some_and(is_loopback);
+    // Browsers send an origin with every cross-site POST, other clients usually none.
+    let origin_is_loopback = origin.as_deref().map_or(true, |origin| {
+        origin
+            .strip_prefix("http://")
+            .or_else(|| origin.strip_prefix("https://"))
+            .is_some_and(is_loopback)
+    });
+    if !host_is_loopback || !origin_is_loopback {
+        return Err(Response::text(
+            "403 Forbidden",
+            "Only requests from this machine are served",
+        ));
+    }
+    if content_length > MAX_BODY_LENGTH {
+        return Err(Response::text(
+            "413 Payload Too Large",
+            format!("Bodies are limited to {MAX_BODY_LENGTH} bytes"),
+        ));
+    }
+
+    let mut body = vec![0; content_length];
+    reader
+        .read_exact(&mut body)
+        .map_err(|err| bad_request(err.to_string()))?;
+
+    Ok(Request {
+        method: method.to_string(),
+        path: path.to_string(),
+        body: String::from_utf8_lossy(&body).into_owned(),
+    })
+}
+
+/// Whether `authority`, a host with an optional port, names this machine.
+fn is_loopback(authority: &str) -> bool {
+    let host = match authority.rsplit_once(':') {
+        // The colons of an IPv6 address are inside the brackets.
+        Some((host, port)) if !port.contains(']') => host,
+        _ => authority,
+    };
+    LOOPBACK_HOSTS
+        .iter()
+        .any(|loopback| host.eq_ignore_ascii_case(loopback))
+}
+
+fn write_response(stream: &mut TcpStream, response: &Response) -> std::io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        response.status,
+        response.content_type,
+        response.body.len(),
+        response.body
+    )
+}
+
+fn serve_requests(world: &mut World) {
+    let mut streams = Vec::new();
+    if let Some(listener) = world.resource::<HttpApi>().listener.as_ref() {
+        while let Ok((stream, peer)) = listener.accept() {
+            // Closed unread, a remote client gets nothing to work with.
+            if peer.ip().to_canonical().is_loopback() {
+                streams.push(stream);
+            }
+        }
+    }
+
+    for mut stream in streams {
+        let response = match read_request(&mut stream) {
+            Ok(request) => handle(world, &request),
+            Err(response) => response,
+        };
+        if let Err(err) = write_response(&mut stream, &response) {
+            warn!("Could not answer HTTP request: {err}");
+        }
+    }
+}
+
+fn handle(world: &mut World, request: &Request) -> Response {
+    match (request.method.as_str(), request.path.as_str()) {
+        ("GET", "/config") => Response::json(
+            serde_json::to_string(world.resource::<Configuration>())
+                .expect("Configuration is always serializable"),
+        ),
+        ("POST", "/config") => match merge_config(world.resource::<Configuration>(), &request.body)
+        {
+            Ok(config) => {
+                world.insert_resource(config);
+                Response::text("204 No Content", "")
+            }
+            Err(err) => Response::text("400 Bad Request", err),
+        },
+        ("GET", "/heads") => Response::json(
+            serde_json::to_string(&head_states(world)).expect("heads are always serializable"),
+        ),
+        ("GET", "/heads.csv") => Response::new("200 OK", "text/csv", heads_csv(world)),
+        ("POST", "/screenshot") => Response::text("202 Accepted", take_screenshot(world, None)),
+        (_, "/config" | "/heads" | "/heads.csv" | "/screenshot") => {
+            Response::text("405 Method Not Allowed", "")
+        }
+        _ => Response::text("404 Not Found", ""),
+    }
+}
+
+/// Overwrites the fields given in `body` and keeps everything else, so clients can send
+/// e.g. `{"rho": 24.0}` alone.
+fn merge_config(config: &Configuration, body: &str) -> Result<Configuration, String> {
+    let patch: serde_json::Value = serde_json::from_str(body).map_err(|err| err.to_string())?;
+    let serde_json::Value::Object(patch) = patch else {
+        return Err("Expected a JSON object".into());
+    };
+
+    let mut merged = serde_json::to_value(config).expect("Configuration is always serializable");
+    let serde_json::Value::Object(fields) = &mut merged else {
+        unreachable!("Configuration serializes to an object");
+    };
+    for (key, value) in patch {
+        if !fields.contains_key(&key) {
+            return Err(format!("Unknown field `{key}`"));
+        }
+        fields.insert(key, value);
+    }
+
+    let merged: Configuration = serde_json::from_value(merged).map_err(|err| err.to_string())?;
+    merged.validate()?;
+    Ok(merged)
+}
+
+fn head_states(world: &mut World) -> Vec<HeadState> {
+    let mut heads: Vec<HeadState> = world
+        .query_filtered::<(&HeadIndex, &Integrator, &Transform), With<TrailHead>>()
+        .iter(world)
+        .map(|(index, integrator, transform)| HeadState {
+            index: index.0,
+            integrator: *integrator,
+            position: transform.translation,
+        })
+        .collect();
+    heads.sort_by_key(|head| head.index);
+    heads
+}
+
+/// Uses the same layout the reference trajectory loader reads.
+fn heads_csv(world: &mut World) -> String {
+    let t = world.resource::<Time<Virtual>>().elapsed_secs();
+    let mut csv = String::from("head,t,x,y,z\n");
+    for (head, state) in head_states(world).iter().enumerate() {
+        let Vec3 { x, y, z } = state.position;
+        writeln!(csv, "{head},{t},{x},{y},{z}").expect("writing to a String cannot fail");
+    }
+    csv
+}
+
+pub fn api_ui(world: &mut World, ui: &mut egui::Ui) {
+    let mut api = world.resource_mut::<HttpApi>();
+
+    if api.listener.is_some() {
+        ui.label(format!("Serving on http://{}", api.address));
+        if ui.button("Stop").clicked() {
+            api.listener = None;
+        }
+    } else {
+        ui.text_edit_singleline(&mut api.address);
+        if ui.button("Serve").clicked() {
+            api.status = api.listen().err();
+        }
+    }
+
+    if let Some(status) = api.status.as_ref() {
+        ui.colored_label(egui::Color32::RED, status);
+    }
+}
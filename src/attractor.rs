@@ -0,0 +1,219 @@
+use bevy::{math::DVec3, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use crate::Configuration;
+
+pub struct AttractorPlugin;
+
+impl Plugin for AttractorPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            PreUpdate,
+            fill_in_parameters.run_if(|config: Res<Configuration>| config.is_changed()),
+        );
+    }
+}
+
+/// Set of ODEs the trail heads follow. Every variant but Lorenz and Custom carries its own
+/// parameters, Lorenz keeps using `sigma`, `rho` and `beta` of the configuration. Custom
+/// follows the equations of `custom_system`.
+#[derive(Reflect, Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum AttractorSystem {
+    Lorenz,
+    Rossler {
+        a: f32,
+        b: f32,
+        c: f32,
+    },
+    Chen {
+        a: f32,
+        b: f32,
+        c: f32,
+    },
+    Halvorsen {
+        a: f32,
+    },
+    Thomas {
+        b: f32,
+    },
+    Aizawa {
+        a: f32,
+        b: f32,
+        c: f32,
+        d: f32,
+        e: f32,
+        f: f32,
+    },
+    Dadras {
+        a: f32,
+        b: f32,
+        c: f32,
+        d: f32,
+        e: f32,
+    },
+    Custom,
+}
+
+/// `sigma`, `rho` and `beta`, read by the Lorenz and custom systems. Attractor instances have
+/// their own, every other head uses those of the configuration.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Parameters {
+    pub sigma: f32,
+    pub rho: f32,
+    pub beta: f32,
+}
+
+impl Parameters {
+    pub fn of(config: &Configuration) -> Self {
+        Self {
+            sigma: config.sigma,
+            rho: config.rho,
+            beta: config.beta,
+        }
+    }
+}
+
+/// Implements the vector field once for each float width, so single-precision heads stay in
+/// f32 throughout.
+macro_rules! velocity {
+    ($name:ident, $vec:ty, $float:ty) => {
+        pub fn $name(
+            self,
+            config: &Configuration,
+            parameters: &Parameters,
+            position: $vec,
+        ) -> $vec {
+            let (x, y, z) = (position.x, position.y, position.z);
+            let f = |value: f32| value as $float;
+            match self {
+                AttractorSystem::Lorenz => {
+                    let (sigma, rho, beta) =
+                        (f(parameters.sigma), f(parameters.rho), f(parameters.beta));
+                    <$vec>::new(sigma * (y - x), x * (rho - z) - y, x * y - beta * z)
+                }
+                AttractorSystem::Rossler { a, b, c } => {
+                    let (a, b, c) = (f(a), f(b), f(c));
+                    <$vec>::new(-y - z, x + a * y, b + z * (x - c))
+                }
+                AttractorSystem::Chen { a, b, c } => {
+                    let (a, b, c) = (f(a), f(b), f(c));
+                    <$vec>::new(a * (y - x), (c - a) * x - x * z + c * y, x * y - b * z)
+                }
+                AttractorSystem::Halvorsen { a } => {
+                    let a = f(a);
+                    <$vec>::new(
+                        -a * x - 4. * y - 4. * z - y * y,
+                        -a * y - 4. * z - 4. * x - z * z,
+                        -a * z - 4. * x - 4. * y - x * x,
+                    )
+                }
+                AttractorSystem::Thomas { b } => {
+                    let b = f(b);
+                    <$vec>::new(y.sin() - b * x, z.sin() - b * y, x.sin() - b * z)
+                }
+                AttractorSystem::Aizawa {
+                    a,
+                    b,
+                    c,
+                    d,
+                    e,
+                    f: f_,
+                } => {
+                    let (a, b, c, d, e, f_) = (f(a), f(b), f(c), f(d), f(e), f(f_));
+                    <$vec>::new(
+                        (z - b) * x - d * y,
+                        d * x + (z - b) * y,
+                        c + a * z - z * z * z / 3. - (x * x + y * y) * (1. + e * z)
+                            + f_ * z * x * x * x,
+                    )
+                }
+                AttractorSystem::Dadras { a, b, c, d, e } => {
+                    let (a, b, c, d, e) = (f(a), f(b), f(c), f(d), f(e));
+                    <$vec>::new(y - a * x + b * y * z, c * y - x * z + z, d * x * y - e * z)
+                }
+                AttractorSystem::Custom => {
+                    let [dx, dy, dz] = config
+                        .custom_system
+                        .evaluate(parameters, [x as f64, y as f64, z as f64]);
+                    <$vec>::new(dx as $float, dy as $float, dz as $float)
+                }
+            }
+        }
+    };
+}
+
+impl AttractorSystem {
+    pub const ALL: [Self; 7] = [
+        Self::Lorenz,
+        Self::Rossler {
+            a: 0.2,
+            b: 0.2,
+            c: 5.7,
+        },
+        Self::Chen {
+            a: 35.,
+            b: 3.,
+            c: 28.,
+        },
+        Self::Halvorsen { a: 1.89 },
+        Self::Thomas { b: 0.208186 },
+        Self::Aizawa {
+            a: 0.95,
+            b: 0.7,
+            c: 0.6,
+            d: 3.5,
+            e: 0.25,
+            f: 0.1,
+        },
+        Self::Dadras {
+            a: 3.,
+            b: 2.7,
+            c: 1.7,
+            d: 2.,
+            e: 9.,
+        },
+    ];
+
+    velocity!(velocity, Vec3, f32);
+    velocity!(velocity_f64, DVec3, f64);
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Lorenz => "Lorenz",
+            Self::Rossler { .. } => "Rössler",
+            Self::Chen { .. } => "Chen",
+            Self::Halvorsen { .. } => "Halvorsen",
+            Self::Thomas { .. } => "Thomas",
+            Self::Aizawa { .. } => "Aizawa",
+            Self::Dadras { .. } => "Dadras",
+            Self::Custom => "Custom",
+        }
+    }
+
+    /// The same system with the parameters it is usually shown with.
+    pub fn with_classic_parameters(self) -> Self {
+        Self::ALL
+            .into_iter()
+            .find(|system| system.name() == self.name())
+            .unwrap_or(self)
+    }
+
+    fn parameters_are_zero(self) -> bool {
+        match self {
+            Self::Lorenz | Self::Custom => false,
+            Self::Rossler { a, b, c } | Self::Chen { a, b, c } => [a, b, c] == [0.; 3],
+            Self::Halvorsen { a } => a == 0.,
+            Self::Thomas { b } => b == 0.,
+            Self::Aizawa { a, b, c, d, e, f } => [a, b, c, d, e, f] == [0.; 6],
+            Self::Dadras { a, b, c, d, e } => [a, b, c, d, e] == [0.; 5],
+        }
+    }
+}
+
+/// Switching the variant in the inspector zeroes its fields, which is a degenerate flow for
+/// every system. Start from the classic parameters instead.
+fn fill_in_parameters(mut config: ResMut<Configuration>) {
+    if config.attractor.parameters_are_zero() {
+        config.attractor = config.attractor.with_classic_parameters();
+    }
+}
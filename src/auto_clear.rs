@@ -0,0 +1,49 @@
+use bevy::prelude::*;
+
+use crate::{
+    event_log::{LogCategory, LogEntry},
+    gui, Configuration, TimeOfBirth,
+};
+
+pub struct AutoClearPlugin;
+
+impl Plugin for AutoClearPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AutoClearTimer>().add_systems(
+            Update,
+            auto_clear.run_if(|config: Res<Configuration>| config.auto_clear_interval > 0.),
+        );
+    }
+}
+
+/// Simulated time since the last automatic reset.
+#[derive(Resource, Default)]
+struct AutoClearTimer(f32);
+
+/// Periodically wipes the trails, so unattended displays don't end up as a solid blob.
+fn auto_clear(world: &mut World) {
+    let delta = world.resource::<Time<Virtual>>().delta_secs();
+    let config = world.resource::<Configuration>();
+    let (interval, respawn) = (config.auto_clear_interval, config.auto_clear_respawn);
+
+    let mut timer = world.resource_mut::<AutoClearTimer>();
+    timer.0 += delta;
+    if timer.0 < interval {
+        return;
+    }
+    timer.0 = 0.;
+
+    if respawn {
+        gui::clear(world);
+        gui::start(world);
+    } else {
+        let segments: Vec<_> = world
+            .query_filtered::<Entity, With<TimeOfBirth>>()
+            .iter(world)
+            .collect();
+        for segment in segments {
+            world.despawn(segment);
+        }
+        world.send_event(LogEntry::new(LogCategory::Respawn, "Trails cleared"));
+    }
+}
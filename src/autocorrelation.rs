@@ -0,0 +1,110 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+use egui_plot::{HLine, Line, Plot, PlotPoints};
+
+use crate::{
+    extensions::{Visualization, VisualizationSet},
+    statistics::SampleHistory,
+};
+
+pub struct AutocorrelationPanel;
+
+impl Visualization for AutocorrelationPanel {
+    fn name(&self) -> &'static str {
+        "Autocorrelation"
+    }
+
+    fn build(&self, app: &mut App, set: VisualizationSet) {
+        app.init_resource::<AutocorrelationPlot>()
+            .add_systems(Update, autocorrelation_ui.in_set(set));
+    }
+}
+
+#[derive(Resource)]
+struct AutocorrelationPlot {
+    /// Index of the coordinate, 0 to 2 for x to z.
+    axis: usize,
+    /// Largest lag in physics ticks.
+    max_lag: usize,
+}
+
+impl Default for AutocorrelationPlot {
+    fn default() -> Self {
+        Self {
+            axis: 0,
+            max_lag: 300,
+        }
+    }
+}
+
+/// Normalized sample autocorrelation of `series` for lags `0..=max_lag`.
+fn autocorrelation(series: &[f32], max_lag: usize) -> Vec<f32> {
+    let mean = series.iter().sum::<f32>() / series.len() as f32;
+    let deviations: Vec<f32> = series.iter().map(|x| x - mean).collect();
+    let variance: f32 = deviations.iter().map(|d| d * d).sum();
+    if variance == 0. {
+        return Vec::new();
+    }
+
+    (0..=max_lag.min(series.len() - 1))
+        .map(|lag| {
+            deviations
+                .iter()
+                .zip(&deviations[lag..])
+                .map(|(a, b)| a * b)
+                .sum::<f32>()
+                / variance
+        })
+        .collect()
+}
+
+fn autocorrelation_ui(
+    mut contexts: EguiContexts,
+    mut plot: ResMut<AutocorrelationPlot>,
+    history: Res<SampleHistory>,
+    time: Res<Time<Fixed>>,
+) {
+    egui::Window::new("Autocorrelation")
+        .default_open(false)
+        .show(contexts.ctx_mut(), |ui| {
+            ui.horizontal(|ui| {
+                for (axis, label) in ["x", "y", "z"].into_iter().enumerate() {
+                    ui.radio_value(&mut plot.axis, axis, label);
+                }
+            });
+            ui.add(egui::Slider::new(&mut plot.max_lag, 1..=2000).text("max lag (ticks)"));
+            ui.label("Uses the samples collected by the statistics window.");
+
+            // The ensemble is reduced to its mean so every tick contributes one value.
+            let series: Vec<f32> = history
+                .ticks
+                .iter()
+                .map(|samples| {
+                    samples.iter().map(|sample| sample[plot.axis]).sum::<f32>()
+                        / samples.len() as f32
+                })
+                .collect();
+            if series.len() < 2 {
+                ui.label("Not enough samples yet.");
+                return;
+            }
+
+            let dt = time.timestep().as_secs_f64();
+            let points: PlotPoints = autocorrelation(&series, plot.max_lag)
+                .into_iter()
+                .enumerate()
+                .map(|(lag, value)| [lag as f64 * dt, value as f64])
+                .collect();
+
+            Plot::new("autocorrelation")
+                .height(200.)
+                .x_axis_label("lag (s)")
+                .y_axis_label("correlation")
+                .include_y(-1.)
+                .include_y(1.)
+                .show(ui, |plot_ui| {
+                    plot_ui.hline(HLine::new(0.));
+                    plot_ui.line(Line::new(points));
+                });
+        });
+}
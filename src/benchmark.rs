@@ -0,0 +1,302 @@
+use std::{fmt::Write as _, fs, path::PathBuf};
+
+use bevy::{
+    diagnostic::{
+        DiagnosticPath, DiagnosticsStore, EntityCountDiagnosticsPlugin, FrameTimeDiagnosticsPlugin,
+        SystemInformationDiagnosticsPlugin,
+    },
+    prelude::*,
+    window::{PresentMode, PrimaryWindow},
+};
+use serde::Serialize;
+
+use crate::{gui, segment_budget::SEGMENT_COUNT, validation::MAX_TRAILS, Configuration};
+
+/// Segment counts the stages run with, in order.
+const STAGES: [u32; 4] = [100, 1_000, 10_000, 100_000];
+/// Segments each head contributes at most, so more segments also means more heads.
+const SEGMENTS_PER_HEAD: u32 = 100;
+/// Real seconds a stage may take to fill up to its segment count before measuring anyway.
+const WARMUP_TIMEOUT: f32 = 15.;
+/// Real seconds after filling up before measuring, for the frame times to settle.
+const SETTLE_TIME: f32 = 1.;
+
+/// Runs the windowed simulation through stages of increasing trail segment counts, measures
+/// each for `seconds` of real time and writes a report to `report`, as JSON for a `.json` path
+/// and Markdown otherwise, then exits. Vsync is turned off so frame times aren't capped by the
+/// display. Only whole frames are timed, CPU and memory are those of the whole process.
+pub struct BenchmarkPlugin {
+    pub seconds: f32,
+    pub report: PathBuf,
+}
+
+impl Plugin for BenchmarkPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Benchmark {
+            seconds: self.seconds,
+            report: self.report.clone(),
+            stage: None,
+            phase_started: 0.,
+            measuring: false,
+            samples: Vec::new(),
+            results: Vec::new(),
+        })
+        .add_systems(Startup, disable_vsync)
+        .add_systems(Update, run_benchmark);
+    }
+}
+
+#[derive(Resource)]
+struct Benchmark {
+    seconds: f32,
+    report: PathBuf,
+    /// Index into [`STAGES`], `None` before the first stage started.
+    stage: Option<usize>,
+    /// Real time the current warm-up or measurement started at.
+    phase_started: f32,
+    measuring: bool,
+    samples: Vec<Sample>,
+    results: Vec<StageResult>,
+}
+
+/// Latest values of the diagnostics in one frame.
+struct Sample {
+    frame_time: f64,
+    segments: Option<f64>,
+    entities: Option<f64>,
+    cpu: Option<f64>,
+    memory: Option<f64>,
+}
+
+#[derive(Serialize)]
+struct StageResult {
+    target_segments: u32,
+    heads: u16,
+    frames: usize,
+    mean_segments: Option<f64>,
+    mean_entities: Option<f64>,
+    mean_fps: f64,
+    /// Milliseconds.
+    frame_time_mean: f64,
+    frame_time_p50: f64,
+    frame_time_p95: f64,
+    frame_time_p99: f64,
+    frame_time_max: f64,
+    /// Percent of one core.
+    process_cpu: Option<f64>,
+    /// GiB.
+    process_memory: Option<f64>,
+}
+
+#[derive(Serialize)]
+struct Report<'a> {
+    seconds_per_stage: f32,
+    trail_style: String,
+    instanced_heads: bool,
+    physics_refresh_rate: u16,
+    stages: &'a [StageResult],
+}
+
+fn disable_vsync(mut windows: Query<&mut Window, With<PrimaryWindow>>) {
+    for mut window in &mut windows {
+        window.present_mode = PresentMode::AutoNoVsync;
+    }
+}
+
+fn run_benchmark(world: &mut World) {
+    let now = world.resource::<Time<Real>>().elapsed_secs();
+    let segments = latest(world, &SEGMENT_COUNT);
+    let benchmark = world.resource::<Benchmark>();
+
+    let Some(stage) = benchmark.stage else {
+        start_stage(world, 0, now);
+        return;
+    };
+    let target = STAGES[stage];
+    let elapsed = now - benchmark.phase_started;
+
+    if !benchmark.measuring {
+        let filled = segments.is_some_and(|segments| segments >= target as f64);
+        if (filled && elapsed >= SETTLE_TIME) || elapsed >= WARMUP_TIMEOUT {
+            if !filled {
+                warn!("Stage with {target} segments didn't fill up, measuring anyway");
+            }
+            let mut benchmark = world.resource_mut::<Benchmark>();
+            benchmark.measuring = true;
+            benchmark.phase_started = now;
+        }
+        return;
+    }
+
+    if elapsed < benchmark.seconds {
+        let sample = Sample {
+            frame_time: latest(world, &FrameTimeDiagnosticsPlugin::FRAME_TIME).unwrap_or(0.),
+            segments,
+            entities: latest(world, &EntityCountDiagnosticsPlugin::ENTITY_COUNT),
+            cpu: latest(
+                world,
+                &SystemInformationDiagnosticsPlugin::PROCESS_CPU_USAGE,
+            ),
+            memory: latest(
+                world,
+                &SystemInformationDiagnosticsPlugin::PROCESS_MEM_USAGE,
+            ),
+        };
+        world.resource_mut::<Benchmark>().samples.push(sample);
+        return;
+    }
+
+    let heads = world.resource::<Configuration>().num_of_trails;
+    let mut benchmark = world.resource_mut::<Benchmark>();
+    let samples = std::mem::take(&mut benchmark.samples);
+    let result = summarize(target, heads, &samples);
+    info!(
+        "{target} segments: {:.2} ms mean frame time, {:.2} ms at the 99th percentile",
+        result.frame_time_mean, result.frame_time_p99
+    );
+    benchmark.results.push(result);
+
+    if stage + 1 < STAGES.len() {
+        start_stage(world, stage + 1, now);
+        return;
+    }
+
+    let result = write_report(world);
+    match &result {
+        Ok(path) => info!("Wrote the benchmark report to {}", path.display()),
+        Err(err) => error!("Could not write the benchmark report: {err}"),
+    }
+    world.send_event(match result {
+        Ok(_) => AppExit::Success,
+        Err(_) => AppExit::error(),
+    });
+}
+
+fn latest(world: &World, path: &DiagnosticPath) -> Option<f64> {
+    world
+        .resource::<DiagnosticsStore>()
+        .get(path)
+        .and_then(|diagnostic| diagnostic.value())
+}
+
+/// Restarts the scene with enough heads to reach the stage's segment count, which the segment
+/// budget then holds it at. The lifetime is long enough for no segment to expire on its own.
+fn start_stage(world: &mut World, stage: usize, now: f32) {
+    let target = STAGES[stage];
+    let mut config = world.resource_mut::<Configuration>();
+    config.num_of_trails = target
+        .div_ceil(SEGMENTS_PER_HEAD)
+        .clamp(1, MAX_TRAILS as u32) as u16;
+    config.max_segments = target;
+    config.trail_lifetime = u16::MAX;
+    gui::clear(world);
+    gui::start(world);
+
+    let mut benchmark = world.resource_mut::<Benchmark>();
+    benchmark.stage = Some(stage);
+    benchmark.phase_started = now;
+    benchmark.measuring = false;
+    info!("Benchmarking {target} segments");
+}
+
+fn summarize(target: u32, heads: u16, samples: &[Sample]) -> StageResult {
+    let mut frame_times: Vec<f64> = samples.iter().map(|sample| sample.frame_time).collect();
+    frame_times.sort_by(f64::total_cmp);
+    let percentile = |p: f64| {
+        let index = ((frame_times.len() as f64 - 1.) * p).round() as usize;
+        frame_times.get(index).copied().unwrap_or(0.)
+    };
+    let frame_time_mean = mean(frame_times.iter().copied()).unwrap_or(0.);
+
+    StageResult {
+        target_segments: target,
+        heads,
+        frames: samples.len(),
+        mean_segments: mean(samples.iter().filter_map(|sample| sample.segments)),
+        mean_entities: mean(samples.iter().filter_map(|sample| sample.entities)),
+        mean_fps: if frame_time_mean > 0. {
+            1000. / frame_time_mean
+        } else {
+            0.
+        },
+        frame_time_mean,
+        frame_time_p50: percentile(0.5),
+        frame_time_p95: percentile(0.95),
+        frame_time_p99: percentile(0.99),
+        frame_time_max: frame_times.last().copied().unwrap_or(0.),
+        process_cpu: mean(samples.iter().filter_map(|sample| sample.cpu)),
+        process_memory: mean(samples.iter().filter_map(|sample| sample.memory)),
+    }
+}
+
+fn mean(values: impl Iterator<Item = f64>) -> Option<f64> {
+    let (sum, count) = values.fold((0., 0), |(sum, count), value| (sum + value, count + 1));
+    (count > 0).then(|| sum / count as f64)
+}
+
+fn write_report(world: &World) -> Result<PathBuf, String> {
+    let benchmark = world.resource::<Benchmark>();
+    let config = world.resource::<Configuration>();
+    let report = Report {
+        seconds_per_stage: benchmark.seconds,
+        trail_style: format!("{:?}", config.trail_style),
+        instanced_heads: config.instanced_heads,
+        physics_refresh_rate: config.physics_refresh_rate,
+        stages: &benchmark.results,
+    };
+
+    let content = if benchmark
+        .report
+        .extension()
+        .is_some_and(|extension| extension == "json")
+    {
+        serde_json::to_string_pretty(&report).map_err(|err| err.to_string())?
+    } else {
+        markdown(&report)
+    };
+    fs::write(&benchmark.report, content).map_err(|err| err.to_string())?;
+    Ok(benchmark.report.clone())
+}
+
+fn markdown(report: &Report) -> String {
+    let optional = |value: Option<f64>, decimals: usize| {
+        value.map_or("-".to_string(), |value| format!("{value:.decimals$}"))
+    };
+
+    let mut out = String::new();
+    let _ = writeln!(out, "# Benchmark\n");
+    let _ = writeln!(
+        out,
+        "{} s per stage, trail style {}, instanced heads {}, {} physics ticks per second.\n",
+        report.seconds_per_stage,
+        report.trail_style,
+        if report.instanced_heads { "on" } else { "off" },
+        report.physics_refresh_rate,
+    );
+    let _ = writeln!(
+        out,
+        "| Segments | Heads | Frames | Mean segments | Entities | FPS | Mean (ms) | p50 (ms) \
+         | p95 (ms) | p99 (ms) | Max (ms) | Process CPU (%) | Process memory (GiB) |"
+    );
+    let _ = writeln!(out, "|{}", "---:|".repeat(13));
+    for stage in report.stages {
+        let _ = writeln!(
+            out,
+            "| {} | {} | {} | {} | {} | {:.1} | {:.2} | {:.2} | {:.2} | {:.2} | {:.2} | {} | {} |",
+            stage.target_segments,
+            stage.heads,
+            stage.frames,
+            optional(stage.mean_segments, 0),
+            optional(stage.mean_entities, 0),
+            stage.mean_fps,
+            stage.frame_time_mean,
+            stage.frame_time_p50,
+            stage.frame_time_p95,
+            stage.frame_time_p99,
+            stage.frame_time_max,
+            optional(stage.process_cpu, 1),
+            optional(stage.process_memory, 2),
+        );
+    }
+    out
+}
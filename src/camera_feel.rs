@@ -0,0 +1,99 @@
+use bevy::prelude::*;
+use bevy_egui::egui;
+use bevy_panorbit_camera::PanOrbitCamera;
+
+use crate::Configuration;
+
+pub struct CameraFeelPlugin;
+
+impl Plugin for CameraFeelPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            apply_camera_feel.run_if(|config: Res<Configuration>| config.is_changed()),
+        );
+    }
+}
+
+/// How the orbit camera responds to input.
+#[derive(Clone, Copy, PartialEq)]
+pub struct CameraFeel {
+    pub orbit_sensitivity: f32,
+    pub orbit_smoothness: f32,
+    pub pan_smoothness: f32,
+    pub zoom_sensitivity: f32,
+    pub zoom_smoothness: f32,
+}
+
+impl CameraFeel {
+    /// The defaults of the orbit camera, quick but twitchy.
+    pub const RESPONSIVE: Self = Self {
+        orbit_sensitivity: 1.,
+        orbit_smoothness: 0.1,
+        pan_smoothness: 0.02,
+        zoom_sensitivity: 1.,
+        zoom_smoothness: 0.1,
+    };
+
+    /// Slow, heavily damped movement that keeps gliding after input stops, for recordings.
+    pub const CINEMATIC: Self = Self {
+        orbit_sensitivity: 0.4,
+        orbit_smoothness: 0.9,
+        pan_smoothness: 0.85,
+        zoom_sensitivity: 0.5,
+        zoom_smoothness: 0.9,
+    };
+
+    fn of(config: &Configuration) -> Self {
+        Self {
+            orbit_sensitivity: config.orbit_sensitivity,
+            orbit_smoothness: config.orbit_smoothness,
+            pan_smoothness: config.pan_smoothness,
+            zoom_sensitivity: config.zoom_sensitivity,
+            zoom_smoothness: config.zoom_smoothness,
+        }
+    }
+
+    pub fn apply_to(self, config: &mut Configuration) {
+        config.orbit_sensitivity = self.orbit_sensitivity;
+        config.orbit_smoothness = self.orbit_smoothness;
+        config.pan_smoothness = self.pan_smoothness;
+        config.zoom_sensitivity = self.zoom_sensitivity;
+        config.zoom_smoothness = self.zoom_smoothness;
+    }
+}
+
+fn apply_camera_feel(mut cameras: Query<&mut PanOrbitCamera>, config: Res<Configuration>) {
+    let feel = CameraFeel::of(&config);
+    for mut camera in &mut cameras {
+        camera.orbit_sensitivity = feel.orbit_sensitivity;
+        camera.orbit_smoothness = feel.orbit_smoothness;
+        camera.pan_smoothness = feel.pan_smoothness;
+        camera.zoom_sensitivity = feel.zoom_sensitivity;
+        camera.zoom_smoothness = feel.zoom_smoothness;
+    }
+}
+
+pub fn camera_feel_ui(world: &mut World, ui: &mut egui::Ui) {
+    let config = world.resource::<Configuration>();
+    let mut feel = CameraFeel::of(config);
+
+    ui.add(egui::Slider::new(&mut feel.orbit_sensitivity, 0.1..=3.).text("Orbit sensitivity"));
+    ui.add(egui::Slider::new(&mut feel.orbit_smoothness, 0.0..=0.99).text("Rotation inertia"));
+    ui.add(egui::Slider::new(&mut feel.pan_smoothness, 0.0..=0.99).text("Pan smoothing"));
+    ui.add(egui::Slider::new(&mut feel.zoom_sensitivity, 0.1..=3.).text("Zoom sensitivity"));
+    ui.add(egui::Slider::new(&mut feel.zoom_smoothness, 0.0..=0.99).text("Zoom smoothing"));
+
+    ui.horizontal(|ui| {
+        if ui.button("Responsive").clicked() {
+            feel = CameraFeel::RESPONSIVE;
+        }
+        if ui.button("Cinematic damping").clicked() {
+            feel = CameraFeel::CINEMATIC;
+        }
+    });
+
+    if feel != CameraFeel::of(config) {
+        feel.apply_to(&mut world.resource_mut::<Configuration>());
+    }
+}
@@ -0,0 +1,58 @@
+use bevy::prelude::*;
+use bevy_panorbit_camera::{PanOrbitCamera, PanOrbitCameraSystemSet};
+
+use crate::Configuration;
+
+pub struct CameraFollowPlugin;
+
+impl Plugin for CameraFollowPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, follow_head.before(PanOrbitCameraSystemSet));
+    }
+}
+
+/// Makes the orbit camera focus on this head instead of a fixed point. At most one head
+/// carries it, see [`follow`].
+#[derive(Component)]
+pub struct CameraFollowed;
+
+/// Moves the marker to `head`, or removes it so the camera orbits freely from where it is.
+pub fn follow(world: &mut World, head: Option<Entity>) {
+    let followed: Vec<Entity> = world
+        .query_filtered::<Entity, With<CameraFollowed>>()
+        .iter(world)
+        .collect();
+    for entity in followed {
+        if let Ok(mut entity) = world.get_entity_mut(entity) {
+            entity.remove::<CameraFollowed>();
+        }
+    }
+    // The head may have been despawned since it was picked.
+    if let Some(Ok(mut head)) = head.map(|head| world.get_entity_mut(head)) {
+        head.insert(CameraFollowed);
+    }
+}
+
+fn follow_head(
+    heads: Query<&GlobalTransform, With<CameraFollowed>>,
+    mut cameras: Query<&mut PanOrbitCamera>,
+    time: Res<Time<Real>>,
+    config: Res<Configuration>,
+) {
+    let Ok(head) = heads.get_single() else {
+        return;
+    };
+    let position = head.translation();
+
+    for mut camera in &mut cameras {
+        if config.follow_smoothness > 0. {
+            // Independent of the frame rate, the same fraction of the gap closes per 1/60 s.
+            let remaining = config.follow_smoothness.powf(time.delta_secs() * 60.);
+            camera.target_focus = position.lerp(camera.target_focus, remaining);
+        } else {
+            camera.target_focus = position;
+            camera.focus = position;
+            camera.force_update = true;
+        }
+    }
+}
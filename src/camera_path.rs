@@ -0,0 +1,415 @@
+use std::{fmt::Write as _, fs, path::Path};
+
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+use bevy_panorbit_camera::{PanOrbitCamera, PanOrbitCameraSystemSet};
+use ron::ser::PrettyConfig;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    file_dialog::{self, DialogKind},
+    TimeOfBirth,
+};
+
+/// Seconds between two recorded keyframes.
+const RECORD_INTERVAL: f32 = 1. / 30.;
+/// Frame rate of the exported Blender animation.
+const BLENDER_FPS: f32 = 30.;
+
+pub struct CameraPathPlugin;
+
+impl Plugin for CameraPathPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CameraPath>()
+            .init_resource::<CameraPathPlayer>()
+            .add_systems(
+                Update,
+                (
+                    record_camera_path.after(PanOrbitCameraSystemSet),
+                    play_camera_path.after(PanOrbitCameraSystemSet),
+                    camera_path_ui,
+                ),
+            );
+    }
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct CameraKeyframe {
+    /// Seconds since the start of the path.
+    pub time: f32,
+    pub translation: Vec3,
+    pub rotation: Quat,
+}
+
+/// Camera flight recorded from the viewport, replayable and exportable.
+#[derive(Resource, Clone, Debug, Default, Serialize, Deserialize)]
+pub struct CameraPath {
+    pub keyframes: Vec<CameraKeyframe>,
+}
+
+impl CameraPath {
+    pub fn duration(&self) -> f32 {
+        self.keyframes.last().map_or(0., |keyframe| keyframe.time)
+    }
+
+    /// Interpolated camera pose at `time`, clamped to the ends of the path.
+    pub fn sample(&self, time: f32) -> Option<Transform> {
+        let next = self
+            .keyframes
+            .iter()
+            .position(|keyframe| keyframe.time >= time)
+            .unwrap_or(self.keyframes.len().checked_sub(1)?);
+        let b = self.keyframes[next];
+        let a = self.keyframes[next.saturating_sub(1)];
+
+        let span = b.time - a.time;
+        let s = if span > 0. {
+            ((time - a.time) / span).clamp(0., 1.)
+        } else {
+            1.
+        };
+        Some(
+            Transform::from_translation(a.translation.lerp(b.translation, s))
+                .with_rotation(a.rotation.slerp(b.rotation, s)),
+        )
+    }
+
+    /// Writes the path as JSON if the extension is `.json`, otherwise as RON.
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let content = if is_json(path) {
+            serde_json::to_string_pretty(self).map_err(|err| err.to_string())?
+        } else {
+            ron::ser::to_string_pretty(self, PrettyConfig::default())
+                .map_err(|err| err.to_string())?
+        };
+        fs::write(path, content).map_err(|err| format!("{}: {err}", path.display()))
+    }
+
+    /// Reads a path written by [`CameraPath::save`] or authored by hand.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let content =
+            fs::read_to_string(path).map_err(|err| format!("{}: {err}", path.display()))?;
+        let camera_path: Self = if is_json(path) {
+            serde_json::from_str(&content).map_err(|err| format!("{}: {err}", path.display()))?
+        } else {
+            ron::from_str(&content).map_err(|err| format!("{}: {err}", path.display()))?
+        };
+
+        if camera_path.keyframes.is_empty() {
+            return Err(format!("{}: no keyframes", path.display()));
+        }
+        if camera_path
+            .keyframes
+            .iter()
+            .any(|keyframe| !keyframe.time.is_finite())
+            || camera_path
+                .keyframes
+                .windows(2)
+                .any(|pair| pair[0].time > pair[1].time)
+        {
+            return Err(format!(
+                "{}: keyframe times must be ascending",
+                path.display()
+            ));
+        }
+        Ok(camera_path)
+    }
+}
+
+fn is_json(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension == "json")
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+enum PlayerState {
+    #[default]
+    Idle,
+    /// Real time at which recording started.
+    Recording(f32),
+    /// Real time at which playback started.
+    Playing(f32),
+}
+
+#[derive(Resource)]
+struct CameraPathPlayer {
+    state: PlayerState,
+    file_path: String,
+    export_path: String,
+    export_trails: bool,
+    status: Option<Result<String, String>>,
+}
+
+impl Default for CameraPathPlayer {
+    fn default() -> Self {
+        Self {
+            state: PlayerState::Idle,
+            file_path: "camera_path.ron".into(),
+            export_path: "camera_path.py".into(),
+            export_trails: false,
+            status: None,
+        }
+    }
+}
+
+fn record_camera_path(
+    player: Res<CameraPathPlayer>,
+    mut path: ResMut<CameraPath>,
+    cameras: Query<&Transform, With<PanOrbitCamera>>,
+    time: Res<Time<Real>>,
+) {
+    let PlayerState::Recording(start) = player.state else {
+        return;
+    };
+    let Ok(transform) = cameras.get_single() else {
+        return;
+    };
+
+    let now = time.elapsed_secs() - start;
+    if path
+        .keyframes
+        .last()
+        .is_some_and(|last| now - last.time < RECORD_INTERVAL)
+    {
+        return;
+    }
+    path.keyframes.push(CameraKeyframe {
+        time: now,
+        translation: transform.translation,
+        rotation: transform.rotation,
+    });
+}
+
+/// Drives the camera along the path. The orbit controls are suspended meanwhile, since they
+/// would otherwise fight over the camera transform.
+fn play_camera_path(
+    mut player: ResMut<CameraPathPlayer>,
+    path: Res<CameraPath>,
+    mut cameras: Query<(&mut Transform, &mut PanOrbitCamera)>,
+    time: Res<Time<Real>>,
+) {
+    let PlayerState::Playing(start) = player.state else {
+        return;
+    };
+    let Ok((mut transform, mut pan_orbit)) = cameras.get_single_mut() else {
+        return;
+    };
+
+    let now = time.elapsed_secs() - start;
+    match path.sample(now) {
+        Some(pose) if now <= path.duration() => {
+            pan_orbit.enabled = false;
+            *transform = pose;
+        }
+        _ => {
+            pan_orbit.enabled = true;
+            pan_orbit.force_update = true;
+            player.state = PlayerState::Idle;
+        }
+    }
+}
+
+fn camera_path_ui(
+    mut contexts: EguiContexts,
+    mut player: ResMut<CameraPathPlayer>,
+    mut path: ResMut<CameraPath>,
+    cameras: Query<&Projection, With<PanOrbitCamera>>,
+    segments: Query<&Transform, With<TimeOfBirth>>,
+    time: Res<Time<Real>>,
+) {
+    let player = &mut *player;
+
+    egui::Window::new("Camera path")
+        .default_open(false)
+        .show(contexts.ctx_mut(), |ui| {
+            ui.label(format!(
+                "{} keyframes, {:.1} s",
+                path.keyframes.len(),
+                path.duration()
+            ));
+
+            ui.horizontal(|ui| match player.state {
+                PlayerState::Idle => {
+                    if ui.button("Record").clicked() {
+                        path.keyframes.clear();
+                        player.state = PlayerState::Recording(time.elapsed_secs());
+                    }
+                    if ui
+                        .add_enabled(!path.keyframes.is_empty(), egui::Button::new("Play"))
+                        .clicked()
+                    {
+                        player.state = PlayerState::Playing(time.elapsed_secs());
+                    }
+                }
+                PlayerState::Recording(_) | PlayerState::Playing(_) => {
+                    if ui.button("Stop").clicked() {
+                        // Hands the camera back to the orbit controls on the next frame.
+                        player.state = PlayerState::Playing(f32::NEG_INFINITY);
+                    }
+                }
+            });
+
+            ui.separator();
+
+            ui.label("Path file (.ron or .json)");
+            ui.text_edit_singleline(&mut player.file_path);
+            ui.horizontal(|ui| {
+                if ui
+                    .add_enabled(!path.keyframes.is_empty(), egui::Button::new("Save…"))
+                    .clicked()
+                {
+                    if let Some(file) = file_dialog::pick_file(
+                        DialogKind::Save,
+                        &player.file_path,
+                        file_dialog::CAMERA_PATH,
+                    ) {
+                        player.status = Some(
+                            path.save(&file)
+                                .map(|()| format!("Saved {}", file.display())),
+                        );
+                        player.file_path = file.display().to_string();
+                    }
+                }
+                if ui
+                    .add_enabled(
+                        player.state == PlayerState::Idle,
+                        egui::Button::new("Load…"),
+                    )
+                    .clicked()
+                {
+                    if let Some(file) = file_dialog::pick_file(
+                        DialogKind::Open,
+                        &player.file_path,
+                        file_dialog::CAMERA_PATH,
+                    ) {
+                        player.status = Some(CameraPath::load(&file).map(|loaded| {
+                            *path = loaded;
+                            format!("Loaded {}", file.display())
+                        }));
+                        player.file_path = file.display().to_string();
+                    }
+                }
+            });
+
+            ui.separator();
+
+            ui.label("Blender script (.py)");
+            file_dialog::path_field(
+                ui,
+                &mut player.export_path,
+                DialogKind::Save,
+                file_dialog::PYTHON,
+            );
+            ui.checkbox(&mut player.export_trails, "Include trails (.obj)");
+            if ui
+                .add_enabled(!path.keyframes.is_empty(), egui::Button::new("Export"))
+                .clicked()
+            {
+                let fov = match cameras.get_single() {
+                    Ok(Projection::Perspective(perspective)) => perspective.fov,
+                    _ => PerspectiveProjection::default().fov,
+                };
+                let script = Path::new(&player.export_path);
+                let trails = player.export_trails.then(|| script.with_extension("obj"));
+                player.status = Some(
+                    trails
+                        .as_deref()
+                        .map_or(Ok(()), |trails| export_obj(trails, &segments))
+                        .and_then(|()| export_blender(script, &path, fov, trails.as_deref()))
+                        .map(|()| format!("Exported {}", script.display())),
+                );
+            }
+
+            match player.status.as_ref() {
+                Some(Ok(message)) => {
+                    ui.label(message);
+                }
+                Some(Err(err)) => {
+                    ui.colored_label(egui::Color32::RED, err);
+                }
+                None => {}
+            }
+        });
+}
+
+/// Writes a Python script that recreates the path as an animated camera when run from
+/// Blender's text editor, optionally importing the trails exported by [`export_obj`].
+///
+/// Both programs use right-handed, Z-up world coordinates here, and cameras look down their
+/// local -Z axis, so poses carry over unchanged.
+pub fn export_blender(
+    script: &Path,
+    path: &CameraPath,
+    fov: f32,
+    trails: Option<&Path>,
+) -> Result<(), String> {
+    let mut keyframes = String::new();
+    for keyframe in &path.keyframes {
+        let frame = (keyframe.time * BLENDER_FPS).round() as i32 + 1;
+        let Vec3 { x, y, z } = keyframe.translation;
+        let [qx, qy, qz, qw] = keyframe.rotation.to_array();
+        writeln!(
+            keyframes,
+            "    ({frame}, ({x}, {y}, {z}), ({qw}, {qx}, {qy}, {qz})),"
+        )
+        .expect("writing to a String cannot fail");
+    }
+
+    let import_trails = trails.map_or(String::new(), |trails| {
+        format!(
+            "bpy.ops.wm.obj_import(filepath={:?}, forward_axis='Y', up_axis='Z')\n",
+            trails.display().to_string()
+        )
+    });
+
+    let content = format!(
+        r#"import bpy
+
+scene = bpy.context.scene
+scene.render.fps = {fps}
+
+data = bpy.data.cameras.new("LorenzCamera")
+data.sensor_fit = 'VERTICAL'
+data.angle_y = {fov}
+camera = bpy.data.objects.new("LorenzCamera", data)
+camera.rotation_mode = 'QUATERNION'
+scene.collection.objects.link(camera)
+scene.camera = camera
+
+keyframes = [
+{keyframes}]
+for frame, location, rotation in keyframes:
+    camera.location = location
+    camera.rotation_quaternion = rotation
+    camera.keyframe_insert("location", frame=frame)
+    camera.keyframe_insert("rotation_quaternion", frame=frame)
+
+scene.frame_start = keyframes[0][0]
+scene.frame_end = keyframes[-1][0]
+{import_trails}"#,
+        fps = BLENDER_FPS,
+    );
+
+    fs::write(script, content).map_err(|err| format!("{}: {err}", script.display()))
+}
+
+/// Writes every trail segment as a two-vertex line element of a Wavefront OBJ file.
+pub fn export_obj(
+    path: &Path,
+    segments: &Query<&Transform, With<TimeOfBirth>>,
+) -> Result<(), String> {
+    let mut content = String::new();
+    for transform in segments {
+        // Segments are unit cylinders anchored at their bottom, stretched along local Y.
+        let start = transform.translation;
+        let end = transform.transform_point(Vec3::Y);
+        writeln!(
+            content,
+            "v {} {} {}\nv {} {} {}\nl -2 -1",
+            start.x, start.y, start.z, end.x, end.y, end.z
+        )
+        .expect("writing to a String cannot fail");
+    }
+
+    fs::write(path, content).map_err(|err| format!("{}: {err}", path.display()))
+}
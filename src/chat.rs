@@ -0,0 +1,285 @@
+use std::{
+    io::{ErrorKind, Read, Write},
+    net::TcpStream,
+    ops::RangeInclusive,
+    thread::{self, JoinHandle},
+};
+
+use bevy::prelude::*;
+use bevy_egui::egui;
+
+use crate::{gui, Configuration};
+
+/// Anonymous read-only logins are accepted by Twitch's IRC gateway.
+const DEFAULT_SERVER: &str = "irc.chat.twitch.tv:6667";
+const DEFAULT_NICK: &str = "justinfan4815";
+
+const SIGMA_BOUNDS: RangeInclusive<f32> = 1.0..=30.0;
+const RHO_BOUNDS: RangeInclusive<f32> = 0.5..=60.0;
+const BETA_BOUNDS: RangeInclusive<f32> = 0.1..=8.0;
+/// Viewers can restart the trails at most this often, in seconds.
+const CLEAR_COOLDOWN: f32 = 10.;
+/// Longest line kept, in bytes. IRC allows 512 plus 8191 for Twitch's message tags, anything
+/// longer is dropped.
+const MAX_LINE_LENGTH: usize = 8704;
+
+pub struct ChatPlugin;
+
+impl Plugin for ChatPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ChatControl>()
+            .add_systems(Update, apply_chat_commands);
+    }
+}
+
+/// Lets viewers of an IRC (or Twitch) channel steer the attractor with `!sigma`, `!rho`,
+/// `!beta` followed by `up`/`down` and restart it with `!clear`.
+#[derive(Resource)]
+pub struct ChatControl {
+    server: String,
+    nick: String,
+    channel: String,
+    /// Connects and joins the channel off the main thread, as resolving and connecting can take
+    /// seconds.
+    connecting: Option<JoinHandle<Result<TcpStream, String>>>,
+    stream: Option<TcpStream>,
+    buffer: Vec<u8>,
+    /// Whether the line being received was too long and is skipped up to its end.
+    skipping_line: bool,
+    last_clear: f32,
+    last_command: Option<String>,
+    status: Option<String>,
+}
+
+impl Default for ChatControl {
+    fn default() -> Self {
+        Self {
+            server: DEFAULT_SERVER.into(),
+            nick: DEFAULT_NICK.into(),
+            channel: String::new(),
+            connecting: None,
+            stream: None,
+            buffer: Vec::new(),
+            skipping_line: false,
+            last_clear: f32::NEG_INFINITY,
+            last_command: None,
+            status: None,
+        }
+    }
+}
+
+impl ChatControl {
+    fn connect(&mut self) -> Result<(), String> {
+        let channel = self.channel.trim().trim_start_matches('#').to_lowercase();
+        if channel.is_empty() {
+            return Err("No channel given".into());
+        }
+
+        let (server, nick) = (self.server.clone(), self.nick.clone());
+        let connecting = thread::Builder::new()
+            .name("chat connect".into())
+            .spawn(move || {
+                let mut stream = TcpStream::connect(&server).map_err(|err| err.to_string())?;
+                write!(stream, "NICK {nick}\r\nJOIN #{channel}\r\n")
+                    .and_then(|()| stream.set_nonblocking(true))
+                    .map_err(|err| err.to_string())?;
+                Ok(stream)
+            })
+            .map_err(|err| err.to_string())?;
+
+        self.connecting = Some(connecting);
+        Ok(())
+    }
+
+    /// Takes over the stream once the connecting thread is done.
+    fn finish_connecting(&mut self) {
+        if !self
+            .connecting
+            .as_ref()
+            .is_some_and(JoinHandle::is_finished)
+        {
+            return;
+        }
+        let Some(connecting) = self.connecting.take() else {
+            return;
+        };
+        match connecting.join() {
+            Ok(Ok(stream)) => {
+                self.stream = Some(stream);
+                self.buffer.clear();
+                self.skipping_line = false;
+            }
+            Ok(Err(err)) => self.status = Some(err),
+            Err(_) => self.status = Some("Connecting failed".into()),
+        }
+    }
+
+    fn disconnect(&mut self) {
+        self.stream = None;
+    }
+
+    /// Reads whatever arrived since the last frame and returns the complete lines.
+    fn poll_lines(&mut self) -> Vec<String> {
+        self.finish_connecting();
+        let Some(stream) = self.stream.as_mut() else {
+            return Vec::new();
+        };
+
+        let mut chunk = [0; 4096];
+        let mut lines = Vec::new();
+        loop {
+            match stream.read(&mut chunk) {
+                Ok(0) => {
+                    self.status = Some("Connection closed by server".into());
+                    self.stream = None;
+                    break;
+                }
+                Ok(length) => {
+                    self.buffer.extend_from_slice(&chunk[..length]);
+                    take_lines(&mut self.buffer, &mut self.skipping_line, &mut lines);
+                }
+                Err(err) if err.kind() == ErrorKind::WouldBlock => break,
+                Err(err) => {
+                    self.status = Some(err.to_string());
+                    self.stream = None;
+                    break;
+                }
+            }
+        }
+        lines
+    }
+}
+
+/// Moves the complete lines out of `buffer`. Overlong lines are dropped as soon as they exceed
+/// [`MAX_LINE_LENGTH`], so a peer that never sends a newline can't grow the buffer.
+fn take_lines(buffer: &mut Vec<u8>, skipping_line: &mut bool, lines: &mut Vec<String>) {
+    while let Some(end) = buffer.iter().position(|&byte| byte == b'\n') {
+        let line: Vec<u8> = buffer.drain(..=end).collect();
+        if std::mem::take(skipping_line) || line.len() > MAX_LINE_LENGTH {
+            continue;
+        }
+        lines.push(String::from_utf8_lossy(&line).trim_end().to_string());
+    }
+    if buffer.len() > MAX_LINE_LENGTH {
+        buffer.clear();
+        *skipping_line = true;
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum ChatCommand {
+    Nudge(Parameter, f32),
+    Clear,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Parameter {
+    Sigma,
+    Rho,
+    Beta,
+}
+
+/// Extracts a command from a `PRIVMSG` line, ignoring all other traffic.
+fn parse_command(line: &str) -> Option<ChatCommand> {
+    let (_, message) = line.split_once(" PRIVMSG ")?.1.split_once(" :")?;
+    let mut words = message.split_whitespace();
+    let name = words.next()?.strip_prefix('!')?;
+
+    let (parameter, step) = match name.to_lowercase().as_str() {
+        "clear" => return Some(ChatCommand::Clear),
+        "sigma" => (Parameter::Sigma, 0.5),
+        "rho" => (Parameter::Rho, 1.),
+        "beta" => (Parameter::Beta, 0.1),
+        _ => return None,
+    };
+
+    match words.next()? {
+        "up" | "+" => Some(ChatCommand::Nudge(parameter, step)),
+        "down" | "-" => Some(ChatCommand::Nudge(parameter, -step)),
+        _ => None,
+    }
+}
+
+fn apply_chat_commands(world: &mut World) {
+    let lines = world.resource_mut::<ChatControl>().poll_lines();
+    let now = world.resource::<Time<Real>>().elapsed_secs();
+
+    for line in lines {
+        if let Some(server) = line.strip_prefix("PING ") {
+            let mut chat = world.resource_mut::<ChatControl>();
+            if let Some(stream) = chat.stream.as_mut() {
+                if let Err(err) = write!(stream, "PONG {server}\r\n") {
+                    chat.status = Some(err.to_string());
+                }
+            }
+            continue;
+        }
+
+        let Some(command) = parse_command(&line) else {
+            continue;
+        };
+
+        match command {
+            ChatCommand::Nudge(parameter, step) => {
+                let mut config = world.resource_mut::<Configuration>();
+                let (value, bounds) = match parameter {
+                    Parameter::Sigma => (&mut config.sigma, SIGMA_BOUNDS),
+                    Parameter::Rho => (&mut config.rho, RHO_BOUNDS),
+                    Parameter::Beta => (&mut config.beta, BETA_BOUNDS),
+                };
+                // Only the step is limited, a value set outside the bounds elsewhere moves
+                // towards them instead of jumping onto them.
+                *value =
+                    (*value + step).clamp(value.min(*bounds.start()), value.max(*bounds.end()));
+            }
+            ChatCommand::Clear => {
+                if now - world.resource::<ChatControl>().last_clear < CLEAR_COOLDOWN {
+                    continue;
+                }
+                world.resource_mut::<ChatControl>().last_clear = now;
+                gui::clear(world);
+                gui::start(world);
+            }
+        }
+
+        world.resource_mut::<ChatControl>().last_command = Some(format!("{command:?}"));
+    }
+}
+
+pub fn chat_ui(world: &mut World, ui: &mut egui::Ui) {
+    let mut chat = world.resource_mut::<ChatControl>();
+    let connected = chat.stream.is_some();
+    let connecting = chat.connecting.is_some();
+    let editable = !connected && !connecting;
+
+    egui::Grid::new("chat").num_columns(2).show(ui, |ui| {
+        ui.label("Server");
+        ui.add_enabled(editable, egui::TextEdit::singleline(&mut chat.server));
+        ui.end_row();
+
+        ui.label("Nick");
+        ui.add_enabled(editable, egui::TextEdit::singleline(&mut chat.nick));
+        ui.end_row();
+
+        ui.label("Channel");
+        ui.add_enabled(editable, egui::TextEdit::singleline(&mut chat.channel));
+        ui.end_row();
+    });
+
+    if connected {
+        if ui.button("Disconnect").clicked() {
+            chat.disconnect();
+        }
+    } else if connecting {
+        ui.add_enabled(false, egui::Button::new("Connecting…"));
+    } else if ui.button("Connect").clicked() {
+        chat.status = chat.connect().err();
+    }
+
+    if let Some(command) = chat.last_command.as_ref() {
+        ui.label(format!("Last command: {command}"));
+    }
+    if let Some(status) = chat.status.as_ref() {
+        ui.colored_label(egui::Color32::RED, status);
+    }
+}
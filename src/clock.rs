@@ -0,0 +1,118 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+
+use crate::{network, update_position, Configuration};
+
+/// Real seconds over which the rates are averaged.
+const RATE_WINDOW: f32 = 1.;
+/// Fraction of the configured tick rate, scaled by the time scale, below which the schedule counts as falling behind.
+const LAG_THRESHOLD: f64 = 0.9;
+
+pub struct ClockPlugin;
+
+impl Plugin for ClockPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SimulationClock>()
+            .add_systems(
+                FixedUpdate,
+                advance_simulation_clock
+                    .after(update_position)
+                    .run_if(network::simulation_is_local),
+            )
+            .add_systems(Update, (sample_clock, clock_ui).chain());
+    }
+}
+
+/// Integrated time of the Lorenz system, as opposed to the app's wall time.
+#[derive(Resource, Default)]
+pub struct SimulationClock {
+    pub simulated: f64,
+    pub ticks: u64,
+    /// Recent `(wall time, simulated time, ticks)` samples, oldest first.
+    samples: VecDeque<(f32, f64, u64)>,
+}
+
+impl SimulationClock {
+    /// Jumps to the given time, forgetting the samples so the rates don't see the jump.
+    pub fn reset_to(&mut self, simulated: f64, ticks: u64) {
+        self.simulated = simulated;
+        self.ticks = ticks;
+        self.samples.clear();
+    }
+
+    /// Simulated time per wall second and physics ticks per wall second.
+    fn rates(&self) -> Option<(f64, f64)> {
+        let (&(t0, simulated0, ticks0), &(t1, simulated1, ticks1)) =
+            (self.samples.front()?, self.samples.back()?);
+        let elapsed = (t1 - t0) as f64;
+        (elapsed > 0.).then(|| {
+            (
+                (simulated1 - simulated0) / elapsed,
+                (ticks1 - ticks0) as f64 / elapsed,
+            )
+        })
+    }
+}
+
+pub fn advance_simulation_clock(mut clock: ResMut<SimulationClock>, config: Res<Configuration>) {
+    clock.simulated += config.delta_t as f64 / 10000.;
+    clock.ticks += 1;
+}
+
+fn sample_clock(mut clock: ResMut<SimulationClock>, time: Res<Time<Real>>) {
+    let now = time.elapsed_secs();
+    let sample = (now, clock.simulated, clock.ticks);
+    clock.samples.push_back(sample);
+    while clock
+        .samples
+        .front()
+        .is_some_and(|&(t, _, _)| now - t > RATE_WINDOW)
+    {
+        clock.samples.pop_front();
+    }
+}
+
+fn clock_ui(
+    mut contexts: EguiContexts,
+    clock: Res<SimulationClock>,
+    real_time: Res<Time<Real>>,
+    virtual_time: Res<Time<Virtual>>,
+    config: Res<Configuration>,
+) {
+    egui::Window::new("Time")
+        .default_open(false)
+        .show(contexts.ctx_mut(), |ui| {
+            egui::Grid::new("time").num_columns(2).show(ui, |ui| {
+                ui.label("Simulated");
+                ui.label(format!("{:.2}", clock.simulated));
+                ui.end_row();
+
+                ui.label("Wall");
+                ui.label(format!("{:.2} s", real_time.elapsed_secs()));
+                ui.end_row();
+
+                if let Some((ratio, tick_rate)) = clock.rates() {
+                    // Slow motion and fast forward change the ticks per real second as well.
+                    let target_rate = config.physics_refresh_rate as f64 * config.time_scale as f64;
+                    ui.label("Ratio");
+                    ui.label(format!("{ratio:.3}× real time"));
+                    ui.end_row();
+
+                    ui.label("Tick rate");
+                    ui.label(format!("{tick_rate:.0} / {target_rate:.0} Hz"));
+                    ui.end_row();
+
+                    if !virtual_time.is_paused() && tick_rate < target_rate * LAG_THRESHOLD {
+                        ui.colored_label(
+                            egui::Color32::YELLOW,
+                            "Physics can't keep up with the configured refresh rate, the \
+                             simulation runs slower than intended.",
+                        );
+                        ui.end_row();
+                    }
+                }
+            });
+        });
+}
@@ -0,0 +1,350 @@
+use bevy::{prelude::*, utils::HashMap};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    attractor::Parameters,
+    instances::{instance_parameters, AttractorInstance},
+    Configuration, SimpleColorMaterial, TrailData, TrailHead,
+};
+
+/// Number of discrete colors trail segments can pick from in the gradient modes. Segments
+/// share these materials so they keep batching together.
+const PALETTE_SIZE: usize = 16;
+
+/// Fraction of the tracked speed and height ranges kept per frame, so the colors adapt when
+/// the attractor or its parameters change.
+const RANGE_DECAY: f32 = 0.999;
+
+const VIRIDIS: [Srgba; 5] = [
+    Srgba::rgb(0.267, 0.005, 0.329),
+    Srgba::rgb(0.231, 0.322, 0.545),
+    Srgba::rgb(0.129, 0.569, 0.549),
+    Srgba::rgb(0.369, 0.788, 0.384),
+    Srgba::rgb(0.992, 0.906, 0.145),
+];
+
+const PLASMA: [Srgba; 5] = [
+    Srgba::rgb(0.051, 0.031, 0.529),
+    Srgba::rgb(0.494, 0.012, 0.659),
+    Srgba::rgb(0.8, 0.278, 0.471),
+    Srgba::rgb(0.973, 0.584, 0.251),
+    Srgba::rgb(0.941, 0.976, 0.129),
+];
+
+pub struct ColoringPlugin;
+
+impl Plugin for ColoringPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, setup_trail_palette).add_systems(
+            Update,
+            (
+                apply_color_scheme.run_if(|config: Res<Configuration>| config.is_changed()),
+                (
+                    track_value_ranges,
+                    color_heads_by_speed.run_if(|config: Res<Configuration>| {
+                        config.color_scheme == ColorScheme::Speed
+                    }),
+                )
+                    .chain()
+                    .run_if(tracks_value_ranges),
+            ),
+        );
+    }
+}
+
+/// Colors of the heads, their trails and the gradient of the gradient coloring modes. Heads
+/// pick up a new scheme on the next start.
+#[derive(Reflect, Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum ColorScheme {
+    /// Evenly spaced hues, with a blue to red gradient.
+    #[default]
+    Rainbow,
+    Viridis,
+    Plasma,
+    /// Shades of one hue, in degrees.
+    SingleHue(f32),
+    /// Every head and its trail take the color of its current speed relative to the other
+    /// heads, slow is dark and fast is bright.
+    Speed,
+}
+
+impl ColorScheme {
+    /// Color of the head at `ratio`, wrapping around beyond 1 so any number of heads fits.
+    pub fn head_color(self, ratio: f32) -> Hsla {
+        let ratio = ratio.rem_euclid(1.);
+        match self {
+            ColorScheme::Rainbow => Hsla::hsl(ratio * 360., 0.7, 0.5),
+            ColorScheme::SingleHue(hue) => Hsla::hsl(hue, 0.7, 0.3 + 0.5 * ratio),
+            _ => self.gradient(ratio).into(),
+        }
+    }
+
+    /// Color of low values at 0 to high values at 1.
+    pub fn gradient(self, ratio: f32) -> Color {
+        let ratio = ratio.clamp(0., 1.);
+        match self {
+            ColorScheme::Rainbow => Hsla::hsl(240. * (1. - ratio), 0.8, 0.5).into(),
+            ColorScheme::Viridis | ColorScheme::Speed => sample_stops(&VIRIDIS, ratio).into(),
+            ColorScheme::Plasma => sample_stops(&PLASMA, ratio).into(),
+            ColorScheme::SingleHue(hue) => Hsla::hsl(hue, 0.8, 0.2 + 0.6 * ratio).into(),
+        }
+    }
+}
+
+/// Interpolates between evenly spaced `stops`.
+fn sample_stops(stops: &[Srgba], ratio: f32) -> Srgba {
+    let position = ratio * (stops.len() - 1) as f32;
+    let index = (position as usize).min(stops.len() - 2);
+    stops[index].mix(&stops[index + 1], position - index as f32)
+}
+
+/// How trail segment colors are chosen.
+#[derive(Reflect, Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TrailColoring {
+    /// Every head's trail keeps its own color.
+    #[default]
+    PerHead,
+    /// Tight turns take the high end of the scheme's gradient, straight stretches the low
+    /// end.
+    Curvature,
+    /// Strongly twisting stretches are high on the gradient, planar ones low.
+    Torsion,
+    /// Fast stretches are high on the gradient, slow ones low.
+    Speed,
+    /// Stretches are placed on the gradient by their z coordinate.
+    Height,
+}
+
+fn tracks_value_ranges(config: Res<Configuration>) -> bool {
+    matches!(
+        config.trail_coloring,
+        TrailColoring::Speed | TrailColoring::Height
+    ) || config.color_scheme == ColorScheme::Speed
+}
+
+/// Gradient of materials shared by all segments in the gradient coloring modes.
+#[derive(Resource)]
+pub struct TrailPalette {
+    materials: Vec<Handle<SimpleColorMaterial>>,
+    /// Speeds of the heads over the last few seconds, mapped onto the gradient.
+    speed: TrackedRange,
+    /// Heights of the heads over the last few seconds, mapped onto the gradient.
+    height: TrackedRange,
+}
+
+impl TrailPalette {
+    pub fn contains(&self, material: &Handle<SimpleColorMaterial>) -> bool {
+        self.materials.contains(material)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Handle<SimpleColorMaterial>> {
+        self.materials.iter()
+    }
+
+    /// Picks the material for `ratio` in `0..=1`.
+    fn sample(&self, ratio: f32) -> Handle<SimpleColorMaterial> {
+        let index = (ratio.clamp(0., 1.) * (self.materials.len() - 1) as f32).round() as usize;
+        self.materials[index].clone()
+    }
+
+    /// Picks the material of a new segment from `start` to `start + delta` and records its
+    /// direction in the head's history.
+    pub fn segment_material(
+        &self,
+        config: &Configuration,
+        parameters: &Parameters,
+        trail_data: &TrailData,
+        history: &mut SegmentHistory,
+        start: Vec3,
+        delta: Vec3,
+    ) -> Handle<SimpleColorMaterial> {
+        let [before_previous, previous] = history.0;
+        history.0 = [previous, delta];
+
+        let speed = || {
+            let velocity = config.attractor.velocity(config, parameters, start);
+            self.speed.ratio(velocity.length())
+        };
+        match config.trail_coloring {
+            // The head's own material changes with its speed, segments keep theirs.
+            TrailColoring::PerHead if config.color_scheme == ColorScheme::Speed => {
+                self.sample(speed())
+            }
+            TrailColoring::PerHead => trail_data.material.clone(),
+            TrailColoring::Curvature => self.sample(log_ratio(curvature(previous, delta))),
+            TrailColoring::Torsion => {
+                self.sample(log_ratio(torsion(before_previous, previous, delta)))
+            }
+            TrailColoring::Speed => self.sample(speed()),
+            TrailColoring::Height => self.sample(self.height.ratio(start.z + delta.z / 2.)),
+        }
+    }
+}
+
+/// Extent of a value over all heads. It widens at once and narrows slowly, so the colors
+/// don't flicker as heads pass through extremes.
+#[derive(Clone, Copy, Default)]
+struct TrackedRange {
+    min: f32,
+    max: f32,
+}
+
+impl TrackedRange {
+    fn track(&mut self, values: impl Iterator<Item = f32>) {
+        let (low, high) = values
+            .filter(|value| value.is_finite())
+            .fold((f32::INFINITY, f32::NEG_INFINITY), |(low, high), value| {
+                (low.min(value), high.max(value))
+            });
+        if low > high {
+            return;
+        }
+        let shrink = (self.max - self.min) * (1. - RANGE_DECAY) / 2.;
+        self.min = low.min(self.min + shrink);
+        self.max = high.max(self.max - shrink);
+    }
+
+    /// Maps `value` onto `0..=1`.
+    fn ratio(&self, value: f32) -> f32 {
+        if self.max <= self.min {
+            return 0.;
+        }
+        ((value - self.min) / (self.max - self.min)).clamp(0., 1.)
+    }
+}
+
+/// The two most recent segment directions of a trail head.
+#[derive(Component, Default)]
+pub struct SegmentHistory([Vec3; 2]);
+
+fn setup_trail_palette(
+    mut commands: Commands,
+    mut simple_color_materials: ResMut<Assets<SimpleColorMaterial>>,
+    config: Res<Configuration>,
+) {
+    let materials = (0..PALETTE_SIZE)
+        .map(|i| {
+            simple_color_materials.add(SimpleColorMaterial {
+                color: config.color_scheme.gradient(palette_ratio(i)).into(),
+                ..default()
+            })
+        })
+        .collect();
+    commands.insert_resource(TrailPalette {
+        materials,
+        speed: default(),
+        height: default(),
+    });
+}
+
+fn palette_ratio(index: usize) -> f32 {
+    index as f32 / (PALETTE_SIZE - 1) as f32
+}
+
+/// Recolors the shared palette when the scheme changes. Segments using it follow at once.
+fn apply_color_scheme(
+    palette: Res<TrailPalette>,
+    mut simple_color_materials: ResMut<Assets<SimpleColorMaterial>>,
+    mut applied: Local<Option<ColorScheme>>,
+    config: Res<Configuration>,
+) {
+    if *applied == Some(config.color_scheme) {
+        return;
+    }
+    *applied = Some(config.color_scheme);
+
+    for (i, handle) in palette.iter().enumerate() {
+        if let Some(material) = simple_color_materials.get_mut(handle) {
+            material.color = config.color_scheme.gradient(palette_ratio(i)).into();
+        }
+    }
+}
+
+/// Speed of a head under the parameters it follows, its instance's or the configured ones.
+fn head_speed(
+    config: &Configuration,
+    instances: &HashMap<Entity, (Parameters, Vec3)>,
+    transform: &Transform,
+    parent: Option<&Parent>,
+) -> f32 {
+    let parameters = parent
+        .and_then(|parent| instances.get(&parent.get()))
+        .map_or_else(|| Parameters::of(config), |(parameters, _)| *parameters);
+    config
+        .attractor
+        .velocity(config, &parameters, transform.translation)
+        .length()
+}
+
+fn track_value_ranges(
+    heads: Query<(&Transform, Option<&Parent>), With<TrailHead>>,
+    instances: Query<(Entity, &AttractorInstance, &Transform), Without<TrailHead>>,
+    mut palette: ResMut<TrailPalette>,
+    config: Res<Configuration>,
+) {
+    let instances = instance_parameters(&instances);
+    palette.speed.track(
+        heads
+            .iter()
+            .map(|(transform, parent)| head_speed(&config, &instances, transform, parent)),
+    );
+    palette
+        .height
+        .track(heads.iter().map(|(transform, _)| transform.translation.z));
+}
+
+fn color_heads_by_speed(
+    heads: Query<
+        (
+            &Transform,
+            Option<&Parent>,
+            &MeshMaterial3d<SimpleColorMaterial>,
+            &TrailData,
+        ),
+        With<TrailHead>,
+    >,
+    instances: Query<(Entity, &AttractorInstance, &Transform), Without<TrailHead>>,
+    palette: Res<TrailPalette>,
+    mut simple_color_materials: ResMut<Assets<SimpleColorMaterial>>,
+    config: Res<Configuration>,
+) {
+    let instances = instance_parameters(&instances);
+    for (transform, parent, head_material, trail_data) in &heads {
+        let speed = palette
+            .speed
+            .ratio(head_speed(&config, &instances, transform, parent));
+        let color = Hsla::from(config.color_scheme.gradient(speed));
+        if let Some(material) = simple_color_materials.get_mut(head_material) {
+            material.color = color.into();
+        }
+        if let Some(material) = simple_color_materials.get_mut(&trail_data.material) {
+            material.color = color.with_saturation(0.3).into();
+        }
+    }
+}
+
+/// Maps values between 10^-3 and 1 logarithmically onto `0..=1`.
+fn log_ratio(value: f32) -> f32 {
+    (value.max(f32::MIN_POSITIVE).log10() + 3.) / 3.
+}
+
+/// Discrete curvature: turning angle between two consecutive segments per unit length.
+fn curvature(previous: Vec3, current: Vec3) -> f32 {
+    let length = (previous.length() + current.length()) / 2.;
+    if length <= 0. || previous == Vec3::ZERO || current == Vec3::ZERO {
+        return 0.;
+    }
+    previous.angle_between(current) / length
+}
+
+/// Discrete torsion: rotation angle of the binormal between consecutive segment pairs per
+/// unit length.
+fn torsion(before_previous: Vec3, previous: Vec3, current: Vec3) -> f32 {
+    let first_binormal = before_previous.cross(previous);
+    let second_binormal = previous.cross(current);
+    let length = previous.length();
+    if length <= 0. || first_binormal == Vec3::ZERO || second_binormal == Vec3::ZERO {
+        return 0.;
+    }
+    first_binormal.angle_between(second_binormal) / length
+}
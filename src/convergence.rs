@@ -0,0 +1,165 @@
+use bevy::{
+    prelude::*,
+    tasks::{block_on, futures_lite::future, AsyncComputeTaskPool, Task},
+};
+use bevy_egui::{egui, EguiContexts};
+use egui_plot::{Legend, Line, Plot, PlotPoints};
+
+use crate::{
+    extensions::{Visualization, VisualizationSet},
+    integrator::Integrator,
+    velocity, Configuration,
+};
+
+pub struct ConvergencePanel;
+
+impl Visualization for ConvergencePanel {
+    fn name(&self) -> &'static str {
+        "Convergence study"
+    }
+
+    fn build(&self, app: &mut App, set: VisualizationSet) {
+        app.init_resource::<ConvergenceStudy>()
+            .add_systems(Update, convergence_ui.in_set(set));
+    }
+}
+
+/// Settings and results of the last convergence study.
+#[derive(Resource)]
+struct ConvergenceStudy {
+    integrator: Integrator,
+    refinements: u8,
+    duration: f32,
+    curves: Vec<(String, Vec<[f64; 2]>)>,
+    /// The study being integrated in the background, which can take seconds with many
+    /// refinements.
+    running: Option<Task<Vec<(String, Vec<[f64; 2]>)>>>,
+}
+
+impl Default for ConvergenceStudy {
+    fn default() -> Self {
+        Self {
+            integrator: Integrator::Euler,
+            refinements: 3,
+            duration: 10.,
+            curves: Vec::new(),
+            running: None,
+        }
+    }
+}
+
+fn convergence_ui(
+    mut contexts: EguiContexts,
+    mut study: ResMut<ConvergenceStudy>,
+    config: Res<Configuration>,
+) {
+    let study = &mut *study;
+    if let Some(curves) = study
+        .running
+        .as_mut()
+        .and_then(|task| block_on(future::poll_once(task)))
+    {
+        study.curves = curves;
+        study.running = None;
+    }
+
+    egui::Window::new("Convergence study")
+        .default_open(false)
+        .show(contexts.ctx_mut(), |ui| {
+            ui.horizontal(|ui| {
+                for integrator in Integrator::ALL {
+                    ui.radio_value(&mut study.integrator, integrator, integrator.name());
+                }
+            });
+            ui.add(egui::Slider::new(&mut study.refinements, 1..=6).text("refinements"));
+            ui.add(egui::Slider::new(&mut study.duration, 1.0..=50.0).text("duration"));
+
+            let running = study.running.is_some();
+            if ui
+                .add_enabled(
+                    !running,
+                    egui::Button::new(if running { "Running…" } else { "Run" }),
+                )
+                .clicked()
+            {
+                let config = config.clone();
+                let (integrator, refinements, duration) =
+                    (study.integrator, study.refinements, study.duration);
+                study.running =
+                    Some(AsyncComputeTaskPool::get().spawn(async move {
+                        run_study(&config, integrator, refinements, duration)
+                    }));
+            }
+
+            Plot::new("convergence")
+                .height(200.)
+                .legend(Legend::default())
+                .x_axis_label("t")
+                .y_axis_label("log10 deviation")
+                .show(ui, |plot_ui| {
+                    for (name, points) in &study.curves {
+                        plot_ui.line(Line::new(PlotPoints::new(points.clone())).name(name));
+                    }
+                });
+        });
+}
+
+/// Integrates the first trail head's initial condition with the configured dt and with
+/// successively halved steps, returning the log10 distance between each pair of neighboring
+/// refinements, sampled at multiples of the coarsest dt.
+fn run_study(
+    config: &Configuration,
+    integrator: Integrator,
+    refinements: u8,
+    duration: f32,
+) -> Vec<(String, Vec<[f64; 2]>)> {
+    let dt = config.delta_t as f32 / 10000.;
+    // The first head's initial condition, or where the default line would put it if the
+    // configuration spawns no heads.
+    let start = config
+        .initial_conditions
+        .positions(1, config.initial_distance, config.seed)
+        .first()
+        .copied()
+        .unwrap_or(Vec3::splat(config.initial_distance));
+    let steps = (duration / dt).ceil() as usize;
+
+    let trajectories: Vec<Vec<Vec3>> = (0..=refinements)
+        .map(|level| {
+            let substeps = 1 << level;
+            let h = dt / substeps as f32;
+
+            let mut position = start;
+            let mut samples = Vec::with_capacity(steps + 1);
+            samples.push(position);
+            for _ in 0..steps {
+                for _ in 0..substeps {
+                    position = integrator
+                        .step(position, h, config.rk45_tolerance, |p| velocity(config, p));
+                }
+                samples.push(position);
+            }
+            samples
+        })
+        .collect();
+
+    trajectories
+        .windows(2)
+        .enumerate()
+        .map(|(level, pair)| {
+            let points = pair[0]
+                .iter()
+                .zip(&pair[1])
+                .enumerate()
+                .filter_map(|(step, (coarse, fine))| {
+                    let deviation = coarse.distance(*fine);
+                    (deviation > 0.).then(|| [step as f64 * dt as f64, (deviation as f64).log10()])
+                })
+                .collect();
+            (
+                format!("dt/{} vs dt/{}", 1 << level, 1 << (level + 1)),
+                points,
+            )
+        })
+        .collect()
+}
@@ -0,0 +1,467 @@
+use std::{iter::Peekable, str::CharIndices};
+
+use bevy::prelude::*;
+use bevy_egui::egui;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    attractor::{AttractorSystem, Parameters},
+    Configuration,
+};
+
+/// Deepest evaluation stack a compiled expression may need, so evaluating never allocates.
+const MAX_STACK: usize = 32;
+/// How deeply parentheses, function calls, leading minuses and exponents may nest, so a long
+/// chain of them can't overflow the stack while parsing.
+const MAX_NESTING: usize = 16;
+/// Names of the variables an expression can use, in the order [`CustomSystem::evaluate`]
+/// passes their values.
+const VARIABLES: [&str; 6] = ["x", "y", "z", "sigma", "rho", "beta"];
+const DEFAULT_EQUATIONS: [&str; 3] = ["sigma * (y - x)", "x * (rho - z) - y", "x * y - beta * z"];
+
+#[derive(Clone, Copy, Debug)]
+enum Function {
+    Sin,
+    Cos,
+    Tan,
+    Exp,
+    Ln,
+    Sqrt,
+    Abs,
+}
+
+impl Function {
+    fn parse(name: &str) -> Option<Self> {
+        Some(match name {
+            "sin" => Function::Sin,
+            "cos" => Function::Cos,
+            "tan" => Function::Tan,
+            "exp" => Function::Exp,
+            "ln" => Function::Ln,
+            "sqrt" => Function::Sqrt,
+            "abs" => Function::Abs,
+            _ => return None,
+        })
+    }
+
+    fn apply(self, value: f64) -> f64 {
+        match self {
+            Function::Sin => value.sin(),
+            Function::Cos => value.cos(),
+            Function::Tan => value.tan(),
+            Function::Exp => value.exp(),
+            Function::Ln => value.ln(),
+            Function::Sqrt => value.sqrt(),
+            Function::Abs => value.abs(),
+        }
+    }
+}
+
+/// One instruction of a stack machine.
+#[derive(Clone, Copy, Debug)]
+enum Op {
+    Constant(f64),
+    /// Index into [`VARIABLES`].
+    Variable(usize),
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Power,
+    Negate,
+    Call(Function),
+}
+
+/// An expression compiled to postfix order.
+#[derive(Clone, Debug)]
+struct Program(Vec<Op>);
+
+impl Program {
+    fn evaluate(&self, variables: &[f64; 6]) -> f64 {
+        let mut stack = [0.; MAX_STACK];
+        let mut len = 0;
+        for &op in &self.0 {
+            match op {
+                Op::Constant(value) => {
+                    stack[len] = value;
+                    len += 1;
+                }
+                Op::Variable(index) => {
+                    stack[len] = variables[index];
+                    len += 1;
+                }
+                Op::Negate => stack[len - 1] = -stack[len - 1],
+                Op::Call(function) => stack[len - 1] = function.apply(stack[len - 1]),
+                binary => {
+                    len -= 1;
+                    let (a, b) = (stack[len - 1], stack[len]);
+                    stack[len - 1] = match binary {
+                        Op::Add => a + b,
+                        Op::Subtract => a - b,
+                        Op::Multiply => a * b,
+                        Op::Divide => a / b,
+                        _ => a.powf(b),
+                    };
+                }
+            }
+        }
+        stack[0]
+    }
+}
+
+/// Recursive descent parser that emits postfix code while it goes.
+struct Parser<'a> {
+    source: &'a str,
+    chars: Peekable<CharIndices<'a>>,
+    ops: Vec<Op>,
+    depth: usize,
+    max_depth: usize,
+    nesting: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn compile(source: &'a str) -> Result<Program, String> {
+        let mut parser = Parser {
+            source,
+            chars: source.char_indices().peekable(),
+            ops: Vec::new(),
+            depth: 0,
+            max_depth: 0,
+            nesting: 0,
+        };
+        parser.expression()?;
+        parser.skip_whitespace();
+        if let Some(&(position, c)) = parser.chars.peek() {
+            return Err(format!("Unexpected '{c}' at {}", position + 1));
+        }
+        if parser.max_depth > MAX_STACK {
+            return Err("Expression is nested too deeply".into());
+        }
+        Ok(Program(parser.ops))
+    }
+
+    fn emit(&mut self, op: Op) {
+        match op {
+            Op::Constant(_) | Op::Variable(_) => self.depth += 1,
+            Op::Negate | Op::Call(_) => {}
+            _ => self.depth -= 1,
+        }
+        self.max_depth = self.max_depth.max(self.depth);
+        self.ops.push(op);
+    }
+
+    /// Runs `parse` one nesting level deeper, failing past [`MAX_NESTING`].
+    fn nested(&mut self, parse: fn(&mut Self) -> Result<(), String>) -> Result<(), String> {
+        if self.nesting == MAX_NESTING {
+            return Err("Expression is nested too deeply".into());
+        }
+        self.nesting += 1;
+        let result = parse(self);
+        self.nesting -= 1;
+        result
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.chars.next_if(|(_, c)| c.is_whitespace()).is_some() {}
+    }
+
+    /// Consumes `expected` if it comes next.
+    fn eat(&mut self, expected: char) -> bool {
+        self.skip_whitespace();
+        self.chars.next_if(|&(_, c)| c == expected).is_some()
+    }
+
+    fn expression(&mut self) -> Result<(), String> {
+        self.term()?;
+        loop {
+            if self.eat('+') {
+                self.term()?;
+                self.emit(Op::Add);
+            } else if self.eat('-') {
+                self.term()?;
+                self.emit(Op::Subtract);
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    fn term(&mut self) -> Result<(), String> {
+        self.unary()?;
+        loop {
+            if self.eat('*') {
+                self.unary()?;
+                self.emit(Op::Multiply);
+            } else if self.eat('/') {
+                self.unary()?;
+                self.emit(Op::Divide);
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    fn unary(&mut self) -> Result<(), String> {
+        if self.eat('-') {
+            self.nested(Self::unary)?;
+            self.emit(Op::Negate);
+            Ok(())
+        } else {
+            self.power()
+        }
+    }
+
+    /// Right-associative, and binds tighter than a leading minus: `-x^2` is `-(x^2)`.
+    fn power(&mut self) -> Result<(), String> {
+        self.atom()?;
+        if self.eat('^') {
+            self.nested(Self::unary)?;
+            self.emit(Op::Power);
+        }
+        Ok(())
+    }
+
+    fn atom(&mut self) -> Result<(), String> {
+        self.skip_whitespace();
+        let Some(&(start, c)) = self.chars.peek() else {
+            return Err("Unexpected end of expression".into());
+        };
+
+        if self.eat('(') {
+            self.nested(Self::expression)?;
+            return if self.eat(')') {
+                Ok(())
+            } else {
+                Err(format!("Missing ')' for '(' at {}", start + 1))
+            };
+        }
+
+        let end = |parser: &mut Self, accept: fn(char) -> bool| {
+            while parser.chars.next_if(|&(_, c)| accept(c)).is_some() {}
+            parser
+                .chars
+                .peek()
+                .map_or(parser.source.len(), |&(position, _)| position)
+        };
+        if c.is_ascii_digit() || c == '.' {
+            end(self, |c| c.is_ascii_digit() || c == '.');
+            // An exponent like `1e-3`, only if digits follow so `2e` still fails as a number
+            // followed by the constant.
+            let mut exponent = self.chars.clone();
+            if exponent.next_if(|&(_, c)| c == 'e' || c == 'E').is_some() {
+                exponent.next_if(|&(_, c)| c == '+' || c == '-');
+                if exponent.peek().is_some_and(|&(_, c)| c.is_ascii_digit()) {
+                    self.chars = exponent;
+                }
+            }
+            let end = end(self, |c| c.is_ascii_digit());
+            let number = &self.source[start..end];
+            let value = number
+                .parse()
+                .map_err(|_| format!("Invalid number '{number}' at {}", start + 1))?;
+            self.emit(Op::Constant(value));
+            return Ok(());
+        }
+        if c.is_alphabetic() {
+            let end = end(self, |c| c.is_alphanumeric() || c == '_');
+            let name = &self.source[start..end];
+            if let Some(function) = Function::parse(name) {
+                if !self.eat('(') {
+                    return Err(format!("Expected '(' after {name}"));
+                }
+                self.nested(Self::expression)?;
+                if !self.eat(')') {
+                    return Err(format!("Missing ')' after the argument of {name}"));
+                }
+                self.emit(Op::Call(function));
+                return Ok(());
+            }
+            let op = match name {
+                "pi" => Op::Constant(std::f64::consts::PI),
+                "e" => Op::Constant(std::f64::consts::E),
+                _ => Op::Variable(
+                    VARIABLES
+                        .iter()
+                        .position(|&variable| variable == name)
+                        .ok_or_else(|| format!("Unknown name '{name}' at {}", start + 1))?,
+                ),
+            };
+            self.emit(op);
+            return Ok(());
+        }
+        Err(format!("Unexpected '{c}' at {}", start + 1))
+    }
+}
+
+/// Three user-written equations for dx/dt, dy/dt and dz/dt, followed by heads while
+/// [`AttractorSystem::Custom`] is selected. Saved as the equation texts, and compiled whenever
+/// they are set or loaded.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(from = "[String; 3]", into = "[String; 3]")]
+pub struct CustomSystem {
+    equations: [String; 3],
+    programs: [Result<Program, String>; 3],
+}
+
+impl Default for CustomSystem {
+    fn default() -> Self {
+        DEFAULT_EQUATIONS.map(String::from).into()
+    }
+}
+
+impl From<[String; 3]> for CustomSystem {
+    fn from(equations: [String; 3]) -> Self {
+        let programs = equations
+            .each_ref()
+            .map(|equation| Parser::compile(equation));
+        Self {
+            equations,
+            programs,
+        }
+    }
+}
+
+impl From<CustomSystem> for [String; 3] {
+    fn from(system: CustomSystem) -> Self {
+        system.equations
+    }
+}
+
+impl PartialEq for CustomSystem {
+    fn eq(&self, other: &Self) -> bool {
+        self.equations == other.equations
+    }
+}
+
+impl CustomSystem {
+    /// The velocity at `position`, zero while an equation doesn't compile.
+    pub fn evaluate(&self, parameters: &Parameters, position: [f64; 3]) -> [f64; 3] {
+        let variables = [
+            position[0],
+            position[1],
+            position[2],
+            parameters.sigma as f64,
+            parameters.rho as f64,
+            parameters.beta as f64,
+        ];
+        self.programs.each_ref().map(|program| {
+            program
+                .as_ref()
+                .map_or(0., |program| program.evaluate(&variables))
+        })
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.programs.iter().all(Result::is_ok)
+    }
+}
+
+/// A text field per equation with its error underneath, and a button to switch to the
+/// custom system.
+pub fn custom_system_ui(world: &mut World, ui: &mut egui::Ui) {
+    let config = world.resource::<Configuration>();
+    let mut equations = config.custom_system.equations.clone();
+    let errors = config
+        .custom_system
+        .programs
+        .each_ref()
+        .map(|program| program.as_ref().err().cloned());
+    let active = config.attractor == AttractorSystem::Custom;
+
+    ui.label(format!(
+        "Variables: {}. Functions: sin, cos, tan, exp, ln, sqrt, abs.",
+        VARIABLES.join(", ")
+    ));
+    egui::Grid::new("custom_system").show(ui, |ui| {
+        for ((name, equation), error) in ["dx/dt", "dy/dt", "dz/dt"]
+            .into_iter()
+            .zip(&mut equations)
+            .zip(&errors)
+        {
+            ui.label(name);
+            ui.add(egui::TextEdit::singleline(equation).code_editor());
+            ui.end_row();
+            if let Some(error) = error {
+                ui.label("");
+                ui.colored_label(egui::Color32::RED, error);
+                ui.end_row();
+            }
+        }
+    });
+
+    let mut use_custom = false;
+    ui.add_enabled_ui(!active && errors.iter().all(Option::is_none), |ui| {
+        use_custom = ui.button("Use custom system").clicked();
+    });
+
+    let mut config = world.resource_mut::<Configuration>();
+    if equations != config.custom_system.equations {
+        config.custom_system = equations.into();
+    }
+    if use_custom {
+        config.attractor = AttractorSystem::Custom;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Compiles and evaluates `source` with x = 1, y = 2, z = 3, sigma = 10, rho = 28 and
+    /// beta = 8/3.
+    fn evaluate(source: &str) -> Result<f64, String> {
+        Parser::compile(source).map(|program| program.evaluate(&[1., 2., 3., 10., 28., 8. / 3.]))
+    }
+
+    #[test]
+    fn precedence() {
+        assert_eq!(evaluate("1 + 2 * 3"), Ok(7.));
+        assert_eq!(evaluate("(1 + 2) * 3"), Ok(9.));
+        assert_eq!(evaluate("10 - 4 - 3"), Ok(3.));
+        assert_eq!(evaluate("12 / 2 / 3"), Ok(2.));
+        assert_eq!(evaluate("2 ^ 3 ^ 2"), Ok(512.));
+        assert_eq!(evaluate("x + y * z ^ 2"), Ok(19.));
+        assert_eq!(evaluate("sigma * (y - x)"), Ok(10.));
+    }
+
+    #[test]
+    fn unary_minus() {
+        assert_eq!(evaluate("-y ^ 2"), Ok(-4.));
+        assert_eq!(evaluate("(-y) ^ 2"), Ok(4.));
+        assert_eq!(evaluate("--y"), Ok(2.));
+        assert_eq!(evaluate("z * -y"), Ok(-6.));
+        assert_eq!(evaluate("y ^ -1"), Ok(0.5));
+    }
+
+    #[test]
+    fn numbers() {
+        assert_eq!(evaluate("1.5"), Ok(1.5));
+        assert_eq!(evaluate(".5"), Ok(0.5));
+        assert_eq!(evaluate("1e-3"), Ok(0.001));
+        assert_eq!(evaluate("2.5E+2"), Ok(250.));
+        assert_eq!(evaluate("3e2 * x"), Ok(300.));
+        assert_eq!(evaluate("2 * e"), Ok(2. * std::f64::consts::E));
+    }
+
+    #[test]
+    fn nesting_limit() {
+        let nested = |depth: usize, open: &str, close: &str| {
+            format!("{}x{}", open.repeat(depth), close.repeat(depth))
+        };
+        assert_eq!(evaluate(&nested(MAX_NESTING, "(", ")")), Ok(1.));
+        assert!(evaluate(&nested(MAX_NESTING + 1, "(", ")")).is_err());
+        assert!(evaluate(&nested(10_000, "(", ")")).is_err());
+        assert!(evaluate(&nested(10_000, "-", "")).is_err());
+        assert!(evaluate(&nested(10_000, "abs(", ")")).is_err());
+        assert!(evaluate(&nested(10_000, "x ^ ", "")).is_err());
+    }
+
+    #[test]
+    fn invalid_input() {
+        for source in [
+            "", "x +", "(x", "x)", "foo", "sin x", "sin(x", "1..2", "x y", "2e", "1e+", "x $ y",
+        ] {
+            assert!(evaluate(source).is_err(), "{source:?} should not compile");
+        }
+    }
+}
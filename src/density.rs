@@ -0,0 +1,263 @@
+use bevy::{
+    prelude::*,
+    render::{
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+    },
+};
+use bevy_egui::{egui, EguiContexts};
+
+use crate::{
+    file_dialog::{self, DialogKind},
+    update_position, TrailHead,
+};
+
+const RESOLUTION: usize = 256;
+/// Half of the edge length of the square region covered by the histogram.
+const HALF_EXTENT: f32 = 30.;
+
+pub struct DensityPlugin;
+
+impl Plugin for DensityPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, setup_density_histogram)
+            .add_systems(
+                FixedUpdate,
+                accumulate_density
+                    .after(update_position)
+                    .run_if(|histogram: Res<DensityHistogram>| histogram.enabled),
+            )
+            .add_systems(Update, (density_ui, update_density_texture).chain());
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ProjectionPlane {
+    Xy,
+    Xz,
+    Yz,
+}
+
+impl ProjectionPlane {
+    /// Maps a position onto the plane's (u, v) coordinates.
+    fn project(self, position: Vec3) -> Vec2 {
+        match self {
+            ProjectionPlane::Xy => Vec2::new(position.x, position.y),
+            ProjectionPlane::Xz => Vec2::new(position.x, position.z),
+            ProjectionPlane::Yz => Vec2::new(position.y, position.z),
+        }
+    }
+
+    /// Center of the covered region in (u, v) coordinates.
+    fn center(self) -> Vec2 {
+        match self {
+            ProjectionPlane::Xy => Vec2::ZERO,
+            ProjectionPlane::Xz | ProjectionPlane::Yz => Vec2::new(0., HALF_EXTENT),
+        }
+    }
+
+    /// Places the heatmap quad on the plane, behind the attractor as seen from the default
+    /// camera position.
+    fn transform(self) -> Transform {
+        match self {
+            ProjectionPlane::Xy => Transform::IDENTITY,
+            ProjectionPlane::Xz => Transform::from_xyz(0., -HALF_EXTENT, HALF_EXTENT)
+                .with_rotation(Quat::from_rotation_x(90_f32.to_radians())),
+            ProjectionPlane::Yz => Transform::from_xyz(-HALF_EXTENT, 0., HALF_EXTENT)
+                .with_rotation(
+                    Quat::from_rotation_z(90_f32.to_radians())
+                        * Quat::from_rotation_x(90_f32.to_radians()),
+                ),
+        }
+    }
+}
+
+#[derive(Resource)]
+struct DensityHistogram {
+    enabled: bool,
+    plane: ProjectionPlane,
+    counts: Vec<u32>,
+    image: Handle<Image>,
+    export_path: String,
+    export_status: Option<String>,
+}
+
+impl DensityHistogram {
+    fn clear(&mut self) {
+        self.counts.iter_mut().for_each(|count| *count = 0);
+    }
+}
+
+#[derive(Component)]
+struct DensityQuad;
+
+fn setup_density_histogram(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    let image = images.add(Image::new_fill(
+        Extent3d {
+            width: RESOLUTION as u32,
+            height: RESOLUTION as u32,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        &[0, 0, 0, 0],
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::all(),
+    ));
+
+    let plane = ProjectionPlane::Xz;
+    commands.spawn((
+        DensityQuad,
+        Mesh3d(meshes.add(Rectangle::from_length(HALF_EXTENT * 2.))),
+        MeshMaterial3d(materials.add(StandardMaterial {
+            base_color_texture: Some(image.clone()),
+            unlit: true,
+            double_sided: true,
+            cull_mode: None,
+            alpha_mode: AlphaMode::Blend,
+            ..default()
+        })),
+        plane.transform(),
+        Visibility::Hidden,
+    ));
+
+    commands.insert_resource(DensityHistogram {
+        enabled: false,
+        plane,
+        counts: vec![0; RESOLUTION * RESOLUTION],
+        image,
+        export_path: "density.png".into(),
+        export_status: None,
+    });
+}
+
+fn accumulate_density(
+    query: Query<&Transform, With<TrailHead>>,
+    mut histogram: ResMut<DensityHistogram>,
+) {
+    let plane = histogram.plane;
+    for transform in &query {
+        let uv = (plane.project(transform.translation) - plane.center()) / (HALF_EXTENT * 2.) + 0.5;
+        if !(0. ..1.).contains(&uv.x) || !(0. ..1.).contains(&uv.y) {
+            continue;
+        }
+
+        // Image rows run top to bottom, so v is flipped.
+        let column = (uv.x * RESOLUTION as f32) as usize;
+        let row = ((1. - uv.y) * RESOLUTION as f32) as usize;
+        histogram.counts[row.min(RESOLUTION - 1) * RESOLUTION + column] += 1;
+    }
+}
+
+fn update_density_texture(
+    histogram: Res<DensityHistogram>,
+    mut images: ResMut<Assets<Image>>,
+    mut quad: Query<(&mut Transform, &mut Visibility), With<DensityQuad>>,
+) {
+    if !histogram.is_changed() {
+        return;
+    }
+
+    if let Ok((mut transform, mut visibility)) = quad.get_single_mut() {
+        *transform = histogram.plane.transform();
+        *visibility = if histogram.enabled {
+            Visibility::Visible
+        } else {
+            Visibility::Hidden
+        };
+    }
+
+    let Some(image) = images.get_mut(&histogram.image) else {
+        return;
+    };
+    // Log scaling keeps the rarely visited regions visible next to the dense lobes.
+    let max = histogram.counts.iter().copied().max().unwrap_or(0).max(1) as f32;
+    for (pixel, &count) in image.data.chunks_exact_mut(4).zip(&histogram.counts) {
+        let intensity = (count as f32).ln_1p() / max.ln_1p();
+        pixel.copy_from_slice(&heat_color(intensity));
+    }
+}
+
+/// Black-red-yellow-white ramp, transparent where nothing was recorded.
+fn heat_color(intensity: f32) -> [u8; 4] {
+    if intensity <= 0. {
+        return [0, 0, 0, 0];
+    }
+    let channel = |offset: f32| ((intensity * 3. - offset).clamp(0., 1.) * 255.) as u8;
+    [channel(0.), channel(1.), channel(2.), 255]
+}
+
+/// Edits copies of the settings and only writes back what a control changed, so the histogram
+/// isn't marked changed and its texture rebuilt every frame.
+fn density_ui(
+    mut contexts: EguiContexts,
+    mut histogram: ResMut<DensityHistogram>,
+    images: Res<Assets<Image>>,
+) {
+    let mut enabled = histogram.enabled;
+    let mut plane = histogram.plane;
+    let mut export_path = histogram.export_path.clone();
+    let mut clear = false;
+    let mut export = false;
+
+    egui::Window::new("Density histogram")
+        .default_open(false)
+        .show(contexts.ctx_mut(), |ui| {
+            ui.checkbox(&mut enabled, "Accumulate and show");
+
+            ui.horizontal(|ui| {
+                ui.radio_value(&mut plane, ProjectionPlane::Xy, "XY");
+                ui.radio_value(&mut plane, ProjectionPlane::Xz, "XZ");
+                ui.radio_value(&mut plane, ProjectionPlane::Yz, "YZ");
+            });
+
+            clear = ui.button("Clear").clicked();
+
+            ui.separator();
+
+            file_dialog::path_field(ui, &mut export_path, DialogKind::Save, file_dialog::PNG);
+            export = ui.button("Export PNG").clicked();
+            if let Some(status) = histogram.export_status.as_ref() {
+                ui.label(status);
+            }
+        });
+
+    if enabled != histogram.enabled {
+        histogram.enabled = enabled;
+    }
+    if plane != histogram.plane {
+        histogram.plane = plane;
+        histogram.clear();
+    }
+    if clear {
+        histogram.clear();
+    }
+    if export_path != histogram.export_path {
+        histogram.export_path = export_path;
+    }
+    if export {
+        let status = export_png(&images, &histogram.image, &histogram.export_path);
+        histogram.export_status = Some(status);
+    }
+}
+
+fn export_png(images: &Assets<Image>, handle: &Handle<Image>, path: &str) -> String {
+    let Some(image) = images.get(handle) else {
+        return "Histogram image is not loaded".into();
+    };
+
+    let result = image
+        .clone()
+        .try_into_dynamic()
+        .map_err(|err| err.to_string())
+        .and_then(|dynamic| dynamic.save(path).map_err(|err| err.to_string()));
+
+    match result {
+        Ok(()) => format!("Saved {path}"),
+        Err(err) => format!("Export failed: {err}"),
+    }
+}
@@ -0,0 +1,273 @@
+use bevy::{
+    image::ImageSampler,
+    pbr::{MaterialPipeline, MaterialPipelineKey},
+    prelude::*,
+    render::{
+        mesh::MeshVertexBufferLayoutRef,
+        render_asset::RenderAssetUsages,
+        render_resource::{
+            AsBindGroup, Extent3d, Face, RenderPipelineDescriptor, ShaderRef,
+            SpecializedMeshPipelineError, TextureDimension, TextureFormat,
+        },
+    },
+};
+use bevy_egui::egui;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    selection::{self, TrailHidden},
+    tube::TubeTrail,
+    update_position, Configuration, SegmentOf, TrailHead,
+};
+
+/// Voxels along each edge of the volume.
+const RESOLUTION: usize = 64;
+/// Half of the edge length of the cube covered by the volume, the same region as the 2D
+/// density histogram.
+const HALF_EXTENT: f32 = 30.;
+const CENTER: Vec3 = Vec3::new(0., 0., HALF_EXTENT);
+
+pub struct DensityVolumePlugin;
+
+impl Plugin for DensityVolumePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(MaterialPlugin::<DensityVolumeMaterial>::default())
+            .add_systems(Startup, setup_density_volume)
+            .add_systems(
+                FixedUpdate,
+                accumulate_visits
+                    .after(update_position)
+                    .run_if(|config: Res<Configuration>| {
+                        config.density_volume != DensityVolumeMode::Off
+                    }),
+            )
+            .add_systems(
+                Update,
+                (
+                    update_volume_texture,
+                    hide_trails.after(selection::apply_trail_visibility),
+                ),
+            );
+    }
+}
+
+/// Whether the visited space is rendered as a volume, and whether the trails stay.
+#[derive(Reflect, Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DensityVolumeMode {
+    #[default]
+    Off,
+    AlongsideTrails,
+    /// Hides all trails, leaving only the heads and the volume.
+    InsteadOfTrails,
+}
+
+/// Visits of the heads per voxel, the invariant measure of the attractor once enough have
+/// been collected.
+#[derive(Resource)]
+struct DensityVolume {
+    counts: Vec<u32>,
+    image: Handle<Image>,
+}
+
+#[derive(Component)]
+struct DensityVolumeBox;
+
+/// Raymarches the volume texture from the camera to the back faces of the box, so the camera
+/// can move into the volume as well.
+#[derive(Asset, TypePath, AsBindGroup, Debug, Clone)]
+struct DensityVolumeMaterial {
+    #[texture(0, dimension = "3d")]
+    #[sampler(1)]
+    volume: Handle<Image>,
+    #[uniform(2)]
+    box_min: Vec4,
+    #[uniform(3)]
+    box_max: Vec4,
+    /// x: opacity per unit of length where the volume is densest.
+    #[uniform(4)]
+    settings: Vec4,
+}
+
+impl Material for DensityVolumeMaterial {
+    fn fragment_shader() -> ShaderRef {
+        "shaders/density_volume.wgsl".into()
+    }
+
+    fn alpha_mode(&self) -> AlphaMode {
+        AlphaMode::Premultiplied
+    }
+
+    fn specialize(
+        _pipeline: &MaterialPipeline<Self>,
+        descriptor: &mut RenderPipelineDescriptor,
+        _layout: &MeshVertexBufferLayoutRef,
+        _key: MaterialPipelineKey<Self>,
+    ) -> Result<(), SpecializedMeshPipelineError> {
+        descriptor.primitive.cull_mode = Some(Face::Front);
+        Ok(())
+    }
+}
+
+fn setup_density_volume(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<DensityVolumeMaterial>>,
+    mut images: ResMut<Assets<Image>>,
+    config: Res<Configuration>,
+) {
+    let mut image = Image::new_fill(
+        Extent3d {
+            width: RESOLUTION as u32,
+            height: RESOLUTION as u32,
+            depth_or_array_layers: RESOLUTION as u32,
+        },
+        TextureDimension::D3,
+        &[0],
+        TextureFormat::R8Unorm,
+        RenderAssetUsages::all(),
+    );
+    image.sampler = ImageSampler::linear();
+    let image = images.add(image);
+
+    commands.spawn((
+        DensityVolumeBox,
+        Mesh3d(meshes.add(Cuboid::from_length(HALF_EXTENT * 2.))),
+        MeshMaterial3d(materials.add(DensityVolumeMaterial {
+            volume: image.clone(),
+            box_min: (CENTER - HALF_EXTENT).extend(0.),
+            box_max: (CENTER + HALF_EXTENT).extend(0.),
+            settings: Vec4::new(config.density_volume_opacity, 0., 0., 0.),
+        })),
+        Transform::from_translation(CENTER),
+        Visibility::Hidden,
+    ));
+
+    commands.insert_resource(DensityVolume {
+        counts: vec![0; RESOLUTION * RESOLUTION * RESOLUTION],
+        image,
+    });
+}
+
+fn accumulate_visits(query: Query<&Transform, With<TrailHead>>, mut volume: ResMut<DensityVolume>) {
+    for transform in &query {
+        let uvw = (transform.translation - CENTER) / (HALF_EXTENT * 2.) + 0.5;
+        if uvw.cmplt(Vec3::ZERO).any() || uvw.cmpge(Vec3::ONE).any() {
+            continue;
+        }
+
+        let voxel = (uvw * RESOLUTION as f32).as_uvec3();
+        let index =
+            (voxel.z as usize * RESOLUTION + voxel.y as usize) * RESOLUTION + voxel.x as usize;
+        volume.counts[index.min(volume.counts.len() - 1)] += 1;
+    }
+}
+
+fn update_volume_texture(
+    volume: Res<DensityVolume>,
+    config: Res<Configuration>,
+    mut images: ResMut<Assets<Image>>,
+    mut materials: ResMut<Assets<DensityVolumeMaterial>>,
+    mut boxes: Query<
+        (&MeshMaterial3d<DensityVolumeMaterial>, &mut Visibility),
+        With<DensityVolumeBox>,
+    >,
+) {
+    if config.is_changed() {
+        for (material, mut visibility) in &mut boxes {
+            visibility.set_if_neq(if config.density_volume == DensityVolumeMode::Off {
+                Visibility::Hidden
+            } else {
+                Visibility::Visible
+            });
+            let settings = Vec4::new(config.density_volume_opacity, 0., 0., 0.);
+            if materials
+                .get(material)
+                .is_some_and(|material| material.settings != settings)
+            {
+                if let Some(material) = materials.get_mut(material) {
+                    material.settings = settings;
+                }
+            }
+        }
+    }
+
+    if !volume.is_changed() {
+        return;
+    }
+    let Some(image) = images.get_mut(&volume.image) else {
+        return;
+    };
+    // Log scaling like the 2D histogram, the lobes are visited far more often than the rest.
+    let max = volume.counts.iter().copied().max().unwrap_or(0).max(1) as f32;
+    for (voxel, &count) in image.data.iter_mut().zip(&volume.counts) {
+        *voxel = ((count as f32).ln_1p() / max.ln_1p() * 255.) as u8;
+    }
+}
+
+/// Hides every trail while the volume replaces them, and shows them again afterwards except
+/// those of hidden heads.
+fn hide_trails(
+    config: Res<Configuration>,
+    mut hiding: Local<bool>,
+    heads: Query<Has<TrailHidden>, With<TrailHead>>,
+    mut segments: Query<
+        (Ref<SegmentOf>, &mut Visibility),
+        (Without<TrailHead>, Without<TubeTrail>),
+    >,
+    mut tubes: Query<(Ref<TubeTrail>, &mut Visibility), Without<TrailHead>>,
+) {
+    let hide = config.density_volume == DensityVolumeMode::InsteadOfTrails;
+    let toggled = hide != *hiding;
+    *hiding = hide;
+    if !hide && !toggled {
+        return;
+    }
+    let visibility = |hidden: bool| {
+        if hidden {
+            Visibility::Hidden
+        } else {
+            Visibility::Inherited
+        }
+    };
+
+    for (owner, mut segment_visibility) in &mut segments {
+        if toggled || owner.is_added() {
+            let hidden = hide || heads.get(owner.0).unwrap_or(false);
+            segment_visibility.set_if_neq(visibility(hidden));
+        }
+    }
+    for (tube, mut tube_visibility) in &mut tubes {
+        if toggled || tube.is_added() {
+            tube_visibility.set_if_neq(visibility(hide));
+        }
+    }
+}
+
+/// How the volume is shown, and a button to start collecting visits from scratch.
+pub fn density_volume_ui(world: &mut World, ui: &mut egui::Ui) {
+    let mut mode = world.resource::<Configuration>().density_volume;
+    ui.horizontal(|ui| {
+        ui.radio_value(&mut mode, DensityVolumeMode::Off, "Off");
+        ui.radio_value(&mut mode, DensityVolumeMode::AlongsideTrails, "With trails");
+        ui.radio_value(
+            &mut mode,
+            DensityVolumeMode::InsteadOfTrails,
+            "Instead of trails",
+        );
+    });
+    if mode != world.resource::<Configuration>().density_volume {
+        world.resource_mut::<Configuration>().density_volume = mode;
+    }
+
+    let visits: u64 = world
+        .resource::<DensityVolume>()
+        .counts
+        .iter()
+        .map(|&count| count as u64)
+        .sum();
+    ui.label(format!("{visits} visits recorded"));
+    if ui.button("Clear").clicked() {
+        let mut volume = world.resource_mut::<DensityVolume>();
+        volume.counts.iter_mut().for_each(|count| *count = 0);
+    }
+}
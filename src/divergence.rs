@@ -0,0 +1,166 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+use egui_plot::{Line, Plot, PlotPoints};
+
+use crate::{
+    clock::{advance_simulation_clock, SimulationClock},
+    extensions::{Visualization, VisualizationSet},
+    integrator::Integrator,
+    precision::PrecisionTwin,
+    update_position, Configuration, HeadIndex, TrailHead,
+};
+
+/// Samples kept for the plot.
+const CAPACITY: usize = 20_000;
+/// Distance beyond which the separation stops growing exponentially, as it approaches the size
+/// of the attractor. Only the samples before it is first reached are fitted.
+const SATURATION: f64 = 1.;
+
+pub struct DivergencePanel;
+
+impl Visualization for DivergencePanel {
+    fn name(&self) -> &'static str {
+        "Divergence"
+    }
+
+    fn build(&self, app: &mut App, set: VisualizationSet) {
+        app.init_resource::<Divergence>()
+            .add_systems(
+                FixedUpdate,
+                sample_divergence
+                    .after(update_position)
+                    .after(advance_simulation_clock)
+                    .in_set(set),
+            )
+            .add_systems(Update, divergence_ui.in_set(set));
+    }
+}
+
+/// Distance between the first two heads of the main attractor over simulated time, the
+/// butterfly effect in one plot.
+#[derive(Resource, Default)]
+struct Divergence {
+    heads: Option<(Entity, Entity)>,
+    /// Simulated time and distance.
+    samples: Vec<(f64, f64)>,
+}
+
+impl Divergence {
+    /// Slope and intercept of a least-squares line through the logarithm of the distance,
+    /// before it saturates. The slope is the exponential growth rate.
+    fn fit(&self) -> Option<(f64, f64)> {
+        let points: Vec<(f64, f64)> = self
+            .samples
+            .iter()
+            .take_while(|&&(_, distance)| distance < SATURATION)
+            .filter(|&&(_, distance)| distance > 0.)
+            .map(|&(t, distance)| (t, distance.ln()))
+            .collect();
+        if points.len() < 2 {
+            return None;
+        }
+
+        let n = points.len() as f64;
+        let mean_t = points.iter().map(|&(t, _)| t).sum::<f64>() / n;
+        let mean_log = points.iter().map(|&(_, log)| log).sum::<f64>() / n;
+        let (covariance, variance) =
+            points
+                .iter()
+                .fold((0., 0.), |(covariance, variance), &(t, log)| {
+                    (
+                        covariance + (t - mean_t) * (log - mean_log),
+                        variance + (t - mean_t).powi(2),
+                    )
+                });
+        (variance > 0.).then(|| {
+            let slope = covariance / variance;
+            (slope, mean_log - slope * mean_t)
+        })
+    }
+}
+
+fn sample_divergence(
+    heads: Query<
+        (Entity, &HeadIndex, &Integrator, &Transform),
+        (With<TrailHead>, Without<PrecisionTwin>),
+    >,
+    parents: Query<&Parent>,
+    mut divergence: ResMut<Divergence>,
+    clock: Res<SimulationClock>,
+    config: Res<Configuration>,
+) {
+    // Heads of the main attractor with the configured integrator, ordered by index.
+    let mut first: Vec<(u16, Entity, Vec3)> = heads
+        .iter()
+        .filter(|&(entity, _, integrator, _)| {
+            parents.get(entity).is_err() && *integrator == config.integrator
+        })
+        .map(|(entity, index, _, transform)| (index.0, entity, transform.translation))
+        .collect();
+    first.sort_by_key(|&(index, ..)| index);
+    let [(_, a, position_a), (_, b, position_b), ..] = first[..] else {
+        divergence.heads = None;
+        divergence.samples.clear();
+        return;
+    };
+
+    // New heads, after a restart for example, start a new measurement.
+    if divergence.heads != Some((a, b)) {
+        divergence.heads = Some((a, b));
+        divergence.samples.clear();
+    }
+    if divergence.samples.len() == CAPACITY {
+        return;
+    }
+    divergence
+        .samples
+        .push((clock.simulated, position_a.distance(position_b) as f64));
+}
+
+fn divergence_ui(mut contexts: EguiContexts, divergence: Res<Divergence>) {
+    egui::Window::new("Divergence")
+        .default_open(false)
+        .show(contexts.ctx_mut(), |ui| {
+            if divergence.samples.len() < 2 {
+                ui.label("Needs at least two heads.");
+                return;
+            }
+
+            let fit = divergence.fit();
+            match fit {
+                Some((rate, _)) if rate > 0. => ui.label(format!(
+                    "Growth rate ≈ {rate:.3}, the distance doubles every {:.2}",
+                    2_f64.ln() / rate
+                )),
+                Some((rate, _)) => ui.label(format!("Growth rate ≈ {rate:.3}, not diverging")),
+                None => ui.label("Not enough samples before the distance saturates."),
+            };
+
+            let start = divergence.samples[0].0;
+            let distance: PlotPoints = divergence
+                .samples
+                .iter()
+                .filter(|&&(_, distance)| distance > 0.)
+                .map(|&(t, distance)| [t - start, distance.log10()])
+                .collect();
+            let end = divergence.samples.last().map_or(start, |&(t, _)| t);
+            Plot::new("divergence")
+                .height(200.)
+                .x_axis_label("t")
+                .y_axis_label("log₁₀ distance")
+                .show(ui, |plot_ui| {
+                    plot_ui.line(Line::new(distance).name("Distance"));
+                    if let Some((rate, intercept)) = fit {
+                        let fitted = |t: f64| (intercept + rate * t) / 10_f64.ln();
+                        plot_ui.line(
+                            Line::new(PlotPoints::new(vec![
+                                [0., fitted(start)],
+                                [end - start, fitted(end)],
+                            ]))
+                            .name("Fit")
+                            .style(egui_plot::LineStyle::dashed_loose()),
+                        );
+                    }
+                });
+        });
+}
@@ -0,0 +1,117 @@
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::{
+    add_head_meshes, network, precision::DoublePrecision, update_position, Configuration,
+    HeadIndex, InitialCondition, SimpleColorMaterial, TrailData, TrailHead,
+};
+
+/// Step through the color scheme between consecutively emitted heads, the golden angle as a
+/// fraction of a turn keeps neighbors distinct.
+const COLOR_STEP: f32 = 137.508 / 360.;
+
+pub struct EmitterPlugin;
+
+impl Plugin for EmitterPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Emitter>().add_systems(
+            FixedUpdate,
+            (emit_heads, retire_oldest_heads)
+                .chain()
+                .before(update_position)
+                .run_if(|config: Res<Configuration>| config.emitter_enabled)
+                .run_if(network::simulation_is_local),
+        );
+    }
+}
+
+#[derive(Resource, Default)]
+struct Emitter {
+    /// Fractional heads carried over to the next tick.
+    pending: f32,
+    emitted: u16,
+    meshes: Option<(Handle<Mesh>, Handle<Mesh>)>,
+}
+
+/// Marks heads spawned by the emitter, with the simulation time they were born at.
+#[derive(Component)]
+pub struct Emitted(f32);
+
+fn emit_heads(
+    mut emitter: ResMut<Emitter>,
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut simple_color_materials: ResMut<Assets<SimpleColorMaterial>>,
+    time: Res<Time>,
+    config: Res<Configuration>,
+) {
+    emitter.pending += config.emitter_rate * time.delta_secs();
+    let (head_mesh, trail_mesh) = emitter
+        .meshes
+        .get_or_insert_with(|| add_head_meshes(&mut meshes))
+        .clone();
+
+    let mut rng = rand::thread_rng();
+    while emitter.pending >= 1. {
+        emitter.pending -= 1.;
+        emitter.emitted = emitter.emitted.wrapping_add(1);
+
+        // Rejection sampling keeps the distribution uniform within the ball.
+        let offset = loop {
+            let candidate = Vec3::new(rng.gen(), rng.gen(), rng.gen()) * 2. - 1.;
+            if candidate.length_squared() <= 1. {
+                break candidate * config.emitter_radius;
+            }
+        };
+
+        let head_color = config
+            .color_scheme
+            .head_color(emitter.emitted as f32 * COLOR_STEP);
+        let position = config.emitter_center + offset;
+        let mut head = commands.spawn((
+            TrailHead,
+            HeadIndex(emitter.emitted),
+            config.integrator,
+            InitialCondition(position),
+            Emitted(time.elapsed_secs()),
+            Mesh3d(head_mesh.clone()),
+            MeshMaterial3d(simple_color_materials.add(SimpleColorMaterial {
+                color: head_color.into(),
+                ..default()
+            })),
+            Transform::from_translation(position),
+            TrailData {
+                mesh: trail_mesh.clone(),
+                material: simple_color_materials.add(SimpleColorMaterial {
+                    color: head_color.with_saturation(0.3).into(),
+                    ..default()
+                }),
+            },
+        ));
+        if config.double_precision {
+            head.insert(DoublePrecision(position.as_dvec3()));
+        }
+    }
+}
+
+/// Despawns the oldest emitted heads beyond the target population. Their materials are freed
+/// once the remaining trail segments have faded.
+fn retire_oldest_heads(
+    mut commands: Commands,
+    heads: Query<(Entity, &Emitted)>,
+    config: Res<Configuration>,
+) {
+    let excess = heads
+        .iter()
+        .len()
+        .saturating_sub(config.emitter_population as usize);
+    if excess == 0 {
+        return;
+    }
+
+    let mut heads: Vec<_> = heads.iter().collect();
+    heads.sort_by(|(_, a), (_, b)| a.0.total_cmp(&b.0));
+    for (entity, _) in heads.into_iter().take(excess) {
+        commands.entity(entity).despawn_recursive();
+    }
+}
@@ -0,0 +1,230 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+
+use crate::{proximity::HeadsApproached, Configuration};
+
+const MAX_ENTRIES: usize = 1000;
+/// Seconds a notification stays on screen.
+const NOTIFICATION_DURATION: f32 = 4.;
+
+pub struct EventLogPlugin;
+
+impl Plugin for EventLogPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<LogEntry>()
+            .init_resource::<EventLog>()
+            .add_systems(
+                Update,
+                (
+                    log_parameter_edits,
+                    log_approaches,
+                    record_log_entries,
+                    event_log_ui,
+                    notifications_ui,
+                )
+                    .chain(),
+            );
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LogCategory {
+    Parameters,
+    Divergence,
+    Crossing,
+    Proximity,
+    Respawn,
+}
+
+impl LogCategory {
+    const ALL: [Self; 5] = [
+        Self::Parameters,
+        Self::Divergence,
+        Self::Crossing,
+        Self::Proximity,
+        Self::Respawn,
+    ];
+}
+
+/// Something notable that should show up in the event log.
+#[derive(Event, Clone, Debug)]
+pub struct LogEntry {
+    pub category: LogCategory,
+    pub message: String,
+    /// Also pop up on screen, for things the user should notice right away.
+    pub notify: bool,
+}
+
+impl LogEntry {
+    pub fn new(category: LogCategory, message: impl Into<String>) -> Self {
+        Self {
+            category,
+            message: message.into(),
+            notify: false,
+        }
+    }
+
+    pub fn notify(mut self) -> Self {
+        self.notify = true;
+        self
+    }
+}
+
+#[derive(Resource)]
+struct EventLog {
+    /// Simulation time of each entry, oldest first.
+    entries: VecDeque<(f32, LogEntry)>,
+    shown: Vec<LogCategory>,
+    filter: String,
+    /// Messages to pop up and the wall time at which they were logged.
+    notifications: VecDeque<(f32, String)>,
+}
+
+impl Default for EventLog {
+    fn default() -> Self {
+        Self {
+            entries: VecDeque::new(),
+            shown: LogCategory::ALL.to_vec(),
+            filter: String::new(),
+            notifications: VecDeque::new(),
+        }
+    }
+}
+
+/// Logs which configuration fields changed, comparing their serialized values.
+fn log_parameter_edits(
+    config: Res<Configuration>,
+    mut previous: Local<Option<serde_json::Value>>,
+    mut entries: EventWriter<LogEntry>,
+) {
+    if !config.is_changed() {
+        return;
+    }
+    let current = serde_json::to_value(&*config).expect("Configuration is always serializable");
+
+    if let (Some(serde_json::Value::Object(before)), serde_json::Value::Object(after)) =
+        (previous.as_ref(), &current)
+    {
+        for (field, value) in after {
+            if before.get(field) != Some(value) {
+                entries.send(LogEntry::new(
+                    LogCategory::Parameters,
+                    format!("{field} = {value}"),
+                ));
+            }
+        }
+    }
+    *previous = Some(current);
+}
+
+fn log_approaches(
+    mut approaches: EventReader<HeadsApproached>,
+    mut entries: EventWriter<LogEntry>,
+) {
+    for approach in approaches.read() {
+        entries.send(LogEntry::new(
+            LogCategory::Proximity,
+            format!(
+                "Heads {} and {} came within {:.3}",
+                approach.heads[0], approach.heads[1], approach.distance
+            ),
+        ));
+    }
+}
+
+fn record_log_entries(
+    mut log: ResMut<EventLog>,
+    mut entries: EventReader<LogEntry>,
+    time: Res<Time<Virtual>>,
+    real_time: Res<Time<Real>>,
+) {
+    for entry in entries.read() {
+        log.entries.push_back((time.elapsed_secs(), entry.clone()));
+        // Repeats of the same message, e.g. many heads running away at once, show up once.
+        if entry.notify
+            && !log
+                .notifications
+                .iter()
+                .any(|(_, message)| *message == entry.message)
+        {
+            log.notifications
+                .push_back((real_time.elapsed_secs(), entry.message.clone()));
+        }
+    }
+    let excess = log.entries.len().saturating_sub(MAX_ENTRIES);
+    log.entries.drain(..excess);
+}
+
+fn event_log_ui(mut contexts: EguiContexts, mut log: ResMut<EventLog>) {
+    let log = &mut *log;
+
+    egui::Window::new("Event log")
+        .default_open(false)
+        .show(contexts.ctx_mut(), |ui| {
+            ui.horizontal_wrapped(|ui| {
+                for category in LogCategory::ALL {
+                    let mut shown = log.shown.contains(&category);
+                    if ui.checkbox(&mut shown, format!("{category:?}")).changed() {
+                        if shown {
+                            log.shown.push(category);
+                        } else {
+                            log.shown.retain(|&other| other != category);
+                        }
+                    }
+                }
+            });
+            ui.horizontal(|ui| {
+                ui.label("Filter");
+                ui.text_edit_singleline(&mut log.filter);
+                if ui.button("Clear").clicked() {
+                    log.entries.clear();
+                }
+            });
+
+            ui.separator();
+
+            let filter = log.filter.to_lowercase();
+            egui::ScrollArea::vertical()
+                .max_height(300.)
+                .stick_to_bottom(true)
+                .show(ui, |ui| {
+                    for (time, entry) in &log.entries {
+                        if !log.shown.contains(&entry.category)
+                            || !entry.message.to_lowercase().contains(&filter)
+                        {
+                            continue;
+                        }
+                        ui.label(format!(
+                            "[{time:8.2} s] {:?}: {}",
+                            entry.category, entry.message
+                        ));
+                    }
+                });
+        });
+}
+
+fn notifications_ui(
+    mut contexts: EguiContexts,
+    mut log: ResMut<EventLog>,
+    real_time: Res<Time<Real>>,
+) {
+    let now = real_time.elapsed_secs();
+    log.notifications
+        .retain(|&(logged, _)| now - logged < NOTIFICATION_DURATION);
+    if log.notifications.is_empty() {
+        return;
+    }
+
+    egui::Area::new(egui::Id::new("notifications"))
+        .anchor(egui::Align2::RIGHT_BOTTOM, [-12., -12.])
+        .interactable(false)
+        .show(contexts.ctx_mut(), |ui| {
+            for (_, message) in &log.notifications {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.colored_label(egui::Color32::YELLOW, message);
+                });
+            }
+        });
+}
@@ -0,0 +1,111 @@
+use std::{collections::VecDeque, ops::RangeInclusive};
+
+use bevy::prelude::*;
+use bevy_egui::egui;
+use rand::Rng;
+
+use crate::{attractor::AttractorSystem, gui, Configuration};
+
+/// Parameter sets kept in the history, the oldest ones drop off first.
+const HISTORY_LENGTH: usize = 12;
+/// Ranges the parameters are drawn from. They cover fixed points, periodic windows and chaos
+/// without most heads running away.
+const SIGMA_RANGE: RangeInclusive<f32> = 4.0..=20.0;
+const RHO_RANGE: RangeInclusive<f32> = 10.0..=180.0;
+const BETA_RANGE: RangeInclusive<f32> = 0.5..=4.0;
+
+pub struct ExplorePlugin;
+
+impl Plugin for ExplorePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ExploreHistory>();
+    }
+}
+
+/// `(sigma, rho, beta)` of the recently explored parameter sets, newest first.
+#[derive(Resource, Default)]
+struct ExploreHistory(VecDeque<(f32, f32, f32)>);
+
+/// Only the Lorenz and custom systems are driven by `sigma`, `rho` and `beta`.
+fn uses_parameters(config: &Configuration) -> bool {
+    matches!(
+        config.attractor,
+        AttractorSystem::Lorenz | AttractorSystem::Custom
+    )
+}
+
+/// Draws random parameters, restarts the heads with them and adds them to the history.
+pub fn explore(world: &mut World) {
+    if !uses_parameters(world.resource::<Configuration>()) {
+        return;
+    }
+    let mut rng = rand::thread_rng();
+    let parameters = (
+        rng.gen_range(SIGMA_RANGE),
+        rng.gen_range(RHO_RANGE),
+        rng.gen_range(BETA_RANGE),
+    );
+
+    let mut history = world.resource_mut::<ExploreHistory>();
+    history.0.push_front(parameters);
+    history.0.truncate(HISTORY_LENGTH);
+    apply(world, parameters);
+}
+
+fn apply(world: &mut World, (sigma, rho, beta): (f32, f32, f32)) {
+    let mut config = world.resource_mut::<Configuration>();
+    config.sigma = sigma;
+    config.rho = rho;
+    config.beta = beta;
+    gui::clear(world);
+    gui::start(world);
+}
+
+/// The explore button and the history, each entry with a button to go back to it.
+pub fn explore_ui(world: &mut World, ui: &mut egui::Ui) {
+    let enabled = uses_parameters(world.resource::<Configuration>());
+    if ui
+        .add_enabled(enabled, egui::Button::new("Explore"))
+        .on_disabled_hover_text("Only the Lorenz and custom systems use sigma, rho and beta")
+        .clicked()
+    {
+        explore(world);
+    }
+
+    let history = world.resource::<ExploreHistory>().0.clone();
+    if history.is_empty() {
+        return;
+    }
+    let current = {
+        let config = world.resource::<Configuration>();
+        (config.sigma, config.rho, config.beta)
+    };
+
+    let mut applied = None;
+    egui::Grid::new("explore_history")
+        .striped(true)
+        .show(ui, |ui| {
+            for header in ["sigma", "rho", "beta"] {
+                ui.label(header);
+            }
+            ui.end_row();
+
+            for parameters in history {
+                let (sigma, rho, beta) = parameters;
+                for value in [sigma, rho, beta] {
+                    ui.label(format!("{value:.2}"));
+                }
+                if ui
+                    .add_enabled(parameters != current, egui::Button::new("Apply"))
+                    .clicked()
+                {
+                    applied = Some(parameters);
+                }
+                ui.end_row();
+            }
+        });
+
+    if let Some(parameters) = applied {
+        apply(world, parameters);
+    }
+}
@@ -0,0 +1,65 @@
+use bevy::prelude::*;
+use bevy_egui::egui;
+
+/// A panel, overlay or analyzer that plugs into the simulation and can be switched on and off
+/// at runtime. Implementations get ordinary ECS access to the heads, trail segments and the
+/// [`Configuration`](crate::Configuration) and must add their systems to the given set.
+pub trait Visualization {
+    /// Shown next to the toggle in the control window. Must be unique.
+    fn name(&self) -> &'static str;
+
+    fn build(&self, app: &mut App, set: VisualizationSet);
+}
+
+/// Systems of one [`Visualization`]. They only run while it's enabled.
+#[derive(SystemSet, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct VisualizationSet(&'static str);
+
+/// Every registered visualization and whether it's enabled.
+#[derive(Resource, Default)]
+pub struct VisualizationRegistry(Vec<(&'static str, bool)>);
+
+impl VisualizationRegistry {
+    pub fn is_enabled(&self, name: &str) -> bool {
+        self.0
+            .iter()
+            .any(|&(registered, enabled)| registered == name && enabled)
+    }
+}
+
+pub trait AppVisualizationExt {
+    /// Registers a visualization, so adding one doesn't require touching `main`'s setup
+    /// beyond this call.
+    fn add_visualization(&mut self, visualization: impl Visualization) -> &mut Self;
+}
+
+impl AppVisualizationExt for App {
+    fn add_visualization(&mut self, visualization: impl Visualization) -> &mut Self {
+        let name = visualization.name();
+        let set = VisualizationSet(name);
+        let enabled = move |registry: Res<VisualizationRegistry>| registry.is_enabled(name);
+
+        self.init_resource::<VisualizationRegistry>()
+            .configure_sets(Update, set.run_if(enabled))
+            .configure_sets(FixedUpdate, set.run_if(enabled))
+            .configure_sets(PostUpdate, set.run_if(enabled));
+
+        let mut registry = self.world_mut().resource_mut::<VisualizationRegistry>();
+        assert!(
+            registry.0.iter().all(|&(registered, _)| registered != name),
+            "visualization `{name}` registered twice"
+        );
+        registry.0.push((name, true));
+
+        visualization.build(self, set);
+        self
+    }
+}
+
+/// Checkboxes for switching the registered visualizations on and off.
+pub fn visualizations_ui(world: &mut World, ui: &mut egui::Ui) {
+    let mut registry = world.resource_mut::<VisualizationRegistry>();
+    for (name, enabled) in &mut registry.0 {
+        ui.checkbox(enabled, *name);
+    }
+}
@@ -0,0 +1,81 @@
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::Path;
+use std::path::PathBuf;
+
+use bevy_egui::egui;
+
+/// Whether the dialog asks for an existing file, a place to write one or a directory.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum DialogKind {
+    Open,
+    Save,
+    Folder,
+}
+
+/// Extension filters for the formats the app reads and writes.
+pub const CSV: &[(&str, &[&str])] = &[("CSV", &["csv"])];
+pub const TRAJECTORIES: &[(&str, &[&str])] = &[("CSV", &["csv"]), ("JSON", &["json"])];
+pub const PNG: &[(&str, &[&str])] = &[("PNG image", &["png"])];
+pub const SNAPSHOT: &[(&str, &[&str])] = &[("RON", &["ron"]), ("Binary snapshot", &["snapshot"])];
+pub const CAMERA_PATH: &[(&str, &[&str])] = &[("RON", &["ron"]), ("JSON", &["json"])];
+#[cfg(feature = "video-export")]
+pub const VIDEO: &[(&str, &[&str])] = &[("MP4", &["mp4"]), ("WebM", &["webm"])];
+pub const PYTHON: &[(&str, &[&str])] = &[("Blender script", &["py"])];
+
+/// Shows a native file dialog that starts next to `current`, or in the working directory
+/// when it is empty. Blocks until the dialog is closed.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn pick_file(kind: DialogKind, current: &str, filters: &[(&str, &[&str])]) -> Option<PathBuf> {
+    let mut dialog = rfd::FileDialog::new().set_directory(start_directory(current));
+    for (name, extensions) in filters {
+        dialog = dialog.add_filter(*name, extensions);
+    }
+    if let Some(file_name) = Path::new(current).file_name() {
+        dialog = dialog.set_file_name(file_name.to_string_lossy());
+    }
+
+    match kind {
+        DialogKind::Open => dialog.pick_file(),
+        DialogKind::Save => dialog.save_file(),
+        DialogKind::Folder => dialog.pick_folder(),
+    }
+}
+
+/// Browsers only offer asynchronous file dialogs, which can't hand a path back.
+#[cfg(target_arch = "wasm32")]
+pub fn pick_file(_: DialogKind, _: &str, _: &[(&str, &[&str])]) -> Option<PathBuf> {
+    None
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn start_directory(current: &str) -> PathBuf {
+    let parent = Path::new(current)
+        .parent()
+        .filter(|parent| parent.is_dir())
+        .map(Path::to_path_buf);
+    parent
+        .or_else(|| std::env::current_dir().ok())
+        .unwrap_or_default()
+}
+
+/// A path text field with a "Browse…" button that fills it from a file dialog. Returns true
+/// when a file was picked.
+pub fn path_field(
+    ui: &mut egui::Ui,
+    path: &mut String,
+    kind: DialogKind,
+    filters: &[(&str, &[&str])],
+) -> bool {
+    ui.horizontal(|ui| {
+        ui.text_edit_singleline(path);
+        if cfg!(target_arch = "wasm32") || !ui.button("Browse…").clicked() {
+            return false;
+        }
+        let Some(picked) = pick_file(kind, path, filters) else {
+            return false;
+        };
+        *path = picked.display().to_string();
+        true
+    })
+    .inner
+}
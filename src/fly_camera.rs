@@ -0,0 +1,115 @@
+use bevy::{input::mouse::AccumulatedMouseMotion, prelude::*};
+use bevy_egui::EguiContexts;
+use bevy_panorbit_camera::{PanOrbitCamera, PanOrbitCameraSystemSet};
+
+use crate::Configuration;
+
+/// Radians of rotation per pixel of mouse movement.
+const LOOK_SENSITIVITY: f32 = 0.003;
+/// Keeps the camera from flipping over when looking straight up or down.
+const MAX_PITCH: f32 = 1.54;
+const FAST_FACTOR: f32 = 4.;
+const SLOW_FACTOR: f32 = 0.25;
+
+pub struct FlyCameraPlugin;
+
+impl Plugin for FlyCameraPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                switch_camera_mode.run_if(|config: Res<Configuration>| config.is_changed()),
+                fly_camera.run_if(|config: Res<Configuration>| config.fly_camera),
+            )
+                .chain()
+                .after(PanOrbitCameraSystemSet),
+        );
+    }
+}
+
+/// Free-fly state of a camera. The up axis is taken from the camera when fly mode starts, so
+/// looking around never rolls it relative to the orbit view.
+#[derive(Component)]
+struct FlyCamera {
+    up: Vec3,
+    pitch: f32,
+}
+
+/// Hands the camera back and forth between the orbit controls and the fly controls. Leaving
+/// fly mode returns to the last orbit view.
+fn switch_camera_mode(
+    mut cameras: Query<(Entity, &Transform, &mut PanOrbitCamera, Option<&FlyCamera>)>,
+    mut commands: Commands,
+    config: Res<Configuration>,
+) {
+    for (entity, transform, mut pan_orbit, fly) in &mut cameras {
+        match (config.fly_camera, fly.is_some()) {
+            (true, false) => {
+                pan_orbit.enabled = false;
+                let up = transform.up().as_vec3();
+                let pitch = transform.forward().dot(up).clamp(-1., 1.).asin();
+                commands.entity(entity).insert(FlyCamera { up, pitch });
+            }
+            (false, true) => {
+                pan_orbit.enabled = true;
+                pan_orbit.force_update = true;
+                commands.entity(entity).remove::<FlyCamera>();
+            }
+            _ => {}
+        }
+    }
+}
+
+/// WASD to move, Space and C to rise and sink, right mouse button to look around. Shift flies
+/// faster, Alt slower.
+fn fly_camera(
+    mut cameras: Query<(&mut Transform, &mut FlyCamera)>,
+    mut contexts: EguiContexts,
+    keys: Res<ButtonInput<KeyCode>>,
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    mouse_motion: Res<AccumulatedMouseMotion>,
+    time: Res<Time<Real>>,
+    config: Res<Configuration>,
+) {
+    let ctx = contexts.ctx_mut();
+    let (keyboard_free, pointer_free) = (!ctx.wants_keyboard_input(), !ctx.wants_pointer_input());
+
+    let Ok((mut transform, mut fly)) = cameras.get_single_mut() else {
+        return;
+    };
+
+    if pointer_free && mouse_buttons.pressed(MouseButton::Right) {
+        let delta = mouse_motion.delta * LOOK_SENSITIVITY;
+        let pitch = (fly.pitch - delta.y).clamp(-MAX_PITCH, MAX_PITCH);
+        let yaw = Quat::from_axis_angle(fly.up, -delta.x);
+        let pitch_axis = transform.right().as_vec3();
+        transform.rotate(yaw * Quat::from_axis_angle(pitch_axis, pitch - fly.pitch));
+        fly.pitch = pitch;
+    }
+
+    if !keyboard_free {
+        return;
+    }
+    let mut direction = Vec3::ZERO;
+    for (key, axis) in [
+        (KeyCode::KeyW, transform.forward().as_vec3()),
+        (KeyCode::KeyS, transform.back().as_vec3()),
+        (KeyCode::KeyD, transform.right().as_vec3()),
+        (KeyCode::KeyA, transform.left().as_vec3()),
+        (KeyCode::Space, fly.up),
+        (KeyCode::KeyC, -fly.up),
+    ] {
+        if keys.pressed(key) {
+            direction += axis;
+        }
+    }
+
+    let mut speed = config.fly_speed;
+    if keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]) {
+        speed *= FAST_FACTOR;
+    }
+    if keys.any_pressed([KeyCode::AltLeft, KeyCode::AltRight]) {
+        speed *= SLOW_FACTOR;
+    }
+    transform.translation += direction.normalize_or_zero() * speed * time.delta_secs();
+}
@@ -0,0 +1,44 @@
+use bevy::{pbr::FogFalloff, prelude::*};
+
+use crate::Configuration;
+
+pub struct FogPlugin;
+
+impl Plugin for FogPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            apply_fog.run_if(|config: Res<Configuration>| config.is_changed()),
+        );
+    }
+}
+
+fn apply_fog(
+    mut commands: Commands,
+    mut cameras: Query<(Entity, Option<&mut DistanceFog>), With<Camera3d>>,
+    config: Res<Configuration>,
+) {
+    for (camera, fog) in &mut cameras {
+        if !config.fog_enabled {
+            if fog.is_some() {
+                commands.entity(camera).remove::<DistanceFog>();
+            }
+            continue;
+        }
+
+        let settings = DistanceFog {
+            color: config.fog_color,
+            falloff: FogFalloff::Linear {
+                start: config.fog_start,
+                end: config.fog_end.max(config.fog_start),
+            },
+            ..default()
+        };
+        match fog {
+            Some(mut fog) => *fog = settings,
+            None => {
+                commands.entity(camera).insert(settings);
+            }
+        }
+    }
+}
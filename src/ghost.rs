@@ -0,0 +1,88 @@
+use std::{fs, path::Path};
+
+use bevy::{
+    prelude::*,
+    render::{mesh::PrimitiveTopology, render_asset::RenderAssetUsages},
+    utils::HashMap,
+};
+
+use crate::SimpleColorMaterial;
+
+const GHOST_COLOR: LinearRgba = LinearRgba::new(1., 1., 1., 0.25);
+
+/// Static, semi-transparent line strip showing a previously recorded trajectory.
+#[derive(Component)]
+pub struct GhostTrail;
+
+/// Reads a trajectory stored as CSV with one `head,t,x,y,z` sample per row. Rows belonging
+/// to the same head are returned in file order, heads ordered by their index. A leading header
+/// row is skipped.
+pub fn read_trajectory(path: &Path) -> Result<Vec<Vec<Vec3>>, String> {
+    let content = fs::read_to_string(path).map_err(|err| format!("{}: {err}", path.display()))?;
+    // Keyed by index, so sparse or huge indices don't allocate a trajectory for every index
+    // below them.
+    let mut heads: HashMap<usize, Vec<Vec3>> = HashMap::default();
+
+    for (line_number, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || (line_number == 0 && line.starts_with("head")) {
+            continue;
+        }
+
+        let parse_error = || {
+            format!(
+                "{}:{}: expected head,t,x,y,z",
+                path.display(),
+                line_number + 1
+            )
+        };
+        let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+        let [head, _t, x, y, z] = fields[..] else {
+            return Err(parse_error());
+        };
+        let head: usize = head.parse().map_err(|_| parse_error())?;
+        let position = Vec3::new(
+            x.parse().map_err(|_| parse_error())?,
+            y.parse().map_err(|_| parse_error())?,
+            z.parse().map_err(|_| parse_error())?,
+        );
+
+        heads.entry(head).or_default().push(position);
+    }
+
+    if heads.is_empty() {
+        return Err(format!("{}: no samples found", path.display()));
+    }
+    let mut heads: Vec<(usize, Vec<Vec3>)> = heads.into_iter().collect();
+    heads.sort_by_key(|&(head, _)| head);
+    Ok(heads.into_iter().map(|(_, samples)| samples).collect())
+}
+
+pub fn spawn_ghost_trails(
+    commands: &mut Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut simple_color_materials: ResMut<Assets<SimpleColorMaterial>>,
+    trajectories: Vec<Vec<Vec3>>,
+) {
+    let material = simple_color_materials.add(SimpleColorMaterial {
+        color: GHOST_COLOR,
+        ..default()
+    });
+
+    for positions in trajectories
+        .into_iter()
+        .filter(|positions| positions.len() > 1)
+    {
+        let mesh = Mesh::new(
+            PrimitiveTopology::LineStrip,
+            RenderAssetUsages::RENDER_WORLD,
+        )
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+
+        commands.spawn((
+            GhostTrail,
+            Mesh3d(meshes.add(mesh)),
+            MeshMaterial3d(material.clone()),
+        ));
+    }
+}
@@ -0,0 +1,132 @@
+use bevy::{prelude::*, window::PrimaryWindow};
+use bevy_panorbit_camera::{PanOrbitCamera, PanOrbitCameraSystemSet};
+
+use crate::{precision::DoublePrecision, selection::Selection};
+
+const AXIS_LENGTH: f32 = 4.;
+/// Maximum distance in pixels between cursor and axis for a click to grab it.
+const GRAB_DISTANCE: f32 = 10.;
+const AXES: [(Vec3, Color); 3] = [
+    (Vec3::X, Color::srgb(1., 0.2, 0.2)),
+    (Vec3::Y, Color::srgb(0.2, 1., 0.2)),
+    (Vec3::Z, Color::srgb(0.2, 0.4, 1.)),
+];
+
+pub struct TranslationGizmoPlugin;
+
+impl Plugin for TranslationGizmoPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GizmoDrag>().add_systems(
+            Update,
+            (
+                drag_translation_gizmo.before(PanOrbitCameraSystemSet),
+                draw_translation_gizmo,
+            )
+                .run_if(|time: Res<Time<Virtual>>, selection: Res<Selection>| {
+                    time.is_paused() && selection.0.is_some()
+                }),
+        );
+    }
+}
+
+/// Axis currently being dragged and the cursor position of the previous frame.
+#[derive(Resource, Default)]
+struct GizmoDrag(Option<(Vec3, Vec2)>);
+
+fn draw_translation_gizmo(
+    mut gizmos: Gizmos,
+    heads: Query<&Transform>,
+    selection: Res<Selection>,
+    drag: Res<GizmoDrag>,
+) {
+    let Some(Ok(transform)) = selection.0.map(|head| heads.get(head)) else {
+        return;
+    };
+
+    for (axis, color) in AXES {
+        let color = match drag.0 {
+            Some((dragged, _)) if dragged == axis => Color::WHITE,
+            _ => color,
+        };
+        gizmos.arrow(
+            transform.translation,
+            transform.translation + axis * AXIS_LENGTH,
+            color,
+        );
+    }
+}
+
+/// Moves the selected head along the grabbed axis by the cursor movement projected onto the
+/// axis' on-screen direction. Camera controls are disabled while dragging.
+fn drag_translation_gizmo(
+    mut drag: ResMut<GizmoDrag>,
+    mut heads: Query<(&mut Transform, Option<&mut DoublePrecision>)>,
+    mut cameras: Query<(&Camera, &GlobalTransform, &mut PanOrbitCamera)>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    selection: Res<Selection>,
+) {
+    let Ok((camera, camera_transform, mut pan_orbit)) = cameras.get_single_mut() else {
+        return;
+    };
+    let Some(Ok((mut transform, double_precision))) = selection.0.map(|head| heads.get_mut(head))
+    else {
+        return;
+    };
+    let Some(cursor) = windows.get_single().ok().and_then(Window::cursor_position) else {
+        return;
+    };
+
+    if mouse_buttons.just_released(MouseButton::Left) && drag.0.take().is_some() {
+        pan_orbit.enabled = true;
+        return;
+    }
+
+    let origin = transform.translation;
+    let screen_axis = |axis: Vec3| {
+        let start = camera.world_to_viewport(camera_transform, origin).ok()?;
+        let end = camera
+            .world_to_viewport(camera_transform, origin + axis * AXIS_LENGTH)
+            .ok()?;
+        Some((start, end))
+    };
+
+    if mouse_buttons.just_pressed(MouseButton::Left) {
+        let grabbed = AXES.iter().find(|(axis, _)| {
+            screen_axis(*axis)
+                .is_some_and(|(start, end)| distance_to_segment(cursor, start, end) < GRAB_DISTANCE)
+        });
+        if let Some(&(axis, _)) = grabbed {
+            drag.0 = Some((axis, cursor));
+            pan_orbit.enabled = false;
+        }
+        return;
+    }
+
+    let Some((axis, last_cursor)) = drag.0 else {
+        return;
+    };
+    drag.0 = Some((axis, cursor));
+
+    let Some((start, end)) = screen_axis(axis) else {
+        return;
+    };
+    let pixels_per_axis = start.distance(end);
+    if pixels_per_axis < f32::EPSILON {
+        return;
+    }
+    let along_axis = (cursor - last_cursor).dot((end - start) / pixels_per_axis);
+    let offset = axis * along_axis / pixels_per_axis * AXIS_LENGTH;
+
+    transform.translation += offset;
+    if let Some(mut state) = double_precision {
+        **state += offset.as_dvec3();
+    }
+}
+
+fn distance_to_segment(point: Vec2, start: Vec2, end: Vec2) -> f32 {
+    let segment = end - start;
+    let t =
+        ((point - start).dot(segment) / segment.length_squared().max(f32::EPSILON)).clamp(0., 1.);
+    point.distance(start + segment * t)
+}
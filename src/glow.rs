@@ -0,0 +1,61 @@
+use bevy::{core_pipeline::bloom::Bloom, prelude::*};
+
+use crate::{Configuration, SimpleColorMaterial, TrailHead};
+
+pub struct GlowPlugin;
+
+impl Plugin for GlowPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                apply_bloom.run_if(|config: Res<Configuration>| config.is_changed()),
+                apply_head_glow,
+            ),
+        );
+    }
+}
+
+/// Renders every 3D camera in HDR with bloom while glow is enabled.
+fn apply_bloom(
+    mut commands: Commands,
+    mut cameras: Query<(Entity, &mut Camera), With<Camera3d>>,
+    config: Res<Configuration>,
+) {
+    for (entity, mut camera) in &mut cameras {
+        camera.hdr = config.bloom_enabled;
+        if config.bloom_enabled {
+            commands.entity(entity).insert(Bloom {
+                intensity: config.bloom_intensity,
+                ..Bloom::NATURAL
+            });
+        } else {
+            commands.entity(entity).remove::<Bloom>();
+        }
+    }
+}
+
+/// Keeps the glow uniform of the head materials in sync with the configuration. Without HDR
+/// the extra brightness would only clip to white, so it is off along with bloom.
+fn apply_head_glow(
+    heads: Query<&MeshMaterial3d<SimpleColorMaterial>, With<TrailHead>>,
+    mut simple_color_materials: ResMut<Assets<SimpleColorMaterial>>,
+    config: Res<Configuration>,
+) {
+    let glow = if config.bloom_enabled {
+        Vec4::new(config.head_glow, 0., 0., 0.)
+    } else {
+        Vec4::ZERO
+    };
+
+    for head_material in &heads {
+        if simple_color_materials
+            .get(head_material)
+            .is_some_and(|material| material.glow != glow)
+        {
+            if let Some(material) = simple_color_materials.get_mut(head_material) {
+                material.glow = glow;
+            }
+        }
+    }
+}
@@ -1,7 +1,29 @@
 use bevy::{ecs::system::SystemState, prelude::*, window::PrimaryWindow};
-use bevy_egui::{egui, EguiContext, EguiPlugin};
+use bevy_egui::{egui, EguiClipboard, EguiContext, EguiPlugin};
 
-use crate::{spawn_trail_heads, Configuration, SimpleColorMaterial, TimeOfBirth, TrailHead};
+use std::path::Path;
+
+use crate::{
+    api, camera_feel, chat,
+    coloring::TrailPalette,
+    custom_system, density_volume,
+    event_log::{LogCategory, LogEntry},
+    explore, extensions,
+    file_dialog::{self, DialogKind},
+    ghost::{self, GhostTrail},
+    initial_conditions,
+    instances::{self, AttractorInstance},
+    integrator, key_bindings, lighting, network,
+    persistence::{self, ResumePrompt, AUTOSAVE_PATH},
+    playback,
+    precision::DivergenceMarker,
+    presets,
+    rewind::{self, RewindHistory},
+    screenshot, segment_budget, selection, share, snapshot, spawn_trail_heads,
+    tube::TubeTrail,
+    tutorial::Tutorial,
+    validation, views, Configuration, SimpleColorMaterial, TimeOfBirth, TrailHead,
+};
 
 pub struct ControlUIPlugin;
 
@@ -11,7 +33,16 @@ impl Plugin for ControlUIPlugin {
     }
 }
 
-fn control_ui(world: &mut World) {
+#[derive(Default)]
+struct ControlUIState {
+    import_error: Option<String>,
+    reference_path: String,
+    reference_error: Option<String>,
+    snapshot_path: String,
+    snapshot_status: Option<Result<String, String>>,
+}
+
+fn control_ui(world: &mut World, mut state: Local<ControlUIState>) {
     let Ok(egui_context) = world
         .query_filtered::<&mut EguiContext, With<PrimaryWindow>>()
         .get_single(world)
@@ -20,8 +51,29 @@ fn control_ui(world: &mut World) {
     };
     let mut egui_context = egui_context.clone();
 
+    if world.contains_resource::<ResumePrompt>() {
+        egui::Window::new("Resume previous session?")
+            .collapsible(false)
+            .show(egui_context.get_mut(), |ui| {
+                ui.horizontal(|ui| {
+                    if ui.button("Resume").clicked() {
+                        if let Err(err) = load_snapshot(world, Path::new(AUTOSAVE_PATH)) {
+                            state.snapshot_status = Some(Err(err));
+                        }
+                        world.remove_resource::<ResumePrompt>();
+                    }
+                    if ui.button("Discard").clicked() {
+                        persistence::discard_autosave();
+                        world.remove_resource::<ResumePrompt>();
+                    }
+                });
+            });
+    }
+
     egui::Window::new("Control").show(egui_context.get_mut(), |ui| {
         egui::ScrollArea::vertical().show(ui, |ui| {
+            validation::conflicts_ui(world, ui);
+
             if ui.button("Clear").clicked() {
                 clear(world);
             };
@@ -30,34 +82,301 @@ fn control_ui(world: &mut World) {
                 clear(world);
                 start(world);
             };
+
+            playback::playback_ui(world, ui);
+            integrator::effective_step_ui(world, ui);
+            segment_budget::segment_budget_ui(world, ui);
+            rewind::rewind_ui(world, ui);
+
+            if ui.button("Tutorial").clicked() {
+                world.resource_mut::<Tutorial>().0 = Some(0);
+            };
+
+            ui.separator();
+
+            if ui.button("Copy share code").clicked() {
+                let code = share::encode(world.resource::<Configuration>());
+                ui.ctx().copy_text(code);
+            };
+
+            if ui.button("Copy as RON").clicked() {
+                let ron = share::to_ron(world.resource::<Configuration>());
+                ui.ctx().copy_text(ron);
+            };
+
+            if ui.button("Copy as JSON").clicked() {
+                let json = share::to_json(world.resource::<Configuration>());
+                ui.ctx().copy_text(json);
+            };
+
+            if ui.button("Import from clipboard").clicked() {
+                state.import_error = import_from_clipboard(world).err();
+            };
+
+            if let Some(err) = state.import_error.as_ref() {
+                ui.colored_label(egui::Color32::RED, err);
+            }
+
+            ui.separator();
+
+            ui.label("Reference trajectory (CSV)");
+            file_dialog::path_field(
+                ui,
+                &mut state.reference_path,
+                DialogKind::Open,
+                file_dialog::CSV,
+            );
+            ui.horizontal(|ui| {
+                if ui.button("Load").clicked() {
+                    state.reference_error = load_reference(world, &state.reference_path).err();
+                };
+
+                if ui.button("Remove").clicked() {
+                    remove_reference(world);
+                    state.reference_error = None;
+                };
+            });
+
+            if let Some(err) = state.reference_error.as_ref() {
+                ui.colored_label(egui::Color32::RED, err);
+            }
+
+            ui.separator();
+
+            ui.label("Snapshot (.ron or binary)");
+            ui.text_edit_singleline(&mut state.snapshot_path);
+            ui.horizontal(|ui| {
+                if ui.button("Save state…").clicked() {
+                    if let Some(path) = file_dialog::pick_file(
+                        DialogKind::Save,
+                        &state.snapshot_path,
+                        file_dialog::SNAPSHOT,
+                    ) {
+                        let snapshot = snapshot::capture(world);
+                        state.snapshot_status = Some(
+                            snapshot::save(&snapshot, &path)
+                                .map(|()| format!("Saved {}", path.display())),
+                        );
+                        state.snapshot_path = path.display().to_string();
+                    }
+                };
+
+                if ui.button("Load state…").clicked() {
+                    if let Some(path) = file_dialog::pick_file(
+                        DialogKind::Open,
+                        &state.snapshot_path,
+                        file_dialog::SNAPSHOT,
+                    ) {
+                        state.snapshot_status = Some(
+                            load_snapshot(world, &path)
+                                .map(|()| format!("Loaded {}", path.display())),
+                        );
+                        state.snapshot_path = path.display().to_string();
+                    }
+                };
+            });
+
+            match state.snapshot_status.as_ref() {
+                Some(Ok(message)) => {
+                    ui.label(message);
+                }
+                Some(Err(err)) => {
+                    ui.colored_label(egui::Color32::RED, err);
+                }
+                None => {}
+            }
+
+            ui.separator();
+
+            selection::selection_ui(world, ui);
+
+            ui.separator();
+
+            egui::CollapsingHeader::new("Capture").show(ui, |ui| {
+                screenshot::capture_ui(world, ui);
+                #[cfg(feature = "video-export")]
+                {
+                    ui.separator();
+                    crate::video::video_ui(world, ui);
+                }
+            });
+
+            egui::CollapsingHeader::new("Custom system").show(ui, |ui| {
+                custom_system::custom_system_ui(world, ui);
+            });
+
+            egui::CollapsingHeader::new("Density volume").show(ui, |ui| {
+                density_volume::density_volume_ui(world, ui);
+            });
+
+            egui::CollapsingHeader::new("Explore").show(ui, |ui| {
+                explore::explore_ui(world, ui);
+            });
+
+            egui::CollapsingHeader::new("Initial positions").show(ui, |ui| {
+                initial_conditions::initial_positions_ui(world, ui);
+            });
+
+            egui::CollapsingHeader::new("Instances").show(ui, |ui| {
+                instances::instances_ui(world, ui);
+            });
+
+            egui::CollapsingHeader::new("Presets").show(ui, |ui| {
+                presets::presets_ui(world, ui);
+            });
+
+            egui::CollapsingHeader::new("Visualizations").show(ui, |ui| {
+                extensions::visualizations_ui(world, ui);
+            });
+
+            // Browsers can't open sockets.
+            #[cfg(not(target_arch = "wasm32"))]
+            egui::CollapsingHeader::new("Network").show(ui, |ui| {
+                network::network_ui(world, ui);
+            });
+
+            #[cfg(not(target_arch = "wasm32"))]
+            egui::CollapsingHeader::new("Chat control").show(ui, |ui| {
+                chat::chat_ui(world, ui);
+            });
+
+            #[cfg(not(target_arch = "wasm32"))]
+            egui::CollapsingHeader::new("HTTP API").show(ui, |ui| {
+                api::api_ui(world, ui);
+            });
+
+            #[cfg(feature = "live-control")]
+            egui::CollapsingHeader::new("OSC and MIDI").show(ui, |ui| {
+                crate::live_control::live_control_ui(world, ui);
+            });
+
+            egui::CollapsingHeader::new("Camera").show(ui, |ui| {
+                views::views_ui(world, ui);
+                ui.separator();
+                camera_feel::camera_feel_ui(world, ui);
+            });
+
+            egui::CollapsingHeader::new("Lights").show(ui, |ui| {
+                lighting::lights_ui(world, ui);
+            });
+
+            egui::CollapsingHeader::new("Key bindings").show(ui, |ui| {
+                key_bindings::key_bindings_ui(world, ui);
+            });
         });
     });
 }
 
-fn clear(world: &mut World) {
+fn import_from_clipboard(world: &mut World) -> Result<(), String> {
+    let text = world
+        .resource_mut::<EguiClipboard>()
+        .get_contents()
+        .ok_or("Clipboard is empty")?;
+
+    let mut config = share::import(&text).map_err(|err| format!("Import failed: {err}"))?;
+    // Heads are spawned right away, before the configuration is sanitized as usual.
+    for change in config.clamp() {
+        warn!("{change}");
+    }
+    world.insert_resource(config);
+    clear(world);
+    start(world);
+    Ok(())
+}
+
+fn load_snapshot(world: &mut World, path: &Path) -> Result<(), String> {
+    let snapshot = snapshot::load(path)?;
+    let mut config = snapshot.config.clone();
+    // Heads are spawned right away, before the configuration is sanitized as usual.
+    for change in config.clamp() {
+        warn!("{change}");
+    }
+    world.insert_resource(config);
+    clear(world);
+    start(world);
+    snapshot::restore(world, &snapshot);
+    Ok(())
+}
+
+fn load_reference(world: &mut World, path: &str) -> Result<(), String> {
+    let trajectories = ghost::read_trajectory(Path::new(path))?;
+    remove_reference(world);
+
+    let mut system_state: SystemState<(
+        Commands,
+        ResMut<Assets<Mesh>>,
+        ResMut<Assets<SimpleColorMaterial>>,
+    )> = SystemState::new(world);
+
+    let (mut commands, meshes, simple_color_materials) = system_state.get_mut(world);
+
+    ghost::spawn_ghost_trails(&mut commands, meshes, simple_color_materials, trajectories);
+
+    system_state.apply(world);
+    Ok(())
+}
+
+fn remove_reference(world: &mut World) {
+    let mut system_state: SystemState<(
+        Query<(Entity, &Mesh3d, &MeshMaterial3d<SimpleColorMaterial>), With<GhostTrail>>,
+        ResMut<Assets<Mesh>>,
+        ResMut<Assets<SimpleColorMaterial>>,
+        Commands,
+    )> = SystemState::new(world);
+
+    let (query, mut meshes, mut simple_color_materials, mut commands) = system_state.get_mut(world);
+
+    query.iter().for_each(|(entity, mesh, material)| {
+        commands.entity(entity).despawn();
+        meshes.remove(mesh);
+        simple_color_materials.remove(material);
+    });
+
+    system_state.apply(world);
+}
+
+pub fn clear(world: &mut World) {
     let mut system_state: SystemState<(
         Query<
-            (Entity, &Mesh3d, &MeshMaterial3d<SimpleColorMaterial>),
-            Or<(With<TrailHead>, With<TimeOfBirth>)>,
+            (
+                Entity,
+                Option<&Mesh3d>,
+                &MeshMaterial3d<SimpleColorMaterial>,
+            ),
+            Or<(
+                With<TrailHead>,
+                With<TimeOfBirth>,
+                With<DivergenceMarker>,
+                With<TubeTrail>,
+            )>,
         >,
         ResMut<Assets<Mesh>>,
         ResMut<Assets<SimpleColorMaterial>>,
+        Res<TrailPalette>,
         Commands,
     )> = SystemState::new(world);
 
-    let (mut query, mut meshes, mut simple_color_materials, mut commands) =
+    let (mut query, mut meshes, mut simple_color_materials, palette, mut commands) =
         system_state.get_mut(world);
 
     query.iter_mut().for_each(|(entity, mesh, material)| {
         commands.entity(entity).despawn_recursive();
-        meshes.remove(mesh);
-        simple_color_materials.remove(material);
+        // Instanced heads have no mesh of their own.
+        if let Some(mesh) = mesh {
+            meshes.remove(mesh);
+        }
+        // The shared palette outlives individual trails.
+        if !palette.contains(material) {
+            simple_color_materials.remove(material);
+        }
     });
 
     system_state.apply(world);
+    // The recorded frames refer to the despawned heads.
+    *world.resource_mut::<RewindHistory>() = RewindHistory::default();
 }
 
-fn start(world: &mut World) {
+pub fn start(world: &mut World) {
     let mut system_state: SystemState<(
         Commands,
         ResMut<Assets<Mesh>>,
@@ -65,9 +384,25 @@ fn start(world: &mut World) {
         Res<Configuration>,
     )> = SystemState::new(world);
 
-    let (mut commands, meshes, simple_color_materials, config) = system_state.get_mut(world);
+    let (mut commands, mut meshes, mut simple_color_materials, config) =
+        system_state.get_mut(world);
 
-    spawn_trail_heads(&mut commands, meshes, simple_color_materials, config);
+    spawn_trail_heads(
+        &mut commands,
+        &mut meshes,
+        &mut simple_color_materials,
+        &config,
+        None,
+    );
 
     system_state.apply(world);
+
+    let instances: Vec<Entity> = world
+        .query_filtered::<Entity, With<AttractorInstance>>()
+        .iter(world)
+        .collect();
+    for instance in instances {
+        instances::spawn_instance_heads(world, instance);
+    }
+    world.send_event(LogEntry::new(LogCategory::Respawn, "Trail heads respawned"));
 }
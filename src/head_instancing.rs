@@ -0,0 +1,355 @@
+use bevy::{
+    core_pipeline::core_3d::Transparent3d,
+    ecs::{
+        query::QueryItem,
+        system::{lifetimeless::*, SystemParamItem},
+    },
+    pbr::{
+        MeshPipeline, MeshPipelineKey, RenderMeshInstances, SetMeshBindGroup, SetMeshViewBindGroup,
+    },
+    prelude::*,
+    render::{
+        extract_component::{ExtractComponent, ExtractComponentPlugin},
+        mesh::{
+            allocator::MeshAllocator, MeshVertexBufferLayoutRef, RenderMesh, RenderMeshBufferInfo,
+        },
+        render_asset::RenderAssets,
+        render_phase::{
+            AddRenderCommand, DrawFunctions, PhaseItem, PhaseItemExtraIndex, RenderCommand,
+            RenderCommandResult, SetItemPipeline, TrackedRenderPass, ViewSortedRenderPhases,
+        },
+        render_resource::*,
+        renderer::RenderDevice,
+        sync_world::MainEntity,
+        view::{ExtractedView, NoFrustumCulling},
+        Render, RenderApp, RenderSet,
+    },
+};
+use bytemuck::{Pod, Zeroable};
+
+use crate::{Configuration, SimpleColorMaterial, TrailHead, HEAD_RADIUS};
+
+const SHADER_ASSET_PATH: &str = "shaders/head_instancing.wgsl";
+
+pub struct HeadInstancingPlugin;
+
+impl Plugin for HeadInstancingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(ExtractComponentPlugin::<HeadInstances>::default())
+            .add_systems(Startup, spawn_instanced_heads)
+            .add_systems(Update, swap_head_meshes)
+            .add_systems(
+                PostUpdate,
+                collect_head_instances.after(TransformSystem::TransformPropagate),
+            );
+        app.sub_app_mut(RenderApp)
+            .add_render_command::<Transparent3d, DrawHeadInstances>()
+            .init_resource::<SpecializedMeshPipelines<HeadInstancePipeline>>()
+            .add_systems(
+                Render,
+                (
+                    queue_head_instances.in_set(RenderSet::QueueMeshes),
+                    prepare_instance_buffers.in_set(RenderSet::PrepareResources),
+                ),
+            );
+    }
+
+    fn finish(&self, app: &mut App) {
+        app.sub_app_mut(RenderApp)
+            .init_resource::<HeadInstancePipeline>();
+    }
+}
+
+/// Position, scale and color of one head as the shader reads it.
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+struct HeadInstance {
+    /// xyz: position, w: scale.
+    position_scale: [f32; 4],
+    color: [f32; 4],
+}
+
+/// Every visible head, drawn with a single instanced draw call of this entity's sphere
+/// instead of a mesh entity per head.
+#[derive(Component, Clone, Default)]
+struct HeadInstances(Vec<HeadInstance>);
+
+impl ExtractComponent for HeadInstances {
+    type QueryData = &'static HeadInstances;
+    type QueryFilter = ();
+    type Out = Self;
+
+    fn extract_component(instances: QueryItem<'_, Self::QueryData>) -> Option<Self> {
+        (!instances.0.is_empty()).then(|| instances.clone())
+    }
+}
+
+fn spawn_instanced_heads(mut commands: Commands, mut meshes: ResMut<Assets<Mesh>>) {
+    commands.spawn((
+        Mesh3d(meshes.add(Sphere::new(HEAD_RADIUS))),
+        HeadInstances::default(),
+        // The heads are spread over the whole scene, unlike the sphere's bounding box.
+        NoFrustumCulling,
+    ));
+}
+
+/// The mesh of a head while it is drawn as an instance.
+#[derive(Component)]
+struct InstancedHead(Handle<Mesh>);
+
+/// Takes the meshes away from the heads while they are instanced, and gives them back after.
+/// The materials stay, they keep the color, emphasis and glow the instances are drawn with.
+fn swap_head_meshes(
+    mut commands: Commands,
+    meshed: Query<(Entity, &Mesh3d), With<TrailHead>>,
+    instanced: Query<(Entity, &InstancedHead)>,
+    config: Res<Configuration>,
+) {
+    if config.instanced_heads {
+        for (head, mesh) in &meshed {
+            commands
+                .entity(head)
+                .remove::<Mesh3d>()
+                .insert(InstancedHead(mesh.0.clone()));
+        }
+    } else {
+        for (head, mesh) in &instanced {
+            commands
+                .entity(head)
+                .remove::<InstancedHead>()
+                .insert(Mesh3d(mesh.0.clone()));
+        }
+    }
+}
+
+fn collect_head_instances(
+    mut instances: Query<&mut HeadInstances>,
+    heads: Query<
+        (
+            &GlobalTransform,
+            &InheritedVisibility,
+            &MeshMaterial3d<SimpleColorMaterial>,
+        ),
+        With<TrailHead>,
+    >,
+    materials: Res<Assets<SimpleColorMaterial>>,
+    config: Res<Configuration>,
+) {
+    let Ok(mut instances) = instances.get_single_mut() else {
+        return;
+    };
+    instances.0.clear();
+    if !config.instanced_heads {
+        return;
+    }
+
+    instances.0.extend(
+        heads
+            .iter()
+            .filter(|(_, visibility, _)| visibility.get())
+            .filter_map(|(transform, _, material)| {
+                let material = materials.get(material)?;
+                // Same as the trail shader, without lighting and slicing.
+                let emphasis = material.emphasis.x;
+                let base = material.color.to_vec3();
+                let color = if emphasis > 0. {
+                    base.lerp(Vec3::ONE, emphasis)
+                } else {
+                    base * (1. + emphasis)
+                } + base * material.glow.x;
+                let (scale, _, translation) = transform.to_scale_rotation_translation();
+                Some(HeadInstance {
+                    position_scale: translation.extend(scale.x).to_array(),
+                    color: color.extend(material.color.alpha).to_array(),
+                })
+            }),
+    );
+}
+
+#[allow(clippy::too_many_arguments)]
+fn queue_head_instances(
+    draw_functions: Res<DrawFunctions<Transparent3d>>,
+    pipeline: Res<HeadInstancePipeline>,
+    mut pipelines: ResMut<SpecializedMeshPipelines<HeadInstancePipeline>>,
+    pipeline_cache: Res<PipelineCache>,
+    meshes: Res<RenderAssets<RenderMesh>>,
+    render_mesh_instances: Res<RenderMeshInstances>,
+    instances: Query<(Entity, &MainEntity), With<HeadInstances>>,
+    mut phases: ResMut<ViewSortedRenderPhases<Transparent3d>>,
+    views: Query<(Entity, &ExtractedView, &Msaa)>,
+) {
+    let draw_head_instances = draw_functions.read().id::<DrawHeadInstances>();
+
+    for (view_entity, view, msaa) in &views {
+        let Some(phase) = phases.get_mut(&view_entity) else {
+            continue;
+        };
+        let view_key = MeshPipelineKey::from_msaa_samples(msaa.samples())
+            | MeshPipelineKey::from_hdr(view.hdr);
+        let rangefinder = view.rangefinder3d();
+
+        for (entity, main_entity) in &instances {
+            let Some(mesh_instance) = render_mesh_instances.render_mesh_queue_data(*main_entity)
+            else {
+                continue;
+            };
+            let Some(mesh) = meshes.get(mesh_instance.mesh_asset_id) else {
+                continue;
+            };
+            let key =
+                view_key | MeshPipelineKey::from_primitive_topology(mesh.primitive_topology());
+            let Ok(pipeline) = pipelines.specialize(&pipeline_cache, &pipeline, key, &mesh.layout)
+            else {
+                continue;
+            };
+            phase.add(Transparent3d {
+                entity: (entity, *main_entity),
+                pipeline,
+                draw_function: draw_head_instances,
+                distance: rangefinder.distance_translation(&mesh_instance.translation),
+                batch_range: 0..1,
+                extra_index: PhaseItemExtraIndex::NONE,
+            });
+        }
+    }
+}
+
+#[derive(Component)]
+struct InstanceBuffer {
+    buffer: Buffer,
+    length: usize,
+}
+
+fn prepare_instance_buffers(
+    mut commands: Commands,
+    instances: Query<(Entity, &HeadInstances)>,
+    render_device: Res<RenderDevice>,
+) {
+    for (entity, instances) in &instances {
+        let buffer = render_device.create_buffer_with_data(&BufferInitDescriptor {
+            label: Some("head instance buffer"),
+            contents: bytemuck::cast_slice(&instances.0),
+            usage: BufferUsages::VERTEX | BufferUsages::COPY_DST,
+        });
+        commands.entity(entity).insert(InstanceBuffer {
+            buffer,
+            length: instances.0.len(),
+        });
+    }
+}
+
+/// The mesh pipeline with the instance data as a second vertex buffer.
+#[derive(Resource)]
+struct HeadInstancePipeline {
+    shader: Handle<Shader>,
+    mesh_pipeline: MeshPipeline,
+}
+
+impl FromWorld for HeadInstancePipeline {
+    fn from_world(world: &mut World) -> Self {
+        Self {
+            shader: world.load_asset(SHADER_ASSET_PATH),
+            mesh_pipeline: world.resource::<MeshPipeline>().clone(),
+        }
+    }
+}
+
+impl SpecializedMeshPipeline for HeadInstancePipeline {
+    type Key = MeshPipelineKey;
+
+    fn specialize(
+        &self,
+        key: Self::Key,
+        layout: &MeshVertexBufferLayoutRef,
+    ) -> Result<RenderPipelineDescriptor, SpecializedMeshPipelineError> {
+        let mut descriptor = self.mesh_pipeline.specialize(key, layout)?;
+        descriptor.vertex.shader = self.shader.clone();
+        descriptor.vertex.buffers.push(VertexBufferLayout {
+            array_stride: std::mem::size_of::<HeadInstance>() as u64,
+            step_mode: VertexStepMode::Instance,
+            attributes: vec![
+                VertexAttribute {
+                    format: VertexFormat::Float32x4,
+                    offset: 0,
+                    shader_location: 3,
+                },
+                VertexAttribute {
+                    format: VertexFormat::Float32x4,
+                    offset: VertexFormat::Float32x4.size(),
+                    shader_location: 4,
+                },
+            ],
+        });
+        descriptor.fragment.as_mut().unwrap().shader = self.shader.clone();
+        Ok(descriptor)
+    }
+}
+
+type DrawHeadInstances = (
+    SetItemPipeline,
+    SetMeshViewBindGroup<0>,
+    SetMeshBindGroup<1>,
+    DrawMeshInstanced,
+);
+
+struct DrawMeshInstanced;
+
+impl<P: PhaseItem> RenderCommand<P> for DrawMeshInstanced {
+    type Param = (
+        SRes<RenderAssets<RenderMesh>>,
+        SRes<RenderMeshInstances>,
+        SRes<MeshAllocator>,
+    );
+    type ViewQuery = ();
+    type ItemQuery = Read<InstanceBuffer>;
+
+    fn render<'w>(
+        item: &P,
+        _view: (),
+        instance_buffer: Option<&'w InstanceBuffer>,
+        (meshes, render_mesh_instances, mesh_allocator): SystemParamItem<'w, '_, Self::Param>,
+        pass: &mut TrackedRenderPass<'w>,
+    ) -> RenderCommandResult {
+        let mesh_allocator = mesh_allocator.into_inner();
+        let Some(mesh_instance) = render_mesh_instances.render_mesh_queue_data(item.main_entity())
+        else {
+            return RenderCommandResult::Skip;
+        };
+        let Some(gpu_mesh) = meshes.into_inner().get(mesh_instance.mesh_asset_id) else {
+            return RenderCommandResult::Skip;
+        };
+        let Some(instance_buffer) = instance_buffer else {
+            return RenderCommandResult::Skip;
+        };
+        let Some(vertex_slice) = mesh_allocator.mesh_vertex_slice(&mesh_instance.mesh_asset_id)
+        else {
+            return RenderCommandResult::Skip;
+        };
+
+        pass.set_vertex_buffer(0, vertex_slice.buffer.slice(..));
+        pass.set_vertex_buffer(1, instance_buffer.buffer.slice(..));
+        let instances = 0..instance_buffer.length as u32;
+        match &gpu_mesh.buffer_info {
+            RenderMeshBufferInfo::Indexed {
+                index_format,
+                count,
+            } => {
+                let Some(index_slice) =
+                    mesh_allocator.mesh_index_slice(&mesh_instance.mesh_asset_id)
+                else {
+                    return RenderCommandResult::Skip;
+                };
+                pass.set_index_buffer(index_slice.buffer.slice(..), 0, *index_format);
+                pass.draw_indexed(
+                    index_slice.range.start..(index_slice.range.start + count),
+                    vertex_slice.range.start as i32,
+                    instances,
+                );
+            }
+            RenderMeshBufferInfo::NonIndexed => {
+                pass.draw(vertex_slice.range, instances);
+            }
+        }
+        RenderCommandResult::Success
+    }
+}
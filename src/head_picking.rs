@@ -0,0 +1,185 @@
+use bevy::{prelude::*, window::PrimaryWindow};
+use bevy_egui::{egui, EguiContexts};
+use bevy_panorbit_camera::PanOrbitCamera;
+
+use crate::{
+    attractor::Parameters,
+    instances::{self, AttractorInstance},
+    selection::Selection,
+    Configuration, HeadIndex, InitialCondition, TrailHead, HEAD_RADIUS,
+};
+
+/// Cursor movement in pixels between press and release up to which it still counts as a click
+/// rather than orbiting the camera.
+const CLICK_TOLERANCE: f32 = 4.;
+/// Smallest on-screen radius in pixels a head can be picked within, so far away heads stay
+/// clickable.
+const MIN_PICK_RADIUS: f32 = 8.;
+
+pub struct HeadPickingPlugin;
+
+impl Plugin for HeadPickingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (pick_head, selected_head_ui));
+    }
+}
+
+/// Selects the head under the cursor on a left click. The nearest head to the camera wins when
+/// several overlap.
+fn pick_head(
+    heads: Query<(Entity, &GlobalTransform, &InheritedVisibility), With<TrailHead>>,
+    cameras: Query<(&Camera, &GlobalTransform), With<PanOrbitCamera>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    mut contexts: EguiContexts,
+    mut pressed_at: Local<Option<Vec2>>,
+    mut selection: ResMut<Selection>,
+) {
+    let Some(cursor) = windows.get_single().ok().and_then(Window::cursor_position) else {
+        return;
+    };
+    if mouse_buttons.just_pressed(MouseButton::Left) {
+        *pressed_at = (!contexts.ctx_mut().wants_pointer_input()).then_some(cursor);
+    }
+    if !mouse_buttons.just_released(MouseButton::Left) {
+        return;
+    }
+    let Some(pressed_at) = pressed_at.take() else {
+        return;
+    };
+    if pressed_at.distance(cursor) > CLICK_TOLERANCE {
+        return;
+    }
+    let Ok((camera, camera_transform)) = cameras.get_single() else {
+        return;
+    };
+
+    let picked = heads
+        .iter()
+        .filter(|(_, _, visibility)| visibility.get())
+        .filter_map(|(entity, transform, _)| {
+            let center = transform.translation();
+            let on_screen = camera.world_to_viewport(camera_transform, center).ok()?;
+            // A point on the sphere's silhouette, roughly, to get its radius in pixels.
+            let edge = center + camera_transform.right() * HEAD_RADIUS;
+            let radius = camera
+                .world_to_viewport(camera_transform, edge)
+                .map_or(0., |edge| edge.distance(on_screen));
+            (on_screen.distance(cursor) <= radius.max(MIN_PICK_RADIUS))
+                .then(|| (entity, camera_transform.translation().distance(center)))
+        })
+        .min_by(|(_, a), (_, b)| a.total_cmp(b));
+
+    if let Some((head, _)) = picked {
+        selection.0 = Some(head);
+    }
+}
+
+/// State of the selected head, and how far it drifted from the heads that started next to it.
+fn selected_head_ui(
+    mut contexts: EguiContexts,
+    mut selection: ResMut<Selection>,
+    heads: Query<
+        (
+            Entity,
+            &HeadIndex,
+            &Transform,
+            &InitialCondition,
+            Option<&Parent>,
+        ),
+        With<TrailHead>,
+    >,
+    instances: Query<(Entity, &AttractorInstance, &Transform), Without<TrailHead>>,
+    config: Res<Configuration>,
+) {
+    let Some(Ok((_, index, transform, initial_condition, parent))) =
+        selection.0.map(|head| heads.get(head))
+    else {
+        return;
+    };
+    let parent = parent.map(Parent::get);
+    let instances = instances::instance_parameters(&instances);
+    let parameters = parent
+        .and_then(|parent| instances.get(&parent))
+        .map_or_else(|| Parameters::of(&config), |&(parameters, _)| parameters);
+
+    let position = transform.translation;
+    let velocity = config.attractor.velocity(&config, &parameters, position);
+
+    // Heads of the same attractor that started next to this one, including twins of the
+    // comparison modes.
+    let mut neighbors: Vec<(u16, f32, f32)> = heads
+        .iter()
+        .filter(|&(entity, other_index, .., other_parent)| {
+            Some(entity) != selection.0
+                && other_parent.map(Parent::get) == parent
+                && other_index.0.abs_diff(index.0) <= 1
+        })
+        .map(
+            |(_, other_index, other_transform, other_initial_condition, _)| {
+                (
+                    other_index.0,
+                    initial_condition.0.distance(other_initial_condition.0),
+                    position.distance(other_transform.translation),
+                )
+            },
+        )
+        .collect();
+    neighbors.sort_by_key(|&(index, ..)| index);
+
+    let mut open = true;
+    egui::Window::new("Selected head")
+        .open(&mut open)
+        .show(contexts.ctx_mut(), |ui| {
+            egui::Grid::new("selected_head_state").show(ui, |ui| {
+                ui.label("Head");
+                ui.label(index.0.to_string());
+                ui.end_row();
+                ui.label("Position");
+                ui.label(format!(
+                    "{:.3}, {:.3}, {:.3}",
+                    position.x, position.y, position.z
+                ));
+                ui.end_row();
+                ui.label("Velocity");
+                ui.label(format!(
+                    "{:.3}, {:.3}, {:.3}",
+                    velocity.x, velocity.y, velocity.z
+                ));
+                ui.end_row();
+                ui.label("Speed");
+                ui.label(format!("{:.3}", velocity.length()));
+                ui.end_row();
+            });
+
+            if neighbors.is_empty() {
+                return;
+            }
+            ui.separator();
+            ui.label("Divergence from neighbors");
+            egui::Grid::new("selected_head_neighbors")
+                .striped(true)
+                .show(ui, |ui| {
+                    for header in ["Head", "Initial distance", "Distance", "Growth"] {
+                        ui.label(header);
+                    }
+                    ui.end_row();
+
+                    for (index, initial_distance, distance) in neighbors {
+                        ui.label(index.to_string());
+                        ui.label(format!("{initial_distance:.2e}"));
+                        ui.label(format!("{distance:.3}"));
+                        if initial_distance > 0. {
+                            ui.label(format!("{:.1e}×", distance / initial_distance));
+                        } else {
+                            ui.label("-");
+                        }
+                        ui.end_row();
+                    }
+                });
+        });
+
+    if !open {
+        selection.0 = None;
+    }
+}
@@ -0,0 +1,134 @@
+use std::path::PathBuf;
+
+use bevy::prelude::*;
+
+use crate::{
+    integrator::Integrator,
+    precision::DoublePrecision,
+    recording::{TrajectoryRecording, TrajectorySample},
+    velocity, velocity_f64, Configuration, HeadIndex, InitialCondition, TrailHead,
+};
+
+/// Runs the simulation without a window or rendering, for `MinimalPlugins`. Every update
+/// advances all heads by exactly one step of `delta_t`, so the output only depends on the
+/// configuration and is the same on every machine and run. After `steps` steps the
+/// trajectories are written to `output`, as JSON for a `.json` path and CSV otherwise, and
+/// the app exits.
+pub struct HeadlessPlugin {
+    pub config: Configuration,
+    pub steps: u32,
+    pub output: PathBuf,
+}
+
+impl Plugin for HeadlessPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(self.config.clone())
+            .insert_resource(HeadlessRun {
+                remaining: self.steps,
+                step: 0,
+                output: self.output.clone(),
+            })
+            .init_resource::<TrajectoryRecording>()
+            .add_systems(Startup, spawn_headless_heads)
+            .add_systems(Update, step_headless);
+    }
+}
+
+#[derive(Resource)]
+struct HeadlessRun {
+    remaining: u32,
+    step: u32,
+    output: PathBuf,
+}
+
+fn spawn_headless_heads(mut commands: Commands, config: Res<Configuration>) {
+    let positions = config.initial_conditions.positions(
+        config.num_of_trails,
+        config.initial_distance,
+        config.seed,
+    );
+    for (i, initial_pos) in (1..).zip(positions) {
+        let mut head = commands.spawn((
+            TrailHead,
+            HeadIndex(i),
+            config.integrator,
+            InitialCondition(initial_pos),
+            Transform::from_translation(initial_pos),
+        ));
+        if config.double_precision {
+            head.insert(DoublePrecision(initial_pos.as_dvec3()));
+        }
+    }
+}
+
+fn step_headless(
+    mut heads: Query<
+        (
+            &HeadIndex,
+            &Integrator,
+            &InitialCondition,
+            &mut Transform,
+            Option<&mut DoublePrecision>,
+        ),
+        With<TrailHead>,
+    >,
+    mut run: ResMut<HeadlessRun>,
+    mut recording: ResMut<TrajectoryRecording>,
+    mut exit: EventWriter<AppExit>,
+    config: Res<Configuration>,
+) {
+    if run.remaining == 0 {
+        let result = recording.export(&run.output);
+        match &result {
+            Ok(()) => info!(
+                "Wrote {} samples to {}",
+                recording.0.len(),
+                run.output.display()
+            ),
+            Err(err) => error!("Could not write the trajectories: {err}"),
+        }
+        exit.send(match result {
+            Ok(()) => AppExit::Success,
+            Err(_) => AppExit::error(),
+        });
+        return;
+    }
+
+    let dt = config.delta_t as f32 / 10000.;
+    run.step += 1;
+    run.remaining -= 1;
+
+    for (index, integrator, initial_condition, mut transform, mut double_precision) in &mut heads {
+        let tolerance = config.rk45_tolerance;
+        let mut position = match double_precision.as_deref_mut() {
+            Some(state) => {
+                **state = integrator.step(**state, dt as f64, tolerance, |position| {
+                    velocity_f64(&config, position)
+                });
+                state.as_vec3()
+            }
+            None => integrator.step(transform.translation, dt, tolerance, |position| {
+                velocity(&config, position)
+            }),
+        };
+        // Same recovery as in the windowed simulation, so both produce the same trajectories.
+        if !position.is_finite() || position.length() > config.runaway_limit {
+            warn!("Head {} left the attractor, respawning it", index.0);
+            position = initial_condition.0;
+            if let Some(state) = double_precision.as_deref_mut() {
+                **state = position.as_dvec3();
+            }
+        }
+        transform.translation = position;
+
+        let Vec3 { x, y, z } = position;
+        recording.0.push_back(TrajectorySample {
+            head: index.0,
+            integrator: *integrator,
+            t: run.step as f64 * dt as f64,
+            x,
+            y,
+            z,
+        });
+    }
+}
@@ -0,0 +1,131 @@
+use bevy::prelude::*;
+use bevy_egui::egui;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use crate::{validation::MAX_TRAILS, Configuration};
+
+/// Where the trail heads start. The random layouts draw from `seed`, so the same
+/// configuration always starts from the same points.
+#[derive(Reflect, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum InitialConditions {
+    /// Head `i` starts at `i * initial_distance` on every axis.
+    #[default]
+    LineAlongDiagonal,
+    /// Uniformly distributed within a ball.
+    RandomSphere { center: Vec3, radius: f32 },
+    /// Uniformly distributed within an axis-aligned cube with edges of length `size`.
+    RandomCube { center: Vec3, size: f32 },
+    /// A square grid parallel to the xy-plane, filled row by row.
+    GridPlane { center: Vec3, spacing: f32 },
+    /// One head per point, regardless of `num_of_trails`.
+    Custom(Vec<Vec3>),
+}
+
+impl InitialConditions {
+    /// Starting points of the heads, in spawn order.
+    pub fn positions(&self, count: u16, initial_distance: f32, seed: u64) -> Vec<Vec3> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        match self {
+            InitialConditions::LineAlongDiagonal => (1..=count)
+                .map(|i| Vec3::splat(i as f32 * initial_distance))
+                .collect(),
+            InitialConditions::RandomSphere { center, radius } => (0..count)
+                .map(|_| {
+                    // Rejection sampling keeps the distribution uniform within the ball.
+                    let offset = loop {
+                        let candidate = random_unit_cube(&mut rng);
+                        if candidate.length_squared() <= 1. {
+                            break candidate;
+                        }
+                    };
+                    *center + offset * *radius
+                })
+                .collect(),
+            InitialConditions::RandomCube { center, size } => (0..count)
+                .map(|_| *center + random_unit_cube(&mut rng) * *size / 2.)
+                .collect(),
+            InitialConditions::GridPlane { center, spacing } => {
+                let columns = (count as f32).sqrt().ceil() as u16;
+                let rows = count.div_ceil(columns.max(1));
+                let origin = Vec2::new(columns as f32 - 1., rows as f32 - 1.) * *spacing / 2.;
+                (0..count)
+                    .map(|i| {
+                        let cell = Vec2::new((i % columns) as f32, (i / columns) as f32);
+                        *center + (cell * *spacing - origin).extend(0.)
+                    })
+                    .collect()
+            }
+            InitialConditions::Custom(points) => {
+                points.iter().take(MAX_TRAILS as usize).copied().collect()
+            }
+        }
+    }
+}
+
+/// A point within the cube from -1 to 1 on every axis.
+fn random_unit_cube(rng: &mut StdRng) -> Vec3 {
+    Vec3::new(rng.gen(), rng.gen(), rng.gen()) * 2. - 1.
+}
+
+/// Table of custom starting points, used by the next start. Other layouts can be turned into
+/// a table to fine-tune their points.
+pub fn initial_positions_ui(world: &mut World, ui: &mut egui::Ui) {
+    let config = world.resource::<Configuration>();
+    let InitialConditions::Custom(points) = &config.initial_conditions else {
+        if ui.button("Edit as table").clicked() {
+            let points = config.initial_conditions.positions(
+                config.num_of_trails,
+                config.initial_distance,
+                config.seed,
+            );
+            world.resource_mut::<Configuration>().initial_conditions =
+                InitialConditions::Custom(points);
+        }
+        return;
+    };
+
+    let mut edited = points.clone();
+    let mut removed = None;
+    egui::Grid::new("initial_positions")
+        .striped(true)
+        .show(ui, |ui| {
+            ui.label("Head");
+            ui.label("x");
+            ui.label("y");
+            ui.label("z");
+            ui.end_row();
+
+            for (i, point) in edited.iter_mut().enumerate() {
+                ui.label((i + 1).to_string());
+                for value in [&mut point.x, &mut point.y, &mut point.z] {
+                    ui.add(egui::DragValue::new(value).speed(0.01));
+                }
+                if ui.button("Remove").clicked() {
+                    removed = Some(i);
+                }
+                ui.end_row();
+            }
+        });
+    if let Some(i) = removed {
+        edited.remove(i);
+    }
+
+    let add_enabled = edited.len() < MAX_TRAILS as usize;
+    if ui
+        .add_enabled(add_enabled, egui::Button::new("Add row"))
+        .clicked()
+    {
+        // Close to the previous point, so a new row demonstrates sensitive dependence.
+        let next = edited
+            .last()
+            .map_or(Vec3::ONE, |last| *last + config.initial_distance);
+        edited.push(next);
+    }
+
+    // Only write back real edits, to not retrigger change detection every frame.
+    if edited != *points {
+        world.resource_mut::<Configuration>().initial_conditions =
+            InitialConditions::Custom(edited);
+    }
+}
@@ -0,0 +1,182 @@
+use bevy::{ecs::system::SystemState, prelude::*, utils::HashMap};
+use bevy_egui::egui;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    attractor::Parameters, spawn_trail_heads, Configuration, SimpleColorMaterial, TrailHead,
+};
+
+/// Distance between the origins of neighbouring instances, enough for the classic Lorenz
+/// attractor not to overlap.
+const INSTANCE_SPACING: f32 = 60.;
+
+/// An additional attractor in the scene, with its own parameters. Its heads are children of
+/// this entity, so their `Transform` stays in the instance's own coordinates and the
+/// instance's `Transform` places the whole attractor in the scene. Every other setting is
+/// shared with the main configuration.
+#[derive(Component, Clone, Copy)]
+#[require(Transform, Visibility)]
+pub struct AttractorInstance {
+    pub sigma: f32,
+    pub rho: f32,
+    pub beta: f32,
+}
+
+impl AttractorInstance {
+    pub fn parameters(&self) -> Parameters {
+        Parameters {
+            sigma: self.sigma,
+            rho: self.rho,
+            beta: self.beta,
+        }
+    }
+
+    /// The main configuration with this instance's parameters.
+    pub fn configure(&self, config: &Configuration) -> Configuration {
+        Configuration {
+            sigma: self.sigma,
+            rho: self.rho,
+            beta: self.beta,
+            ..config.clone()
+        }
+    }
+}
+
+/// Parameters and offset of every instance, for looking up the instance a head belongs to by
+/// its parent.
+pub fn instance_parameters(
+    instances: &Query<(Entity, &AttractorInstance, &Transform), Without<TrailHead>>,
+) -> HashMap<Entity, (Parameters, Vec3)> {
+    instances
+        .iter()
+        .map(|(entity, instance, transform)| {
+            (entity, (instance.parameters(), transform.translation))
+        })
+        .collect()
+}
+
+/// Which heads a [`HeadIndex`](crate::HeadIndex) counts among, as the main heads, each instance and the emitter
+/// number their heads separately.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum HeadGroup {
+    Main,
+    /// Position of the instance among all instances, in the order they were added.
+    Instance(usize),
+    Emitted,
+}
+
+impl HeadGroup {
+    pub fn of(instances: &HashMap<Entity, usize>, parent: Option<&Parent>, emitted: bool) -> Self {
+        if emitted {
+            return HeadGroup::Emitted;
+        }
+        parent
+            .and_then(|parent| instances.get(&parent.get()))
+            .map_or(HeadGroup::Main, |&instance| HeadGroup::Instance(instance))
+    }
+}
+
+/// Position of every attractor instance among all of them, by entity.
+pub fn instance_positions(instances: impl IntoIterator<Item = Entity>) -> HashMap<Entity, usize> {
+    let mut instances: Vec<Entity> = instances.into_iter().collect();
+    instances.sort();
+    instances
+        .into_iter()
+        .enumerate()
+        .map(|(position, entity)| (entity, position))
+        .collect()
+}
+
+/// Spawns the heads of `instance` like the main ones.
+pub fn spawn_instance_heads(world: &mut World, instance: Entity) {
+    let mut system_state: SystemState<(
+        Commands,
+        ResMut<Assets<Mesh>>,
+        ResMut<Assets<SimpleColorMaterial>>,
+        Query<&AttractorInstance>,
+        Res<Configuration>,
+    )> = SystemState::new(world);
+
+    let (mut commands, mut meshes, mut simple_color_materials, instances, config) =
+        system_state.get_mut(world);
+
+    if let Ok(attractor) = instances.get(instance) {
+        spawn_trail_heads(
+            &mut commands,
+            &mut meshes,
+            &mut simple_color_materials,
+            &attractor.configure(&config),
+            Some(instance),
+        );
+    }
+
+    system_state.apply(world);
+}
+
+/// Parameters and offset of every instance, and buttons to add and remove them.
+pub fn instances_ui(world: &mut World, ui: &mut egui::Ui) {
+    let mut instances: Vec<(Entity, AttractorInstance, Vec3)> = world
+        .query::<(Entity, &AttractorInstance, &Transform)>()
+        .iter(world)
+        .map(|(entity, instance, transform)| (entity, *instance, transform.translation))
+        .collect();
+    instances.sort_by_key(|&(entity, ..)| entity);
+
+    let mut removed = None;
+    for (i, (entity, instance, offset)) in instances.iter_mut().enumerate() {
+        let mut changed = false;
+        egui::Grid::new(("instance", *entity)).show(ui, |ui| {
+            ui.label(format!("Instance {}", i + 1));
+            if ui.button("Remove").clicked() {
+                removed = Some(*entity);
+            }
+            ui.end_row();
+
+            for (name, value) in [
+                ("sigma", &mut instance.sigma),
+                ("rho", &mut instance.rho),
+                ("beta", &mut instance.beta),
+            ] {
+                ui.label(name);
+                changed |= ui.add(egui::DragValue::new(value).speed(0.1)).changed();
+                ui.end_row();
+            }
+
+            ui.label("offset");
+            ui.horizontal(|ui| {
+                for value in [&mut offset.x, &mut offset.y, &mut offset.z] {
+                    changed |= ui.add(egui::DragValue::new(value)).changed();
+                }
+            });
+            ui.end_row();
+        });
+
+        if changed {
+            let mut entity = world.entity_mut(*entity);
+            if let Some(mut attractor) = entity.get_mut::<AttractorInstance>() {
+                *attractor = *instance;
+            }
+            if let Some(mut transform) = entity.get_mut::<Transform>() {
+                transform.translation = *offset;
+            }
+        }
+    }
+
+    if let Some(entity) = removed {
+        world.entity_mut(entity).despawn_recursive();
+    }
+
+    if ui.button("Add instance").clicked() {
+        let config = world.resource::<Configuration>();
+        let instance = AttractorInstance {
+            sigma: config.sigma,
+            rho: config.rho,
+            beta: config.beta,
+        };
+        let offset = Vec3::X * INSTANCE_SPACING * (instances.len() + 1) as f32;
+        let entity = world
+            .spawn((instance, Transform::from_translation(offset)))
+            .id();
+        spawn_instance_heads(world, entity);
+    }
+}
@@ -0,0 +1,216 @@
+use std::ops::{Add, Div, Mul};
+
+use bevy::{math::DVec3, prelude::*};
+use bevy_egui::egui;
+use serde::{Deserialize, Serialize};
+
+use crate::Configuration;
+
+/// Bounds the work of a single adaptive step, so a stiff region can't stall a physics tick.
+const RK45_MAX_SUBSTEPS: u32 = 64;
+
+/// Numerical scheme used to advance a trail head by one time step.
+#[derive(
+    Component, Reflect, Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize,
+)]
+pub enum Integrator {
+    #[default]
+    Euler,
+    Midpoint,
+    Rk4,
+    /// Dormand–Prince 5(4), splitting each step into as many substeps as needed to keep the
+    /// local error below `rk45_tolerance`.
+    Rk45,
+}
+
+/// Euclidean length of a state, needed by the adaptive scheme to estimate its error.
+pub trait Norm {
+    fn norm(self) -> f64;
+}
+
+impl Norm for Vec3 {
+    fn norm(self) -> f64 {
+        self.length() as f64
+    }
+}
+
+impl Norm for DVec3 {
+    fn norm(self) -> f64 {
+        self.length()
+    }
+}
+
+impl Integrator {
+    pub const ALL: [Self; 4] = [Self::Euler, Self::Midpoint, Self::Rk4, Self::Rk45];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Integrator::Euler => "Euler",
+            Integrator::Midpoint => "Midpoint",
+            Integrator::Rk4 => "RK4",
+            Integrator::Rk45 => "RK45",
+        }
+    }
+
+    /// Advances `position` by `dt` along the vector field `f`. Only the adaptive scheme uses
+    /// `tolerance`.
+    ///
+    /// Generic over the vector type so the same schemes drive both `Vec3` and `DVec3` state.
+    pub fn step<V, S>(self, position: V, dt: S, tolerance: f64, f: impl Fn(V) -> V) -> V
+    where
+        V: Copy + Add<Output = V> + Mul<S, Output = V> + Norm,
+        S: Copy + From<f32> + Div<Output = S> + Mul<Output = S>,
+    {
+        self.step_counted(position, dt, tolerance, f).0
+    }
+
+    /// Like [`Integrator::step`], also returning the number of substeps taken, which is 1 for
+    /// every scheme but the adaptive one.
+    pub fn step_counted<V, S>(
+        self,
+        position: V,
+        dt: S,
+        tolerance: f64,
+        f: impl Fn(V) -> V,
+    ) -> (V, u32)
+    where
+        V: Copy + Add<Output = V> + Mul<S, Output = V> + Norm,
+        S: Copy + From<f32> + Div<Output = S> + Mul<Output = S>,
+    {
+        let two = S::from(2.);
+        let position = match self {
+            Integrator::Euler => position + f(position) * dt,
+            Integrator::Midpoint => position + f(position + f(position) * (dt / two)) * dt,
+            Integrator::Rk4 => {
+                let k1 = f(position);
+                let k2 = f(position + k1 * (dt / two));
+                let k3 = f(position + k2 * (dt / two));
+                let k4 = f(position + k3 * dt);
+                position + (k1 + k2 * two + k3 * two + k4) * (dt / S::from(6.))
+            }
+            Integrator::Rk45 => return rk45(position, dt, tolerance, f),
+        };
+        (position, 1)
+    }
+}
+
+/// One Dormand–Prince step of size `h`, returning the fifth-order result and the distance to
+/// the embedded fourth-order one.
+fn dormand_prince<V, S>(y: V, h: S, f: &impl Fn(V) -> V) -> (V, f64)
+where
+    V: Copy + Add<Output = V> + Mul<S, Output = V> + Norm,
+    S: Copy + From<f32> + Div<Output = S> + Mul<Output = S>,
+{
+    // Integer ratios are exact in f32, so the f64 path loses no precision to the coefficients.
+    let c = |numerator: f32, denominator: f32| h * (S::from(numerator) / S::from(denominator));
+
+    let k1 = f(y);
+    let k2 = f(y + k1 * c(1., 5.));
+    let k3 = f(y + k1 * c(3., 40.) + k2 * c(9., 40.));
+    let k4 = f(y + k1 * c(44., 45.) + k2 * c(-56., 15.) + k3 * c(32., 9.));
+    let k5 = f(y
+        + k1 * c(19372., 6561.)
+        + k2 * c(-25360., 2187.)
+        + k3 * c(64448., 6561.)
+        + k4 * c(-212., 729.));
+    let k6 = f(y
+        + k1 * c(9017., 3168.)
+        + k2 * c(-355., 33.)
+        + k3 * c(46732., 5247.)
+        + k4 * c(49., 176.)
+        + k5 * c(-5103., 18656.));
+
+    let fifth = y
+        + k1 * c(35., 384.)
+        + k3 * c(500., 1113.)
+        + k4 * c(125., 192.)
+        + k5 * c(-2187., 6784.)
+        + k6 * c(11., 84.);
+    // The fourth-order solution also weighs the slope at the fifth-order result.
+    let k7 = f(fifth);
+    // Difference between the fifth- and fourth-order weights.
+    let error = k1 * c(71., 57600.)
+        + k3 * c(-71., 16695.)
+        + k4 * c(71., 1920.)
+        + k5 * c(-17253., 339200.)
+        + k6 * c(22., 525.)
+        + k7 * c(-1., 40.);
+    (fifth, error.norm())
+}
+
+/// Covers `dt` in as many substeps as the error tolerance needs, returning the new position and
+/// the number of accepted substeps.
+fn rk45<V, S>(mut position: V, dt: S, tolerance: f64, f: impl Fn(V) -> V) -> (V, u32)
+where
+    V: Copy + Add<Output = V> + Mul<S, Output = V> + Norm,
+    S: Copy + From<f32> + Div<Output = S> + Mul<Output = S>,
+{
+    // Fraction of dt still to cover and size of the next substep, both relative to dt.
+    let (mut remaining, mut fraction) = (1_f32, 1_f32);
+    let mut accepted = 0;
+    for substep in 1..=RK45_MAX_SUBSTEPS {
+        fraction = fraction.min(remaining);
+        let (next, error) = dormand_prince(position, dt * S::from(fraction), &f);
+        let tolerance = tolerance * position.norm().max(1.);
+
+        let last_chance = substep == RK45_MAX_SUBSTEPS;
+        if error <= tolerance || last_chance {
+            position = next;
+            remaining -= fraction;
+            accepted += 1;
+            if remaining <= f32::EPSILON {
+                break;
+            }
+        }
+
+        let scale = if error > 0. {
+            0.9 * (tolerance / error).powf(0.2)
+        } else {
+            5.
+        };
+        fraction *= scale.clamp(0.1, 5.) as f32;
+        if last_chance {
+            // Out of substeps, cover the rest in one go rather than stopping short.
+            return (
+                dormand_prince(position, dt * S::from(remaining), &f).0,
+                accepted + 1,
+            );
+        }
+    }
+    (position, accepted)
+}
+
+/// Substep size the adaptive scheme ended up using on the last physics tick, in simulated
+/// seconds.
+#[derive(Resource, Default)]
+pub struct EffectiveStep {
+    pub smallest: f32,
+    pub mean: f32,
+}
+
+impl EffectiveStep {
+    /// Summarizes the substep counts of all heads for a tick of length `dt`.
+    pub fn record(&mut self, dt: f32, substeps: &[u32]) {
+        let Some(&most) = substeps.iter().max() else {
+            return;
+        };
+        self.smallest = dt / most as f32;
+        self.mean =
+            substeps.iter().map(|&count| dt / count as f32).sum::<f32>() / substeps.len() as f32;
+    }
+}
+
+/// The effective step of the adaptive scheme, next to the configured one.
+pub fn effective_step_ui(world: &mut World, ui: &mut egui::Ui) {
+    let config = world.resource::<Configuration>();
+    if config.integrator != Integrator::Rk45 {
+        return;
+    }
+    let step = world.resource::<EffectiveStep>();
+    ui.label(format!(
+        "Effective dt: {:.2e} mean, {:.2e} smallest (configured {:.2e})",
+        step.mean,
+        step.smallest,
+        config.delta_t as f32 / 10000.
+    ));
+}
@@ -0,0 +1,264 @@
+use std::fs;
+
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContext};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    gui,
+    playback::{PendingSteps, SimulationState},
+    validation::{MAX_TIME_SCALE, MIN_TIME_SCALE},
+    Configuration,
+};
+
+/// File the bindings are saved to whenever they change, relative to the working directory.
+const KEY_BINDINGS_PATH: &str = "key_bindings.ron";
+/// Keys the fly camera moves with, ignored as shortcuts while it is active.
+const FLY_CAMERA_KEYS: [KeyCode; 6] = [
+    KeyCode::KeyW,
+    KeyCode::KeyA,
+    KeyCode::KeyS,
+    KeyCode::KeyD,
+    KeyCode::Space,
+    KeyCode::KeyC,
+];
+/// Factor the time scale changes by per speed up or down.
+const SPEED_STEP: f32 = 1.25;
+
+pub struct KeyBindingsPlugin;
+
+impl Plugin for KeyBindingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(load_bindings())
+            .init_resource::<Rebinding>()
+            .add_systems(Update, run_bound_actions);
+    }
+}
+
+/// Everything a key can be bound to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Action {
+    Pause,
+    /// Advances a single tick while paused.
+    Step,
+    Clear,
+    Restart,
+    ToggleDiagnostics,
+    ToggleCameraRotation,
+    SpeedUp,
+    SpeedDown,
+}
+
+impl Action {
+    const ALL: [Action; 8] = [
+        Action::Pause,
+        Action::Step,
+        Action::Clear,
+        Action::Restart,
+        Action::ToggleDiagnostics,
+        Action::ToggleCameraRotation,
+        Action::SpeedUp,
+        Action::SpeedDown,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            Action::Pause => "Pause / resume",
+            Action::Step => "Step one tick",
+            Action::Clear => "Clear",
+            Action::Restart => "Restart",
+            Action::ToggleDiagnostics => "Toggle diagnostics",
+            Action::ToggleCameraRotation => "Toggle camera rotation",
+            Action::SpeedUp => "Speed up",
+            Action::SpeedDown => "Slow down",
+        }
+    }
+
+    fn default_key(self) -> KeyCode {
+        match self {
+            Action::Pause => KeyCode::Space,
+            Action::Step => KeyCode::Period,
+            Action::Clear => KeyCode::Backspace,
+            Action::Restart => KeyCode::KeyR,
+            Action::ToggleDiagnostics => KeyCode::F3,
+            Action::ToggleCameraRotation => KeyCode::KeyO,
+            Action::SpeedUp => KeyCode::Equal,
+            Action::SpeedDown => KeyCode::Minus,
+        }
+    }
+
+    fn run(self, world: &mut World) {
+        match self {
+            Action::Pause => world.resource_mut::<SimulationState>().toggle(),
+            Action::Step => {
+                if *world.resource::<SimulationState>() == SimulationState::Paused {
+                    world.resource_mut::<PendingSteps>().0 += 1;
+                }
+            }
+            Action::Clear => gui::clear(world),
+            Action::Restart => {
+                gui::clear(world);
+                gui::start(world);
+            }
+            Action::ToggleDiagnostics => {
+                let mut config = world.resource_mut::<Configuration>();
+                config.show_diagnostics = !config.show_diagnostics;
+            }
+            Action::ToggleCameraRotation => {
+                let mut config = world.resource_mut::<Configuration>();
+                config.rotate_camera = !config.rotate_camera;
+            }
+            Action::SpeedUp => scale_time(world, SPEED_STEP),
+            Action::SpeedDown => scale_time(world, 1. / SPEED_STEP),
+        }
+    }
+}
+
+fn scale_time(world: &mut World, factor: f32) {
+    let mut config = world.resource_mut::<Configuration>();
+    config.time_scale = (config.time_scale * factor).clamp(MIN_TIME_SCALE, MAX_TIME_SCALE);
+}
+
+/// The key of every action, `None` for unbound ones.
+#[derive(Resource, Clone, PartialEq, Serialize, Deserialize)]
+pub struct KeyBindings(pub Vec<(Action, Option<KeyCode>)>);
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self(
+            Action::ALL
+                .into_iter()
+                .map(|action| (action, Some(action.default_key())))
+                .collect(),
+        )
+    }
+}
+
+/// The action waiting for its new key while rebinding.
+#[derive(Resource, Default)]
+struct Rebinding(Option<Action>);
+
+/// The saved bindings, with the defaults for actions that were added since they were saved.
+fn load_bindings() -> KeyBindings {
+    let Ok(content) = fs::read_to_string(KEY_BINDINGS_PATH) else {
+        return KeyBindings::default();
+    };
+    let mut saved = match ron::from_str::<KeyBindings>(&content) {
+        Ok(saved) => saved,
+        Err(err) => {
+            warn!("Ignoring unreadable {KEY_BINDINGS_PATH}: {err}");
+            return KeyBindings::default();
+        }
+    };
+    for (action, key) in KeyBindings::default().0 {
+        if !saved
+            .0
+            .iter()
+            .any(|&(saved_action, _)| saved_action == action)
+        {
+            saved.0.push((action, key));
+        }
+    }
+    saved
+}
+
+fn save_bindings(bindings: &KeyBindings) {
+    let result = ron::to_string(bindings)
+        .map_err(|err| err.to_string())
+        .and_then(|content| fs::write(KEY_BINDINGS_PATH, content).map_err(|err| err.to_string()));
+    if let Err(err) = result {
+        error!("Could not save key bindings: {err}");
+    }
+}
+
+/// Runs the actions of the keys pressed this frame, or binds the first of them while
+/// rebinding.
+fn run_bound_actions(world: &mut World) {
+    let pressed: Vec<KeyCode> = world
+        .resource::<ButtonInput<KeyCode>>()
+        .get_just_pressed()
+        .copied()
+        .collect();
+    let Some(&first) = pressed.first() else {
+        return;
+    };
+
+    if let Some(action) = world.resource_mut::<Rebinding>().0.take() {
+        // Escape cancels instead of becoming the binding.
+        if first != KeyCode::Escape {
+            let mut bindings = world.resource_mut::<KeyBindings>();
+            for (bound_action, key) in &mut bindings.0 {
+                if *bound_action == action {
+                    *key = Some(first);
+                } else if *key == Some(first) {
+                    // A key only triggers one action.
+                    *key = None;
+                }
+            }
+            save_bindings(&bindings);
+        }
+        return;
+    }
+
+    if world
+        .query::<&mut EguiContext>()
+        .iter_mut(world)
+        .any(|mut context| context.get_mut().wants_keyboard_input())
+    {
+        return;
+    }
+
+    let fly_camera = world.resource::<Configuration>().fly_camera;
+    let actions: Vec<Action> = world
+        .resource::<KeyBindings>()
+        .0
+        .iter()
+        .filter_map(|&(action, key)| Some((action, key?)))
+        .filter(|(_, key)| pressed.contains(key))
+        .filter(|(_, key)| !(fly_camera && FLY_CAMERA_KEYS.contains(key)))
+        .map(|(action, _)| action)
+        .collect();
+    for action in actions {
+        action.run(world);
+    }
+}
+
+/// Table of all actions with their keys. Clicking a key waits for the next key press to bind
+/// instead.
+pub fn key_bindings_ui(world: &mut World, ui: &mut egui::Ui) {
+    let mut bindings = world.resource::<KeyBindings>().clone();
+    let mut rebinding = world.resource::<Rebinding>().0;
+
+    egui::Grid::new("key_bindings")
+        .striped(true)
+        .show(ui, |ui| {
+            for (action, key) in &mut bindings.0 {
+                ui.label(action.name());
+                let text = match (rebinding == Some(*action), *key) {
+                    (true, _) => "Press a key…".to_string(),
+                    (false, Some(key)) => format!("{key:?}"),
+                    (false, None) => "Unbound".to_string(),
+                };
+                if ui.button(text).clicked() {
+                    rebinding = Some(*action);
+                }
+                if ui
+                    .add_enabled(key.is_some(), egui::Button::new("Unbind"))
+                    .clicked()
+                {
+                    *key = None;
+                }
+                ui.end_row();
+            }
+        });
+
+    if ui.button("Reset to defaults").clicked() {
+        bindings = KeyBindings::default();
+    }
+
+    world.resource_mut::<Rebinding>().0 = rebinding;
+    if bindings != *world.resource::<KeyBindings>() {
+        save_bindings(&bindings);
+        world.insert_resource(bindings);
+    }
+}
@@ -0,0 +1,1216 @@
+mod analysis;
+mod annotations;
+mod api;
+mod attractor;
+mod auto_clear;
+mod autocorrelation;
+mod benchmark;
+mod camera_feel;
+mod camera_follow;
+mod camera_path;
+mod chat;
+mod clock;
+mod coloring;
+mod convergence;
+mod custom_system;
+mod density;
+mod density_volume;
+mod divergence;
+mod emitter;
+mod event_log;
+mod explore;
+mod extensions;
+mod file_dialog;
+mod fly_camera;
+mod fog;
+mod ghost;
+mod gizmo;
+mod glow;
+mod gui;
+mod head_instancing;
+mod head_picking;
+mod headless;
+mod initial_conditions;
+mod instances;
+mod integrator;
+mod key_bindings;
+mod lighting;
+#[cfg(feature = "live-control")]
+mod live_control;
+mod lyapunov;
+mod network;
+mod outline;
+mod overlay;
+mod palette;
+mod parameter_animation;
+mod particles;
+mod persistence;
+mod playback;
+mod poincare;
+mod precision;
+mod presets;
+mod projections;
+mod proximity;
+mod recording;
+mod rewind;
+mod screenshot;
+mod scripting;
+mod segment_budget;
+mod selection;
+mod share;
+mod slice;
+mod snapshot;
+mod sonification;
+mod statistics;
+mod stereo;
+mod symmetry;
+mod time_series;
+mod transparency;
+mod tube;
+mod tutorial;
+mod validation;
+mod vector_field;
+#[cfg(feature = "video-export")]
+mod video;
+mod views;
+mod wall_shadows;
+
+use annotations::AnnotationsPlugin;
+use api::ApiPlugin;
+use attractor::AttractorPlugin;
+pub use attractor::{AttractorSystem, Parameters};
+use auto_clear::AutoClearPlugin;
+use autocorrelation::AutocorrelationPanel;
+pub use benchmark::BenchmarkPlugin;
+use bevy::{
+    math::DVec3,
+    prelude::*,
+    render::{
+        mesh::{CylinderAnchor, CylinderMeshBuilder},
+        render_resource::{AsBindGroup, ShaderRef},
+    },
+};
+use bevy_inspector_egui::{prelude::*, quick::ResourceInspectorPlugin};
+use bevy_panorbit_camera::{PanOrbitCamera, PanOrbitCameraPlugin};
+use camera_feel::{CameraFeel, CameraFeelPlugin};
+use camera_follow::CameraFollowPlugin;
+use camera_path::CameraPathPlugin;
+use chat::ChatPlugin;
+use clock::ClockPlugin;
+use coloring::{ColorScheme, ColoringPlugin, SegmentHistory, TrailColoring, TrailPalette};
+use convergence::ConvergencePanel;
+use custom_system::CustomSystem;
+use density::DensityPlugin;
+use density_volume::{DensityVolumeMode, DensityVolumePlugin};
+use divergence::DivergencePanel;
+use emitter::EmitterPlugin;
+use event_log::{EventLogPlugin, LogCategory, LogEntry};
+use explore::ExplorePlugin;
+pub use extensions::{AppVisualizationExt, Visualization, VisualizationSet};
+use fly_camera::FlyCameraPlugin;
+use fog::FogPlugin;
+use gizmo::TranslationGizmoPlugin;
+use glow::GlowPlugin;
+use gui::ControlUIPlugin;
+use head_instancing::HeadInstancingPlugin;
+use head_picking::HeadPickingPlugin;
+pub use headless::HeadlessPlugin;
+use initial_conditions::InitialConditions;
+use instances::AttractorInstance;
+use integrator::{EffectiveStep, Integrator};
+use iyes_perf_ui::prelude::*;
+use key_bindings::KeyBindingsPlugin;
+use lighting::{LightingPlugin, SceneLight};
+use lyapunov::LyapunovPanel;
+use network::NetworkPlugin;
+use outline::OutlinePlugin;
+use overlay::OverlayPlugin;
+use palette::CommandPalettePlugin;
+use parameter_animation::ParameterAnimationPlugin;
+use particles::ParticlePlugin;
+use persistence::PersistencePlugin;
+use playback::PlaybackPlugin;
+use poincare::PoincarePlugin;
+use precision::{DoublePrecision, PrecisionPlugin, PrecisionTwin};
+use presets::PresetsPlugin;
+pub use presets::{load as load_preset, load_file as load_preset_file};
+use projections::ProjectionsPlugin;
+use proximity::ProximityPlugin;
+use recording::RecordingPlugin;
+use rewind::RewindPlugin;
+use screenshot::CapturePlugin;
+use scripting::ScriptingPlugin;
+use segment_budget::SegmentBudgetPlugin;
+use selection::{Frozen, Selection, SelectionPlugin};
+use serde::{Deserialize, Serialize};
+use slice::SlicePlugin;
+use sonification::SonificationPlugin;
+use statistics::StatisticsPlugin;
+use stereo::{StereoMode, StereoPlugin};
+use symmetry::SymmetryPlugin;
+use time_series::TimeSeriesPanel;
+use transparency::TransparencyPlugin;
+use tube::{TrailStyle, TubePlugin};
+use tutorial::TutorialPlugin;
+use validation::ValidationPlugin;
+use vector_field::VectorFieldPlugin;
+use views::ViewsPlugin;
+use wall_shadows::WallShadowsPlugin;
+
+const NUM_OF_TRAILS: u16 = 10;
+const INITIAL_DISTANCE: f32 = 0.01;
+const TRAIL_LIFETIME: u16 = 100; // in tenths of a second
+const DELTA_T: u8 = 50;
+const TRAIL_RADIUS: f32 = 0.12;
+const HEAD_RADIUS: f32 = 0.3;
+/// Width of the selected head's trail segments relative to the others.
+const SELECTED_TRAIL_WIDTH: f32 = 2.;
+
+#[derive(Reflect, Resource, InspectorOptions, Serialize, Deserialize, Clone, PartialEq)]
+#[reflect(Resource, InspectorOptions)]
+#[serde(default)]
+pub struct Configuration {
+    show_diagnostics: bool,
+    rotate_camera: bool,
+    camera_speed: i32,
+    /// Fly through the scene with WASD and the right mouse button instead of orbiting it.
+    fly_camera: bool,
+    /// Units per second in fly mode.
+    #[inspector(min = 0.0)]
+    fly_speed: f32,
+    orbit_sensitivity: f32,
+    /// How long the orbit camera keeps turning after input stops, from 0 to just below 1.
+    #[inspector(min = 0.0, max = 0.99)]
+    orbit_smoothness: f32,
+    #[inspector(min = 0.0, max = 0.99)]
+    pan_smoothness: f32,
+    zoom_sensitivity: f32,
+    #[inspector(min = 0.0, max = 0.99)]
+    zoom_smoothness: f32,
+    /// How slowly the camera catches up with a followed head, 0 to stay locked onto it.
+    #[inspector(min = 0.0, max = 0.99)]
+    follow_smoothness: f32,
+    #[inspector(min = 1, max = validation::MAX_REFRESH_RATE)]
+    physics_refresh_rate: u16,
+    /// How fast simulated time runs compared to real time, without changing the tick rate or
+    /// step size.
+    #[inspector(min = validation::MIN_TIME_SCALE, max = validation::MAX_TIME_SCALE)]
+    time_scale: f32,
+    /// Explain the equations and mark the fixed points in the scene.
+    annotations: bool,
+    /// Mark the equilibria of the Lorenz system, green while stable and red once unstable.
+    fixed_point_markers: bool,
+    /// Name the marked equilibria and list their eigenvalues.
+    fixed_point_labels: bool,
+    /// Draw labelled coordinate axes, to show scale and orientation in screenshots.
+    overlay_axes: bool,
+    /// Draw a grid in the xy-plane.
+    overlay_grid: bool,
+    /// Draw a box around all heads and trails.
+    overlay_bounding_box: bool,
+    /// Length of the axes from the origin and half the width of the grid.
+    #[inspector(min = 1.0)]
+    overlay_extent: f32,
+    #[inspector(min = 0.0, max = 1.0)]
+    overlay_opacity: f32,
+    /// Draws all heads with one instanced draw call instead of a mesh per head, for thousands
+    /// of heads. Instanced heads are unlit and ignore the slicing plane.
+    instanced_heads: bool,
+    /// Plays a tone following the selected head, or the first one: higher as it rises along z,
+    /// panned left and right with x. Makes the switches between the lobes audible.
+    sonification: bool,
+    #[inspector(min = 0.0, max = 1.0)]
+    sonification_gain: f32,
+    /// Pitch in Hz at `sonification_z_min` and below.
+    #[inspector(min = 20.0, max = 20000.0)]
+    sonification_low_pitch: f32,
+    /// Pitch in Hz at `sonification_z_max` and above.
+    #[inspector(min = 20.0, max = 20000.0)]
+    sonification_high_pitch: f32,
+    sonification_z_min: f32,
+    sonification_z_max: f32,
+    /// Distance from the origin along x at which the tone is panned fully to one side.
+    #[inspector(min = 0.1)]
+    sonification_x_extent: f32,
+    /// Blends translucent trails per pixel in depth order, so overlapping segments don't
+    /// flicker. Turns off multisampling.
+    order_independent_transparency: bool,
+    /// Orthographic instead of perspective projection, which keeps sizes comparable across the
+    /// scene, like in a plot.
+    orthographic: bool,
+    /// Renders a view per eye, side by side or as a red-cyan anaglyph.
+    stereo: StereoMode,
+    /// Distance between the eye cameras.
+    #[inspector(min = 0.0, max = 20.0)]
+    stereo_eye_separation: f32,
+    /// Distance from the camera at which both eyes' views meet, things there appear at screen
+    /// depth. Nearer things pop out of the screen, farther ones sink behind it.
+    #[inspector(min = 1.0)]
+    stereo_convergence: f32,
+    /// Show the x–y, x–z and z–y projections in small orthographic views next to the main one.
+    projection_views: bool,
+    /// Half of the height of the scene each projection view shows.
+    #[inspector(min = 1.0)]
+    projection_extent: f32,
+    /// Seconds of simulated time between automatic resets of the scene, 0 to never reset.
+    #[inspector(min = 0.0)]
+    auto_clear_interval: f32,
+    /// Respawn the heads at their initial conditions on every reset, not only wipe the trails.
+    auto_clear_respawn: bool,
+    /// Store every head's position at every tick for export.
+    record_trajectories: bool,
+    /// Samples kept while recording, the oldest are dropped first. One tick adds one sample
+    /// per head.
+    recording_capacity: u32,
+    /// Physics ticks kept for scrubbing back in time while paused, 0 to disable.
+    rewind_ticks: u32,
+    /// Save the simulation state on exit and offer to resume it on the next launch.
+    autosave_on_exit: bool,
+    #[inspector(min = 1)]
+    trail_lifetime: u16, // in tenths of a second
+    /// Most trail segments kept at once, the oldest are removed first beyond that regardless
+    /// of their lifetime. 0 for no limit.
+    max_segments: u32,
+    #[inspector(min = 1, max = validation::MAX_TRAILS)]
+    num_of_trails: u16,
+    initial_conditions: InitialConditions,
+    initial_distance: f32,
+    /// Seed of the random initial conditions.
+    seed: u64,
+    #[inspector(min = 1)]
+    delta_t: u8,
+    /// Seconds between the starts of successive heads at the configured physics refresh rate,
+    /// 0 to start all at once. Stepping while paused counts towards it.
+    #[inspector(min = 0.0)]
+    spawn_stagger: f32,
+    /// Continuously spawn heads inside a sphere and retire the oldest ones, like a fountain.
+    emitter_enabled: bool,
+    #[inspector(max = validation::MAX_TRAILS)]
+    emitter_population: u16,
+    /// New heads per second.
+    #[inspector(min = 0.0)]
+    emitter_rate: f32,
+    emitter_center: Vec3,
+    #[inspector(min = 0.0)]
+    emitter_radius: f32,
+    /// Detect heads passing closer than `proximity_distance` to each other.
+    proximity_events: bool,
+    #[inspector(min = 0.0)]
+    proximity_distance: f32,
+    /// Briefly brighten both heads of a close approach.
+    proximity_flash: bool,
+    /// Beep on every close approach.
+    proximity_sound: bool,
+    /// Advect a large point cloud through the flow on the GPU, drawn as a glowing density.
+    particles_enabled: bool,
+    particle_count: u32,
+    particle_color: Color,
+    /// Brightness each point adds, lower values need more overlap to saturate.
+    particle_exposure: f32,
+    /// Collect the visits of all heads into a volume, rendered as a glowing fog that shows
+    /// where trajectories spend their time.
+    density_volume: DensityVolumeMode,
+    /// Opacity per unit of length of the densest part of the volume.
+    #[inspector(min = 0.0)]
+    density_volume_opacity: f32,
+    integrator: Integrator,
+    /// Largest local error per RK45 substep, relative to the distance from the origin. Much
+    /// lower and f32 rounding noise alone would exceed it.
+    #[inspector(min = 1e-9)]
+    rk45_tolerance: f64,
+    /// Also integrate every initial condition with RK4, or with Euler when `integrator` is
+    /// RK4 already, to compare their error.
+    compare_integrators: bool,
+    /// Integrate every head in f64 and only round to f32 for rendering, so long runs don't
+    /// drift apart from f32 rounding alone.
+    double_precision: bool,
+    /// Also integrate every initial condition in f64 and mark where both versions diverge.
+    compare_precision: bool,
+    /// System of ODEs to integrate, `sigma`, `rho` and `beta` belong to Lorenz.
+    attractor: AttractorSystem,
+    /// Equations of the `Custom` attractor, edited in the control panel.
+    #[reflect(ignore)]
+    custom_system: CustomSystem,
+    sigma: f32,
+    rho: f32,
+    beta: f32,
+    /// Distance from the origin beyond which a head counts as diverged and is respawned.
+    runaway_limit: f32,
+    /// Palette of the heads, their trails and the gradient trail colorings.
+    color_scheme: ColorScheme,
+    trail_coloring: TrailColoring,
+    /// Cylinder segments, or one continuous tube or ribbon per head without gaps at turns.
+    trail_style: TrailStyle,
+    #[inspector(min = 0.0)]
+    trail_radius: f32,
+    /// Thin trails out toward their tail as they age, instead of keeping them at full radius
+    /// until they vanish.
+    trail_taper: bool,
+    /// Shade heads and trails with physically based lighting instead of flat colors.
+    lit_trails: bool,
+    trail_roughness: f32,
+    trail_metallic: f32,
+    /// Draw a ring around every head that stays visible through other geometry.
+    head_outlines: bool,
+    outline_color: Color,
+    /// Render in HDR and let the heads glow, like classic attractor renders on a dark
+    /// background.
+    bloom_enabled: bool,
+    #[inspector(min = 0.0, max = 1.0)]
+    bloom_intensity: f32,
+    /// Brightness the heads emit beyond their color, which is what spills into the bloom.
+    #[inspector(min = 0.0)]
+    head_glow: f32,
+    /// Fade distant geometry into the fog color to improve depth perception.
+    fog_enabled: bool,
+    fog_color: Color,
+    fog_start: f32,
+    fog_end: f32,
+    /// Image-based lighting from prefiltered KTX2 cubemaps, as produced for Bevy's
+    /// `environment_maps` examples. None ship with the app, so both paths are empty until
+    /// pointed at maps below `assets/`.
+    environment_map_enabled: bool,
+    environment_diffuse_map: String,
+    environment_specular_map: String,
+    environment_intensity: f32,
+    /// Also show the specular cubemap as the scene background.
+    environment_as_background: bool,
+    slice_enabled: bool,
+    /// Clip geometry beyond the slicing plane instead of dimming it.
+    slice_clip: bool,
+    /// Direction of the plane normal in the xy-plane, in degrees.
+    slice_yaw: f32,
+    /// Elevation of the plane normal towards the z-axis, in degrees.
+    slice_pitch: f32,
+    /// Signed distance of the plane from the origin along its normal.
+    slice_offset: f32,
+    /// Poincaré section crossings kept, the oldest are dropped first.
+    crossing_capacity: u32,
+    /// Also draw every trail mirrored through the z-axis, which is a solution as well.
+    symmetric_twins: bool,
+    /// Project the trails onto the floor and back walls of a box around the attractor.
+    wall_shadows: bool,
+    wall_box_min: Vec3,
+    wall_box_max: Vec3,
+    wall_shadow_color: Color,
+    /// Show the vector field as arrows on a grid, pointing along the velocity and longer and
+    /// brighter where it is faster.
+    vector_field: bool,
+    /// Arrows along each axis of the grid.
+    #[inspector(min = 2, max = validation::MAX_FIELD_DENSITY)]
+    vector_field_density: u8,
+    vector_field_min: Vec3,
+    vector_field_max: Vec3,
+}
+
+impl Default for Configuration {
+    fn default() -> Self {
+        Self {
+            show_diagnostics: false,
+            rotate_camera: false,
+            camera_speed: 10,
+            fly_camera: false,
+            fly_speed: 20.,
+            orbit_sensitivity: CameraFeel::RESPONSIVE.orbit_sensitivity,
+            orbit_smoothness: CameraFeel::RESPONSIVE.orbit_smoothness,
+            pan_smoothness: CameraFeel::RESPONSIVE.pan_smoothness,
+            zoom_sensitivity: CameraFeel::RESPONSIVE.zoom_sensitivity,
+            zoom_smoothness: CameraFeel::RESPONSIVE.zoom_smoothness,
+            follow_smoothness: 0.8,
+            physics_refresh_rate: 120,
+            time_scale: 1.,
+            annotations: false,
+            fixed_point_markers: false,
+            fixed_point_labels: true,
+            overlay_axes: false,
+            overlay_grid: false,
+            overlay_bounding_box: false,
+            overlay_extent: 40.,
+            overlay_opacity: 0.6,
+            instanced_heads: false,
+            order_independent_transparency: false,
+            sonification: false,
+            sonification_gain: 0.2,
+            sonification_low_pitch: 110.,
+            sonification_high_pitch: 880.,
+            sonification_z_min: 0.,
+            sonification_z_max: 50.,
+            sonification_x_extent: 25.,
+            orthographic: false,
+            stereo: StereoMode::Off,
+            stereo_eye_separation: 3.,
+            stereo_convergence: 110.,
+            projection_views: false,
+            projection_extent: 35.,
+            auto_clear_interval: 0.,
+            auto_clear_respawn: false,
+            record_trajectories: false,
+            recording_capacity: 1_000_000,
+            rewind_ticks: 7200,
+            autosave_on_exit: false,
+            trail_lifetime: TRAIL_LIFETIME,
+            max_segments: 100_000,
+            num_of_trails: NUM_OF_TRAILS,
+            initial_conditions: InitialConditions::LineAlongDiagonal,
+            initial_distance: INITIAL_DISTANCE,
+            seed: 0,
+            delta_t: DELTA_T,
+            spawn_stagger: 0.,
+            emitter_enabled: false,
+            emitter_population: 50,
+            emitter_rate: 5.,
+            emitter_center: Vec3::new(1., 1., 25.),
+            emitter_radius: 2.,
+            particles_enabled: false,
+            particle_count: 1 << 20,
+            particle_color: Color::srgb(1., 0.6, 0.25),
+            particle_exposure: 0.05,
+            density_volume: DensityVolumeMode::Off,
+            density_volume_opacity: 0.1,
+            proximity_events: false,
+            proximity_distance: 0.5,
+            proximity_flash: true,
+            proximity_sound: false,
+            integrator: Integrator::Euler,
+            rk45_tolerance: 1e-5,
+            compare_integrators: false,
+            double_precision: false,
+            compare_precision: false,
+            attractor: AttractorSystem::Lorenz,
+            custom_system: CustomSystem::default(),
+            sigma: 10.,
+            rho: 28.,
+            beta: 8. / 3.,
+            runaway_limit: 1000.,
+            color_scheme: ColorScheme::Rainbow,
+            trail_coloring: TrailColoring::PerHead,
+            trail_style: TrailStyle::Segments,
+            trail_radius: TRAIL_RADIUS,
+            trail_taper: true,
+            lit_trails: false,
+            trail_roughness: 0.3,
+            trail_metallic: 0.5,
+            head_outlines: false,
+            outline_color: Color::WHITE,
+            bloom_enabled: false,
+            bloom_intensity: 0.3,
+            head_glow: 4.,
+            fog_enabled: false,
+            fog_color: ClearColor::default().0,
+            fog_start: 60.,
+            fog_end: 160.,
+            environment_map_enabled: false,
+            environment_diffuse_map: String::new(),
+            environment_specular_map: String::new(),
+            environment_intensity: 900.,
+            environment_as_background: false,
+            slice_enabled: false,
+            slice_clip: false,
+            slice_yaw: 0.,
+            slice_pitch: 90.,
+            slice_offset: 27.,
+            crossing_capacity: 100_000,
+            symmetric_twins: false,
+            wall_shadows: false,
+            wall_box_min: Vec3::new(-25., -30., 0.),
+            wall_box_max: Vec3::new(25., 30., 55.),
+            wall_shadow_color: Color::srgba(0.6, 0.6, 0.7, 0.5),
+            vector_field: false,
+            vector_field_density: 10,
+            vector_field_min: Vec3::new(-25., -30., 0.),
+            vector_field_max: Vec3::new(25., 30., 55.),
+        }
+    }
+}
+
+/// Values to replace in a [`Configuration`] before starting, from the command line for
+/// example. `None` keeps the configured value.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ConfigurationOverrides {
+    pub num_of_trails: Option<u16>,
+    pub sigma: Option<f32>,
+    pub rho: Option<f32>,
+    pub beta: Option<f32>,
+    /// In units of 0.0001, like `delta_t`.
+    pub delta_t: Option<u8>,
+    pub physics_refresh_rate: Option<u16>,
+}
+
+impl Configuration {
+    pub fn sigma(&self) -> f32 {
+        self.sigma
+    }
+
+    pub fn rho(&self) -> f32 {
+        self.rho
+    }
+
+    pub fn beta(&self) -> f32 {
+        self.beta
+    }
+
+    pub fn attractor(&self) -> AttractorSystem {
+        self.attractor
+    }
+
+    pub fn num_of_trails(&self) -> u16 {
+        self.num_of_trails
+    }
+
+    /// Simulated seconds per physics tick.
+    pub fn delta_t(&self) -> f32 {
+        self.delta_t as f32 / 10000.
+    }
+
+    /// Physics ticks per second of simulated time.
+    pub fn physics_refresh_rate(&self) -> u16 {
+        self.physics_refresh_rate
+    }
+
+    pub fn time_scale(&self) -> f32 {
+        self.time_scale
+    }
+
+    /// Applies `overrides` and checks the result like a loaded preset.
+    pub fn with_overrides(mut self, overrides: &ConfigurationOverrides) -> Result<Self, String> {
+        self.num_of_trails = overrides.num_of_trails.unwrap_or(self.num_of_trails);
+        self.sigma = overrides.sigma.unwrap_or(self.sigma);
+        self.rho = overrides.rho.unwrap_or(self.rho);
+        self.beta = overrides.beta.unwrap_or(self.beta);
+        self.delta_t = overrides.delta_t.unwrap_or(self.delta_t);
+        self.physics_refresh_rate = overrides
+            .physics_refresh_rate
+            .unwrap_or(self.physics_refresh_rate);
+        self.validate()?;
+        Ok(self)
+    }
+
+    /// Rejects values the simulation can't run with.
+    fn validate(&self) -> Result<(), String> {
+        if self.num_of_trails == 0 {
+            return Err("num_of_trails must be at least 1".into());
+        }
+        if self.trail_lifetime == 0 {
+            return Err("trail_lifetime must be at least 1".into());
+        }
+        if self.physics_refresh_rate == 0 {
+            return Err("physics_refresh_rate must be at least 1".into());
+        }
+        if self.delta_t == 0 {
+            return Err("delta_t must be at least 1".into());
+        }
+        if ![self.sigma, self.rho, self.beta, self.initial_distance]
+            .iter()
+            .all(|value| value.is_finite())
+        {
+            return Err("sigma, rho, beta and initial_distance must be finite".into());
+        }
+        if self.runaway_limit.is_nan() || self.runaway_limit <= 0. {
+            return Err("runaway_limit must be positive".into());
+        }
+        if !(self.spawn_stagger >= 0. && self.spawn_stagger.is_finite()) {
+            return Err("spawn_stagger must be a non-negative number".into());
+        }
+        if !(self.emitter_rate >= 0. && self.emitter_rate.is_finite()) {
+            return Err("emitter_rate must be a non-negative number".into());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Component)]
+#[require(SegmentHistory)]
+pub struct TrailHead;
+
+/// Position of the head's initial condition in the spawn order, starting at 1.
+#[derive(Component)]
+struct HeadIndex(u16);
+
+/// Where a head started, to restart it from after its state became unusable.
+#[derive(Component)]
+struct InitialCondition(Vec3);
+
+/// Keeps a head at its initial condition for this much more simulated time, counted down with
+/// the [`SimulationClock`](clock::SimulationClock) on every physics tick.
+#[derive(Component, Clone, Deref, DerefMut)]
+struct BirthDelay(f64);
+
+#[derive(Component)]
+struct TrailData {
+    mesh: Handle<Mesh>,
+    material: Handle<SimpleColorMaterial>,
+}
+
+#[derive(Component, Deref, DerefMut)]
+struct TimeOfBirth(f32);
+
+/// Links a trail segment to the head that left it.
+#[derive(Component, Clone, Copy)]
+pub struct SegmentOf(Entity);
+
+impl SegmentOf {
+    pub fn head(&self) -> Entity {
+        self.0
+    }
+}
+
+/// The simulation and its rendering: trail heads, trail segments, the camera and every
+/// effect that doesn't need a window of its own. Expects `DefaultPlugins` and the `assets/`
+/// directory of this crate.
+pub struct LorenzPlugin;
+
+impl Plugin for LorenzPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins((
+            MaterialPlugin::<SimpleColorMaterial>::default(),
+            DensityVolumePlugin,
+            HeadInstancingPlugin,
+            PanOrbitCameraPlugin,
+            TransparencyPlugin,
+        ))
+        .add_plugins((
+            ApiPlugin,
+            AttractorPlugin,
+            AutoClearPlugin,
+            CameraFeelPlugin,
+            ChatPlugin,
+            ColoringPlugin,
+            EmitterPlugin,
+            FogPlugin,
+            LightingPlugin,
+            NetworkPlugin,
+            OutlinePlugin,
+            ParticlePlugin,
+            PrecisionPlugin,
+            ProximityPlugin,
+            SelectionPlugin,
+        ))
+        .add_plugins((
+            CameraFollowPlugin,
+            CapturePlugin,
+            GlowPlugin,
+            PlaybackPlugin,
+            RewindPlugin,
+            SegmentBudgetPlugin,
+            SlicePlugin,
+            StereoPlugin,
+            SymmetryPlugin,
+            TranslationGizmoPlugin,
+            TubePlugin,
+            ValidationPlugin,
+            VectorFieldPlugin,
+            ViewsPlugin,
+            WallShadowsPlugin,
+        ))
+        .add_event::<LogEntry>()
+        .init_resource::<Configuration>()
+        .init_resource::<EffectiveStep>()
+        .register_type::<Configuration>()
+        .add_systems(Startup, setup)
+        .add_systems(
+            Update,
+            apply_physics_refresh_rate.run_if(|config: Res<Configuration>| config.is_changed()),
+        )
+        .add_systems(
+            Update,
+            rotate_camera.run_if(|config: Res<Configuration>| config.rotate_camera),
+        )
+        .add_systems(
+            FixedUpdate,
+            (wake_staggered_heads, update_position)
+                .chain()
+                .run_if(network::simulation_is_local),
+        )
+        .add_systems(
+            Update,
+            (shrink_trail_segments, remove_old_trail_segments).chain(),
+        );
+    }
+}
+
+/// The control panel, the configuration inspector and all analysis windows on top of
+/// [`LorenzPlugin`].
+pub struct LorenzGuiPlugin;
+
+impl Plugin for LorenzGuiPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins((
+            ControlUIPlugin,
+            ResourceInspectorPlugin::<Configuration>::default(),
+            AnnotationsPlugin,
+            CameraPathPlugin,
+            ClockPlugin,
+            CommandPalettePlugin,
+            DensityPlugin,
+            EventLogPlugin,
+            ExplorePlugin,
+            FlyCameraPlugin,
+            HeadPickingPlugin,
+            KeyBindingsPlugin,
+        ))
+        .add_plugins((
+            OverlayPlugin,
+            ParameterAnimationPlugin,
+            PersistencePlugin,
+            PoincarePlugin,
+            PresetsPlugin,
+            ProjectionsPlugin,
+            RecordingPlugin,
+            ScriptingPlugin,
+            SonificationPlugin,
+            StatisticsPlugin,
+            TutorialPlugin,
+        ))
+        .add_visualization(AutocorrelationPanel)
+        .add_visualization(ConvergencePanel)
+        .add_visualization(DivergencePanel)
+        .add_visualization(LyapunovPanel)
+        .add_visualization(TimeSeriesPanel)
+        //
+        .add_plugins((
+            bevy::diagnostic::FrameTimeDiagnosticsPlugin,
+            bevy::diagnostic::EntityCountDiagnosticsPlugin,
+        ))
+        .add_plugins(PerfUiPlugin)
+        .add_systems(
+            Update,
+            toggle_diagnostics
+                .before(iyes_perf_ui::PerfUiSet::Setup)
+                .run_if(|config: Res<Configuration>| config.is_changed()),
+        );
+
+        #[cfg(feature = "live-control")]
+        app.add_plugins(live_control::LiveControlPlugin);
+        #[cfg(feature = "video-export")]
+        app.add_plugins(video::VideoExportPlugin);
+
+        // Browsers don't expose CPU and memory usage.
+        #[cfg(not(target_arch = "wasm32"))]
+        app.add_plugins(bevy::diagnostic::SystemInformationDiagnosticsPlugin);
+    }
+}
+
+fn setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut simple_color_materials: ResMut<Assets<SimpleColorMaterial>>,
+    config: Res<Configuration>,
+) {
+    commands.insert_resource(Time::<Fixed>::from_hz(config.physics_refresh_rate as f64));
+
+    spawn_trail_heads(
+        &mut commands,
+        &mut meshes,
+        &mut simple_color_materials,
+        &config,
+        None,
+    );
+
+    commands.spawn((
+        SceneLight,
+        DirectionalLight::default(),
+        Transform::from_xyz(40., 20., 80.).looking_at(Vec3::new(0., 0., 30.), Vec3::Z),
+    ));
+
+    commands.spawn((
+        Transform::from_translation(Vec3::new(1., 0., 1.) * 80.),
+        PanOrbitCamera {
+            focus: Vec3::new(0., 0., 30.),
+            ..default()
+        },
+    ));
+}
+
+/// Spawns the heads of the main attractor, or of `instance` as its children.
+fn spawn_trail_heads(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    simple_color_materials: &mut Assets<SimpleColorMaterial>,
+    config: &Configuration,
+    instance: Option<Entity>,
+) {
+    let (head_mesh, trail_mesh) = add_head_meshes(meshes);
+
+    // When comparing, the twin of each head gets the opposite color of the scheme.
+    let reference = match config.integrator {
+        Integrator::Rk4 => Integrator::Euler,
+        _ => Integrator::Rk4,
+    };
+    let integrators: &[(Integrator, f32)] = if config.compare_integrators {
+        &[(config.integrator, 0.), (reference, 0.5)]
+    } else {
+        &[(config.integrator, 0.)]
+    };
+
+    let positions = config.initial_conditions.positions(
+        config.num_of_trails,
+        config.initial_distance,
+        config.seed,
+    );
+    for (i, initial_pos) in (1..).zip(positions) {
+        let ratio = i as f32 / NUM_OF_TRAILS as f32;
+
+        for &(integrator, offset) in integrators {
+            let head_color = config.color_scheme.head_color(ratio + offset);
+            let head_material = simple_color_materials.add(SimpleColorMaterial {
+                color: head_color.into(),
+                ..default()
+            });
+            let trail_material = simple_color_materials.add(SimpleColorMaterial {
+                color: head_color.with_saturation(0.3).into(),
+                ..default()
+            });
+
+            let head = commands
+                .spawn((
+                    TrailHead,
+                    HeadIndex(i),
+                    integrator,
+                    InitialCondition(initial_pos),
+                    Mesh3d(head_mesh.clone()),
+                    MeshMaterial3d(head_material.clone()),
+                    Transform::from_translation(initial_pos),
+                    TrailData {
+                        mesh: trail_mesh.clone(),
+                        material: trail_material.clone(),
+                    },
+                ))
+                .id();
+
+            let birth_delay = (i > 1 && config.spawn_stagger > 0.).then(|| {
+                let ticks = (i - 1) as f64
+                    * config.spawn_stagger as f64
+                    * config.physics_refresh_rate as f64;
+                BirthDelay(ticks * config.delta_t as f64 / 10000.)
+            });
+            if let Some(birth_delay) = birth_delay.clone() {
+                commands.entity(head).insert(birth_delay);
+            }
+            if let Some(instance) = instance {
+                commands.entity(head).set_parent(instance);
+            }
+            if config.double_precision {
+                commands
+                    .entity(head)
+                    .insert(DoublePrecision(initial_pos.as_dvec3()));
+            }
+
+            // Both twins would be f64 already.
+            if config.compare_precision && !config.double_precision {
+                let twin_color = head_color.with_lightness(0.85);
+                let mut twin = commands.spawn((
+                    TrailHead,
+                    HeadIndex(i),
+                    integrator,
+                    DoublePrecision(initial_pos.as_dvec3()),
+                    PrecisionTwin(head),
+                    InitialCondition(initial_pos),
+                    Mesh3d(head_mesh.clone()),
+                    MeshMaterial3d(simple_color_materials.add(SimpleColorMaterial {
+                        color: twin_color.into(),
+                        ..default()
+                    })),
+                    Transform::from_translation(initial_pos),
+                    TrailData {
+                        mesh: trail_mesh.clone(),
+                        material: simple_color_materials.add(SimpleColorMaterial {
+                            color: twin_color.with_saturation(0.3).into(),
+                            ..default()
+                        }),
+                    },
+                ));
+                if let Some(birth_delay) = birth_delay {
+                    twin.insert(birth_delay);
+                }
+                if let Some(instance) = instance {
+                    twin.set_parent(instance);
+                }
+            }
+        }
+    }
+}
+
+/// Adds the meshes of a head and of its trail segments.
+fn add_head_meshes(meshes: &mut Assets<Mesh>) -> (Handle<Mesh>, Handle<Mesh>) {
+    let head_mesh = meshes.add(Sphere::new(HEAD_RADIUS));
+    let trail_mesh = meshes.add(
+        // Unit radius, segments are scaled to `trail_radius`.
+        CylinderMeshBuilder::new(1., 1., 32)
+            .anchor(CylinderAnchor::Bottom)
+            .without_caps()
+            .build(),
+    );
+    (head_mesh, trail_mesh)
+}
+
+fn apply_physics_refresh_rate(config: Res<Configuration>, mut fixed_time: ResMut<Time<Fixed>>) {
+    fixed_time.set_timestep_hz(std::cmp::max(config.physics_refresh_rate, 1) as f64);
+}
+
+fn toggle_diagnostics(
+    mut commands: Commands,
+    q_root: Query<Entity, With<PerfUiRoot>>,
+    config: Res<Configuration>,
+) {
+    if config.show_diagnostics {
+        if q_root.get_single().is_err() {
+            commands.spawn(PerfUiDefaultEntries::default());
+        }
+    } else {
+        if let Ok(e) = q_root.get_single() {
+            commands.entity(e).despawn_recursive();
+        }
+    }
+}
+
+fn rotate_camera(mut query: Query<&mut PanOrbitCamera>, config: Res<Configuration>) {
+    for mut camera in &mut query {
+        camera.target_yaw += config.camera_speed as f32 / 10_000.;
+    }
+}
+
+fn velocity(config: &Configuration, position: Vec3) -> Vec3 {
+    config
+        .attractor
+        .velocity(config, &Parameters::of(config), position)
+}
+
+fn velocity_f64(config: &Configuration, position: DVec3) -> DVec3 {
+    config
+        .attractor
+        .velocity_f64(config, &Parameters::of(config), position)
+}
+
+/// Runs once per physics tick, also for the ticks stepped by hand while paused, during which
+/// virtual time stands still.
+fn wake_staggered_heads(
+    mut query: Query<(Entity, &mut BirthDelay)>,
+    mut commands: Commands,
+    config: Res<Configuration>,
+) {
+    let step = config.delta_t as f64 / 10000.;
+    for (entity, mut birth_delay) in &mut query {
+        **birth_delay -= step;
+        // Half a step of slack for the rounding the subtractions pile up.
+        if **birth_delay < step / 2. {
+            commands.entity(entity).remove::<BirthDelay>();
+        }
+    }
+}
+
+fn update_position(
+    mut query: Query<
+        (
+            Entity,
+            &mut Transform,
+            &TrailData,
+            &Integrator,
+            &InitialCondition,
+            Option<&mut DoublePrecision>,
+            &mut SegmentHistory,
+            Option<&Parent>,
+        ),
+        (With<TrailHead>, Without<BirthDelay>, Without<Frozen>),
+    >,
+    instances: Query<(Entity, &AttractorInstance, &Transform), Without<TrailHead>>,
+    mut commands: Commands,
+    mut log: EventWriter<LogEntry>,
+    mut effective_step: ResMut<EffectiveStep>,
+    time: Res<Time<Virtual>>,
+    config: Res<Configuration>,
+    palette: Res<TrailPalette>,
+) {
+    let instances = instances::instance_parameters(&instances);
+    let main_parameters = Parameters::of(&config);
+    let mut substeps = Vec::new();
+
+    for (
+        entity,
+        mut transform,
+        trail_data,
+        integrator,
+        initial_condition,
+        mut double_precision,
+        mut history,
+        parent,
+    ) in &mut query
+    {
+        // Heads of an instance follow its parameters and leave their trail where the instance
+        // is placed.
+        let (parameters, offset) = match parent.and_then(|parent| instances.get(&parent.get())) {
+            Some((parameters, offset)) => (parameters, *offset),
+            None => (&main_parameters, Vec3::ZERO),
+        };
+        let old_translation = transform.translation.clone();
+
+        let dt = config.delta_t as f32 / 10000.;
+        let tolerance = config.rk45_tolerance;
+        let (new_translation, steps) = match double_precision.as_deref_mut() {
+            Some(state) => {
+                let (next, steps) =
+                    integrator.step_counted(**state, dt as f64, tolerance, |position| {
+                        config.attractor.velocity_f64(&config, parameters, position)
+                    });
+                **state = next;
+                (state.as_vec3(), steps)
+            }
+            None => integrator.step_counted(old_translation, dt, tolerance, |position| {
+                config.attractor.velocity(&config, parameters, position)
+            }),
+        };
+        if *integrator == Integrator::Rk45 {
+            substeps.push(steps);
+        }
+
+        // A NaN never recovers and runaway heads only head further out, so start them over
+        // instead of letting them poison the trail and everything that reads head positions.
+        let problem = if !new_translation.is_finite() {
+            Some("reached a non-finite state")
+        } else if new_translation.length() > config.runaway_limit {
+            Some("ran away to infinity")
+        } else {
+            None
+        };
+        if let Some(problem) = problem {
+            warn!("Head near {old_translation} {problem}, respawning it");
+            log.send(
+                LogEntry::new(
+                    LogCategory::Respawn,
+                    format!("Respawned a head that {problem} near {old_translation}"),
+                )
+                .notify(),
+            );
+            transform.translation = initial_condition.0;
+            if let Some(state) = double_precision.as_deref_mut() {
+                **state = initial_condition.0.as_dvec3();
+            }
+            *history = SegmentHistory::default();
+            continue;
+        }
+
+        let delta = new_translation - old_translation;
+        transform.translation = new_translation;
+
+        // Heads resting on a fixed point don't move, and a zero-length segment has no
+        // direction to orient it by. Tubes are built from the head positions instead.
+        if delta.try_normalize().is_none() || config.trail_style != TrailStyle::Segments {
+            continue;
+        }
+
+        let material = palette.segment_material(
+            &config,
+            parameters,
+            trail_data,
+            &mut history,
+            old_translation,
+            delta,
+        );
+        spawn_trail_segment(
+            &mut commands,
+            trail_data.mesh.clone(),
+            material,
+            entity,
+            old_translation + offset,
+            delta,
+            time.elapsed_secs(),
+        );
+    }
+
+    effective_step.record(config.delta_t as f32 / 10000., &substeps);
+}
+
+fn spawn_trail_segment(
+    commands: &mut Commands,
+    mesh: Handle<Mesh>,
+    material: Handle<SimpleColorMaterial>,
+    owner: Entity,
+    start: Vec3,
+    delta: Vec3,
+    time_of_birth: f32,
+) {
+    let Some(direction) = delta.try_normalize() else {
+        return;
+    };
+    commands.spawn((
+        Mesh3d(mesh),
+        MeshMaterial3d(material),
+        Transform::from_translation(start)
+            // Sized by `shrink_trail_segments` before it is drawn.
+            .with_scale(Vec3::new(0., delta.length(), 0.))
+            .with_rotation(Quat::from_rotation_arc(Vec3::Y, direction)),
+        TimeOfBirth(time_of_birth),
+        SegmentOf(owner),
+    ));
+}
+
+/// Sizes segments to the trail radius, thinned out over their lifetime when tapering. The
+/// selected head's trail is drawn thicker.
+fn shrink_trail_segments(
+    mut query: Query<(&mut TimeOfBirth, &mut Transform, Option<&SegmentOf>)>,
+    time: Res<Time>,
+    config: Res<Configuration>,
+    selection: Res<Selection>,
+) {
+    query
+        .par_iter_mut()
+        .for_each(|(mut time_of_birth, mut transform, owner)| {
+            let ratio = 1.
+                - ((time.elapsed_secs() - **time_of_birth) / (config.trail_lifetime as f32 / 10.));
+            let width = match (owner, selection.0) {
+                (Some(owner), Some(selected)) if owner.0 == selected => SELECTED_TRAIL_WIDTH,
+                _ => 1.,
+            };
+            if ratio > 0. {
+                let taper = if config.trail_taper { ratio } else { 1. };
+                let radius = config.trail_radius * width * taper;
+                transform.scale.x = radius;
+                transform.scale.z = radius;
+            } else {
+                // Set time of birth to 0, so we can clean it up later.
+                **time_of_birth = 0.
+            }
+        });
+}
+
+fn remove_old_trail_segments(query: Query<(Entity, &TimeOfBirth)>, mut commands: Commands) {
+    query.iter().for_each(|(entity, time_of_birth)| {
+        if **time_of_birth == 0. {
+            commands.entity(entity).despawn();
+        }
+    });
+}
+
+#[derive(Asset, TypePath, AsBindGroup, Debug, Clone, Default)]
+struct SimpleColorMaterial {
+    #[uniform(0)]
+    color: LinearRgba,
+    /// Slicing plane as (normal, offset); a zero normal disables slicing.
+    #[uniform(1)]
+    slice_plane: Vec4,
+    /// x: half-width of the highlighted band, y: brightness of the far side, z: 1 to clip it.
+    #[uniform(2)]
+    slice_settings: Vec4,
+    /// x: 1 to enable lighting, y: perceptual roughness, z: metallic.
+    #[uniform(3)]
+    lighting: Vec4,
+    /// x: amount to brighten (positive) or darken (negative) the color, used for selection.
+    #[uniform(4)]
+    emphasis: Vec4,
+    /// x: emissive strength, multiples of the color added on top of the shaded result.
+    #[uniform(5)]
+    glow: Vec4,
+}
+
+impl Material for SimpleColorMaterial {
+    fn fragment_shader() -> ShaderRef {
+        "shaders/simple_color.wgsl".into()
+    }
+
+    fn alpha_mode(&self) -> AlphaMode {
+        if self.color.alpha < 1. {
+            AlphaMode::Blend
+        } else {
+            AlphaMode::Opaque
+        }
+    }
+}
@@ -0,0 +1,195 @@
+use bevy::{core_pipeline::Skybox, prelude::*};
+use bevy_egui::egui;
+
+use crate::{coloring::TrailPalette, Configuration, SimpleColorMaterial, TrailData};
+
+pub struct LightingPlugin;
+
+impl Plugin for LightingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, apply_trail_lighting).add_systems(
+            Update,
+            apply_environment_map.run_if(|config: Res<Configuration>| config.is_changed()),
+        );
+    }
+}
+
+/// Keeps the lighting uniforms of head and trail materials in sync with the configuration.
+/// Other materials, like the line-based ghost trails, have no normals and stay unlit.
+fn apply_trail_lighting(
+    heads: Query<(&MeshMaterial3d<SimpleColorMaterial>, &TrailData)>,
+    palette: Res<TrailPalette>,
+    mut simple_color_materials: ResMut<Assets<SimpleColorMaterial>>,
+    config: Res<Configuration>,
+) {
+    let lighting = if config.lit_trails {
+        Vec4::new(1., config.trail_roughness, config.trail_metallic, 0.)
+    } else {
+        Vec4::ZERO
+    };
+
+    let ids = heads
+        .iter()
+        .flat_map(|(head_material, trail_data)| [head_material.id(), trail_data.material.id()])
+        .chain(palette.iter().map(Handle::id));
+
+    for id in ids {
+        if simple_color_materials
+            .get(id)
+            .is_some_and(|material| material.lighting != lighting)
+        {
+            if let Some(material) = simple_color_materials.get_mut(id) {
+                material.lighting = lighting;
+            }
+        }
+    }
+}
+
+/// Adds image-based lighting and, optionally, a skybox from the configured cubemaps to
+/// every 3D camera.
+fn apply_environment_map(
+    mut commands: Commands,
+    cameras: Query<Entity, With<Camera3d>>,
+    asset_server: Res<AssetServer>,
+    config: Res<Configuration>,
+) {
+    for camera in &cameras {
+        let mut camera = commands.entity(camera);
+        // Loading an empty path only fails with an asset error.
+        if !config.environment_map_enabled
+            || config.environment_diffuse_map.is_empty()
+            || config.environment_specular_map.is_empty()
+        {
+            camera.remove::<(EnvironmentMapLight, Skybox)>();
+            continue;
+        }
+
+        let specular_map = asset_server.load(&config.environment_specular_map);
+        camera.insert(EnvironmentMapLight {
+            diffuse_map: asset_server.load(&config.environment_diffuse_map),
+            specular_map: specular_map.clone(),
+            intensity: config.environment_intensity,
+            ..default()
+        });
+
+        if config.environment_as_background {
+            camera.insert(Skybox {
+                image: specular_map,
+                brightness: config.environment_intensity,
+                ..default()
+            });
+        } else {
+            camera.remove::<Skybox>();
+        }
+    }
+}
+
+/// Light that can be edited and removed from the control panel.
+#[derive(Component)]
+pub struct SceneLight;
+
+/// Lists all scene lights with editors for their color, intensity and placement.
+pub fn lights_ui(world: &mut World, ui: &mut egui::Ui) {
+    let lights: Vec<Entity> = world
+        .query_filtered::<Entity, With<SceneLight>>()
+        .iter(world)
+        .collect();
+
+    for (index, light) in lights.into_iter().enumerate() {
+        ui.push_id(index, |ui| {
+            ui.separator();
+            if let Some(mut directional) = world.get_mut::<DirectionalLight>(light) {
+                ui.label("Directional light");
+                color_ui(ui, &mut directional.color);
+                ui.add(
+                    egui::DragValue::new(&mut directional.illuminance)
+                        .speed(10.)
+                        .range(0.0..=f32::MAX)
+                        .prefix("lux: "),
+                );
+
+                let mut transform = world.get_mut::<Transform>(light).unwrap();
+                let mut direction = transform.forward().as_vec3();
+                if vec3_ui(ui, &mut direction, 0.01) {
+                    *transform = transform.looking_to(direction, Vec3::Z);
+                }
+            } else if let Some(mut point) = world.get_mut::<PointLight>(light) {
+                ui.label("Point light");
+                color_ui(ui, &mut point.color);
+                ui.add(
+                    egui::DragValue::new(&mut point.intensity)
+                        .speed(10_000.)
+                        .range(0.0..=f32::MAX)
+                        .prefix("lumens: "),
+                );
+                ui.add(
+                    egui::DragValue::new(&mut point.range)
+                        .speed(1.)
+                        .range(0.0..=f32::MAX)
+                        .prefix("range: "),
+                );
+
+                let mut transform = world.get_mut::<Transform>(light).unwrap();
+                vec3_ui(ui, &mut transform.translation, 0.5);
+            }
+
+            if ui.button("Remove").clicked() {
+                world.despawn(light);
+            }
+        });
+    }
+
+    ui.separator();
+    ui.horizontal(|ui| {
+        if ui.button("Add directional").clicked() {
+            world.spawn((
+                SceneLight,
+                DirectionalLight::default(),
+                Transform::default().looking_to(Vec3::NEG_Z, Vec3::Y),
+            ));
+        }
+        if ui.button("Add point").clicked() {
+            world.spawn((
+                SceneLight,
+                PointLight {
+                    intensity: 10_000_000.,
+                    range: 100.,
+                    ..default()
+                },
+                Transform::from_xyz(0., 0., 30.),
+            ));
+        }
+    });
+}
+
+fn color_ui(ui: &mut egui::Ui, color: &mut Color) {
+    let mut rgb = color.to_srgba().to_f32_array_no_alpha();
+    if ui.color_edit_button_rgb(&mut rgb).changed() {
+        *color = Color::srgb_from_array(rgb);
+    }
+}
+
+/// Shows drag values for the three components; returns whether any of them changed.
+fn vec3_ui(ui: &mut egui::Ui, value: &mut Vec3, speed: f32) -> bool {
+    ui.horizontal(|ui| {
+        ui.add(
+            egui::DragValue::new(&mut value.x)
+                .speed(speed)
+                .prefix("x: "),
+        )
+        .changed()
+            | ui.add(
+                egui::DragValue::new(&mut value.y)
+                    .speed(speed)
+                    .prefix("y: "),
+            )
+            .changed()
+            | ui.add(
+                egui::DragValue::new(&mut value.z)
+                    .speed(speed)
+                    .prefix("z: "),
+            )
+            .changed()
+    })
+    .inner
+}
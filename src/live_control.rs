@@ -0,0 +1,329 @@
+use std::{
+    fs,
+    net::UdpSocket,
+    sync::{Arc, Mutex},
+};
+
+use bevy::prelude::*;
+use bevy_egui::egui;
+use midir::{MidiInput, MidiInputConnection};
+use rosc::{OscPacket, OscType};
+use serde::{Deserialize, Serialize};
+
+use crate::Configuration;
+
+/// Mapping file, relative to the working directory. Written with the defaults when missing, as
+/// a template to edit.
+const MAPPING_PATH: &str = "live_control.ron";
+/// Large enough for any single OSC packet over UDP.
+const PACKET_SIZE: usize = 1536;
+
+pub struct LiveControlPlugin;
+
+impl Plugin for LiveControlPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LiveControl>()
+            .add_systems(Update, apply_live_input);
+    }
+}
+
+/// Configuration values a controller can drive.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+enum Parameter {
+    Sigma,
+    Rho,
+    Beta,
+    DeltaT,
+    CameraSpeed,
+}
+
+impl Parameter {
+    fn set(self, config: &mut Configuration, value: f32) {
+        match self {
+            Parameter::Sigma => config.sigma = value,
+            Parameter::Rho => config.rho = value,
+            Parameter::Beta => config.beta = value,
+            Parameter::DeltaT => config.delta_t = value.round().clamp(1., u8::MAX as f32) as u8,
+            Parameter::CameraSpeed => config.camera_speed = value.round() as i32,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+enum Source {
+    /// First numeric argument of messages to this OSC address, expected in 0..=1.
+    Osc(String),
+    /// A MIDI control change, on a channel from 0 to 15.
+    MidiCc { channel: u8, controller: u8 },
+}
+
+/// Maps a source's normalized value linearly onto `min..=max` of a parameter.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct Mapping {
+    source: Source,
+    parameter: Parameter,
+    min: f32,
+    max: f32,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+struct MappingFile {
+    /// UDP port to receive OSC on, none to not listen.
+    osc_port: Option<u16>,
+    /// Part of the name of the MIDI input to open, none to not open any.
+    midi_input: Option<String>,
+    mappings: Vec<Mapping>,
+}
+
+impl Default for MappingFile {
+    fn default() -> Self {
+        let osc = |address: &str, parameter, min, max| Mapping {
+            source: Source::Osc(address.into()),
+            parameter,
+            min,
+            max,
+        };
+        let cc = |controller, parameter, min, max| Mapping {
+            source: Source::MidiCc {
+                channel: 0,
+                controller,
+            },
+            parameter,
+            min,
+            max,
+        };
+        Self {
+            osc_port: Some(9000),
+            midi_input: Some(String::new()),
+            mappings: vec![
+                osc("/lorenz/sigma", Parameter::Sigma, 1., 30.),
+                osc("/lorenz/rho", Parameter::Rho, 0.5, 60.),
+                osc("/lorenz/beta", Parameter::Beta, 0.1, 8.),
+                osc("/lorenz/dt", Parameter::DeltaT, 1., 20.),
+                osc("/lorenz/camera_speed", Parameter::CameraSpeed, -100., 100.),
+                cc(1, Parameter::Sigma, 1., 30.),
+                cc(2, Parameter::Rho, 0.5, 60.),
+                cc(3, Parameter::Beta, 0.1, 8.),
+                cc(4, Parameter::DeltaT, 1., 20.),
+                cc(5, Parameter::CameraSpeed, -100., 100.),
+            ],
+        }
+    }
+}
+
+/// Receives OSC and MIDI control changes while enabled and applies them to the configuration
+/// through the mappings of [`MAPPING_PATH`].
+#[derive(Resource, Default)]
+struct LiveControl {
+    mapping: MappingFile,
+    socket: Option<UdpSocket>,
+    /// Open MIDI connection, and the control changes it received since the last frame as
+    /// `(channel, controller, value)`.
+    midi: Option<Mutex<MidiInputConnection<()>>>,
+    midi_messages: Arc<Mutex<Vec<(u8, u8, u8)>>>,
+    last_message: Option<String>,
+    status: Option<Result<String, String>>,
+}
+
+impl LiveControl {
+    fn is_enabled(&self) -> bool {
+        self.socket.is_some() || self.midi.is_some()
+    }
+
+    fn enable(&mut self) {
+        let mapping = match load_mapping() {
+            Ok(mapping) => mapping,
+            Err(err) => {
+                self.status = Some(Err(err));
+                return;
+            }
+        };
+        let mut opened = Vec::new();
+        let mut errors = Vec::new();
+
+        if let Some(port) = mapping.osc_port {
+            match UdpSocket::bind(("0.0.0.0", port)).and_then(|socket| {
+                socket.set_nonblocking(true)?;
+                Ok(socket)
+            }) {
+                Ok(socket) => {
+                    self.socket = Some(socket);
+                    opened.push(format!("OSC on port {port}"));
+                }
+                Err(err) => errors.push(format!("OSC port {port}: {err}")),
+            }
+        }
+        if let Some(name) = &mapping.midi_input {
+            match self.connect_midi(name) {
+                Ok(port_name) => opened.push(format!("MIDI from {port_name}")),
+                Err(err) => errors.push(format!("MIDI: {err}")),
+            }
+        }
+
+        self.mapping = mapping;
+        self.status = Some(if errors.is_empty() {
+            Ok(format!("Listening to {}", opened.join(" and ")))
+        } else {
+            Err(errors.join("\n"))
+        });
+    }
+
+    /// Opens the first MIDI input whose name contains `name`.
+    fn connect_midi(&mut self, name: &str) -> Result<String, String> {
+        let input = MidiInput::new("lorenz_system").map_err(|err| err.to_string())?;
+        let port = input
+            .ports()
+            .into_iter()
+            .find(|port| {
+                input
+                    .port_name(port)
+                    .is_ok_and(|port_name| port_name.contains(name))
+            })
+            .ok_or_else(|| format!("no input matching \"{name}\""))?;
+        let port_name = input.port_name(&port).map_err(|err| err.to_string())?;
+
+        let messages = self.midi_messages.clone();
+        let connection = input
+            .connect(
+                &port,
+                "lorenz_system",
+                move |_, message, _| {
+                    // Control change: status 0xB0 to 0xBF, the low bits are the channel.
+                    if let &[status, controller, value] = message {
+                        if status & 0xF0 == 0xB0 {
+                            messages
+                                .lock()
+                                .unwrap()
+                                .push((status & 0x0F, controller, value));
+                        }
+                    }
+                },
+                (),
+            )
+            .map_err(|err| err.to_string())?;
+        self.midi = Some(Mutex::new(connection));
+        Ok(port_name)
+    }
+
+    fn disable(&mut self) {
+        self.socket = None;
+        self.midi = None;
+        self.midi_messages.lock().unwrap().clear();
+        self.status = None;
+    }
+
+    /// Normalized values of every mapped source received since the last call.
+    fn receive(&mut self) -> Vec<(Source, f32)> {
+        let mut received = Vec::new();
+
+        if let Some(socket) = &self.socket {
+            let mut buffer = [0; PACKET_SIZE];
+            while let Ok(size) = socket.recv(&mut buffer) {
+                if let Ok((_, packet)) = rosc::decoder::decode_udp(&buffer[..size]) {
+                    collect_osc(packet, &mut received);
+                }
+            }
+        }
+        received.extend(self.midi_messages.lock().unwrap().drain(..).map(
+            |(channel, controller, value)| {
+                (
+                    Source::MidiCc {
+                        channel,
+                        controller,
+                    },
+                    value as f32 / 127.,
+                )
+            },
+        ));
+        received
+    }
+}
+
+/// Flattens bundles into the messages with a numeric first argument.
+fn collect_osc(packet: OscPacket, received: &mut Vec<(Source, f32)>) {
+    match packet {
+        OscPacket::Message(message) => {
+            let value = match message.args.first() {
+                Some(OscType::Float(value)) => *value,
+                Some(OscType::Double(value)) => *value as f32,
+                Some(OscType::Int(value)) => *value as f32,
+                _ => return,
+            };
+            received.push((Source::Osc(message.addr), value));
+        }
+        OscPacket::Bundle(bundle) => {
+            for packet in bundle.content {
+                collect_osc(packet, received);
+            }
+        }
+    }
+}
+
+fn load_mapping() -> Result<MappingFile, String> {
+    match fs::read_to_string(MAPPING_PATH) {
+        Ok(content) => ron::from_str(&content).map_err(|err| format!("{MAPPING_PATH}: {err}")),
+        Err(_) => {
+            let mapping = MappingFile::default();
+            let content =
+                ron::ser::to_string_pretty(&mapping, default()).map_err(|err| err.to_string())?;
+            if let Err(err) = fs::write(MAPPING_PATH, content) {
+                warn!("Could not write the default {MAPPING_PATH}: {err}");
+            }
+            Ok(mapping)
+        }
+    }
+}
+
+fn apply_live_input(mut live_control: ResMut<LiveControl>, mut config: ResMut<Configuration>) {
+    if !live_control.is_enabled() {
+        return;
+    }
+    let received = live_control.receive();
+    let Some((last_source, _)) = received.last() else {
+        return;
+    };
+    live_control.last_message = Some(format!("{last_source:?}"));
+
+    for (source, value) in received {
+        for mapping in live_control
+            .mapping
+            .mappings
+            .iter()
+            .filter(|mapping| mapping.source == source)
+        {
+            let value = mapping.min + (mapping.max - mapping.min) * value.clamp(0., 1.);
+            mapping.parameter.set(&mut config, value);
+        }
+    }
+}
+
+pub fn live_control_ui(world: &mut World, ui: &mut egui::Ui) {
+    let mut live_control = world.resource_mut::<LiveControl>();
+
+    ui.label(format!("Mappings are read from {MAPPING_PATH}."));
+    let mut enabled = live_control.is_enabled();
+    if ui
+        .checkbox(&mut enabled, "Listen to OSC and MIDI")
+        .changed()
+    {
+        if enabled {
+            live_control.enable();
+        } else {
+            live_control.disable();
+        }
+    }
+
+    match live_control.status.as_ref() {
+        Some(Ok(message)) => {
+            ui.label(message);
+        }
+        Some(Err(err)) => {
+            ui.colored_label(egui::Color32::RED, err);
+        }
+        None => {}
+    }
+    if let Some(message) = &live_control.last_message {
+        ui.label(format!("Last input: {message}"));
+    }
+}
@@ -0,0 +1,181 @@
+use bevy::{math::DVec3, prelude::*};
+use bevy_egui::{egui, EguiContexts};
+use egui_plot::{HLine, Line, Plot, PlotPoints};
+
+use crate::{
+    attractor::AttractorSystem,
+    extensions::{Visualization, VisualizationSet},
+    network, velocity_f64, Configuration, TrailHead,
+};
+
+/// Initial and renormalized distance between the two trajectories of a pair.
+const SEPARATION: f64 = 1e-8;
+/// Ticks between renormalizations, short enough that the pair never leaves the linear regime.
+const RENORMALIZE_INTERVAL: u32 = 10;
+const MAX_PAIRS: usize = 16;
+/// Largest exponent of the classic Lorenz parameters, from the literature.
+const CLASSIC_LORENZ_EXPONENT: f64 = 0.9056;
+
+pub struct LyapunovPanel;
+
+impl Visualization for LyapunovPanel {
+    fn name(&self) -> &'static str {
+        "Lyapunov exponent"
+    }
+
+    fn build(&self, app: &mut App, set: VisualizationSet) {
+        app.init_resource::<LyapunovEstimate>()
+            .add_systems(
+                FixedUpdate,
+                advance_pairs
+                    .run_if(|estimate: Res<LyapunovEstimate>| estimate.running)
+                    .run_if(network::simulation_is_local)
+                    .in_set(set),
+            )
+            .add_systems(Update, lyapunov_ui.in_set(set));
+    }
+}
+
+/// Pairs of nearby trajectories integrated in f64 next to the heads. Their separation grows
+/// like e^(λt), so the average logarithmic growth between renormalizations estimates the
+/// largest exponent λ.
+#[derive(Resource, Default)]
+struct LyapunovEstimate {
+    running: bool,
+    pairs: Vec<(DVec3, DVec3)>,
+    log_growth: f64,
+    /// Simulated time covered by `log_growth`, summed over all pairs.
+    elapsed: f64,
+    ticks: u32,
+    since_renormalization: u32,
+    /// Running estimate over simulated time, for the plot.
+    history: Vec<[f64; 2]>,
+    /// Parameters the pairs were started with, a change invalidates the estimate.
+    parameters: Option<(AttractorSystem, [f32; 3], u8)>,
+}
+
+impl LyapunovEstimate {
+    fn exponent(&self) -> Option<f64> {
+        (self.elapsed > 0.).then(|| self.log_growth / self.elapsed)
+    }
+
+    /// Starts one pair at each head, or at the default initial condition without heads.
+    fn restart(&mut self, starts: impl Iterator<Item = Vec3>, config: &Configuration) {
+        let mut starts: Vec<DVec3> = starts
+            .take(MAX_PAIRS)
+            .map(|start| start.as_dvec3())
+            .collect();
+        if starts.is_empty() {
+            starts.push(DVec3::splat(config.initial_distance as f64));
+        }
+        self.pairs = starts
+            .into_iter()
+            .map(|start| (start, start + DVec3::ONE.normalize() * SEPARATION))
+            .collect();
+        self.log_growth = 0.;
+        self.elapsed = 0.;
+        self.ticks = 0;
+        self.since_renormalization = 0;
+        self.history.clear();
+        self.parameters = Some(parameters(config));
+    }
+}
+
+fn parameters(config: &Configuration) -> (AttractorSystem, [f32; 3], u8) {
+    (
+        config.attractor,
+        [config.sigma, config.rho, config.beta],
+        config.delta_t,
+    )
+}
+
+fn advance_pairs(
+    mut estimate: ResMut<LyapunovEstimate>,
+    heads: Query<&Transform, With<TrailHead>>,
+    config: Res<Configuration>,
+) {
+    if estimate.parameters != Some(parameters(&config)) {
+        estimate.restart(heads.iter().map(|transform| transform.translation), &config);
+    }
+
+    let dt = config.delta_t as f64 / 10000.;
+    let integrator = config.integrator;
+    let field = |position| velocity_f64(&config, position);
+    for (reference, perturbed) in &mut estimate.pairs {
+        *reference = integrator.step(*reference, dt, config.rk45_tolerance, field);
+        *perturbed = integrator.step(*perturbed, dt, config.rk45_tolerance, field);
+    }
+
+    estimate.ticks += 1;
+    estimate.since_renormalization += 1;
+    if estimate.since_renormalization < RENORMALIZE_INTERVAL {
+        return;
+    }
+    estimate.since_renormalization = 0;
+
+    let estimate = &mut *estimate;
+    let interval = dt * RENORMALIZE_INTERVAL as f64;
+    for (reference, perturbed) in &mut estimate.pairs {
+        let offset = *perturbed - *reference;
+        let distance = offset.length();
+        if !distance.is_finite() || distance == 0. {
+            continue;
+        }
+        estimate.log_growth += (distance / SEPARATION).ln();
+        estimate.elapsed += interval;
+        *perturbed = *reference + offset * (SEPARATION / distance);
+    }
+
+    if let Some(exponent) = estimate.exponent() {
+        let t = estimate.ticks as f64 * dt;
+        estimate.history.push([t, exponent]);
+    }
+}
+
+fn lyapunov_ui(
+    mut contexts: EguiContexts,
+    mut estimate: ResMut<LyapunovEstimate>,
+    heads: Query<&Transform, With<TrailHead>>,
+    config: Res<Configuration>,
+) {
+    egui::Window::new("Lyapunov exponent")
+        .default_open(false)
+        .show(contexts.ctx_mut(), |ui| {
+            ui.horizontal(|ui| {
+                let label = if estimate.running { "Stop" } else { "Start" };
+                if ui.button(label).clicked() {
+                    estimate.running = !estimate.running;
+                }
+                if ui.button("Restart").clicked() {
+                    estimate.restart(heads.iter().map(|transform| transform.translation), &config);
+                }
+            });
+
+            match estimate.exponent() {
+                Some(exponent) => ui.label(format!(
+                    "λ ≈ {exponent:.4} from {} pairs over t = {:.1}",
+                    estimate.pairs.len(),
+                    estimate.elapsed / estimate.pairs.len().max(1) as f64
+                )),
+                None => ui.label("Start the estimate to track pairs of nearby trajectories."),
+            };
+            let classic = config.attractor == AttractorSystem::Lorenz
+                && (config.sigma, config.rho, config.beta) == (10., 28., 8. / 3.);
+            if classic {
+                ui.label(format!(
+                    "The literature value for these parameters is {CLASSIC_LORENZ_EXPONENT}."
+                ));
+            }
+
+            Plot::new("lyapunov")
+                .height(200.)
+                .x_axis_label("t")
+                .y_axis_label("λ")
+                .show(ui, |plot_ui| {
+                    plot_ui.line(Line::new(PlotPoints::new(estimate.history.clone())));
+                    if classic {
+                        plot_ui.hline(HLine::new(CLASSIC_LORENZ_EXPONENT));
+                    }
+                });
+        });
+}
@@ -1,261 +1,145 @@
-mod gui;
+use std::{path::PathBuf, process::ExitCode};
 
-use bevy::{
-    prelude::*,
-    render::{
-        mesh::{CylinderAnchor, CylinderMeshBuilder},
-        render_resource::{AsBindGroup, ShaderRef},
-    },
+use bevy::{log::LogPlugin, prelude::*};
+use clap::Parser;
+use lorenz_system::{
+    load_preset, load_preset_file, BenchmarkPlugin, Configuration, ConfigurationOverrides,
+    HeadlessPlugin, LorenzGuiPlugin, LorenzPlugin,
 };
-use bevy_inspector_egui::{prelude::*, quick::ResourceInspectorPlugin};
-use bevy_panorbit_camera::{PanOrbitCamera, PanOrbitCameraPlugin};
-use gui::ControlUIPlugin;
-use iyes_perf_ui::prelude::*;
 
-const NUM_OF_TRAILS: u16 = 10;
-const INITIAL_DISTANCE: f32 = 0.01;
-const TRAIL_LIFETIME: u16 = 100; // in tenths of a second
-const DELTA_T: u8 = 50;
-
-#[derive(Reflect, Resource, InspectorOptions)]
-#[reflect(Resource, InspectorOptions)]
-struct Configuration {
-    show_diagnostics: bool,
-    rotate_camera: bool,
-    camera_speed: i32,
-    physics_refresh_rate: u16,
-    trail_lifetime: u16, // in tenths of a second
-    num_of_trails: u16,
-    initial_distance: f32,
-    delta_t: u8,
-    sigma: f32,
-    rho: f32,
-    beta: f32,
-}
-
-impl Default for Configuration {
-    fn default() -> Self {
-        Self {
-            show_diagnostics: false,
-            rotate_camera: false,
-            camera_speed: 10,
-            physics_refresh_rate: 120,
-            trail_lifetime: TRAIL_LIFETIME,
-            num_of_trails: NUM_OF_TRAILS,
-            initial_distance: INITIAL_DISTANCE,
-            delta_t: DELTA_T,
-            sigma: 10.,
-            rho: 28.,
-            beta: 8. / 3.,
-        }
-    }
-}
-
-#[derive(Component)]
-struct TrailHead;
-
-#[derive(Component)]
-struct TrailData {
-    mesh: Handle<Mesh>,
-    material: Handle<SimpleColorMaterial>,
-}
-
-#[derive(Component, Deref, DerefMut)]
-struct TimeOfBirth(f32);
-
-fn main() {
-    App::new()
-        .add_plugins((
-            DefaultPlugins,
-            ControlUIPlugin,
-            MaterialPlugin::<SimpleColorMaterial>::default(),
-            PanOrbitCameraPlugin,
-        ))
-        //
-        .add_plugins((
-            bevy::diagnostic::FrameTimeDiagnosticsPlugin,
-            bevy::diagnostic::EntityCountDiagnosticsPlugin,
-            bevy::diagnostic::SystemInformationDiagnosticsPlugin,
-        ))
-        .add_plugins(PerfUiPlugin)
-        .add_systems(
-            Update,
-            toggle_diagnostics
-                .before(iyes_perf_ui::PerfUiSet::Setup)
-                .run_if(|config: Res<Configuration>| config.is_changed()),
-        )
-        //
-        .insert_resource(Configuration::default())
-        .register_type::<Configuration>()
-        .add_plugins(ResourceInspectorPlugin::<Configuration>::default())
-        //
-        .add_systems(Startup, setup)
-        .add_systems(
-            Update,
-            apply_physics_refresh_rate.run_if(|config: Res<Configuration>| config.is_changed()),
-        )
-        .add_systems(
-            Update,
-            rotate_camera.run_if(|config: Res<Configuration>| config.rotate_camera),
-        )
-        .add_systems(FixedUpdate, update_position)
-        .add_systems(
-            Update,
-            (shrink_trail_segments, remove_old_trail_segments).chain(),
-        )
-        //
-        .run();
+#[derive(Parser)]
+#[command(about)]
+struct Args {
+    /// Simulate without a window and write the trajectories to a file
+    #[arg(long)]
+    headless: bool,
+    /// Number of simulation steps of a headless run
+    #[arg(
+        long,
+        value_name = "N",
+        default_value_t = 10_000,
+        requires = "headless"
+    )]
+    steps: u32,
+    /// Trajectory file of a headless run, JSON if it ends in .json and CSV otherwise
+    #[arg(
+        long,
+        value_name = "PATH",
+        default_value = "trajectories.csv",
+        requires = "headless"
+    )]
+    output: PathBuf,
+    /// Measure frame times of the windowed app with increasing numbers of trail segments, write
+    /// a report and exit. Per-system timings aren't collected, only whole frames and the
+    /// process' CPU and memory usage
+    #[arg(long, conflicts_with = "headless")]
+    bench: bool,
+    /// Real seconds each benchmark stage is measured for
+    #[arg(
+        long,
+        value_name = "SECONDS",
+        default_value_t = 10.,
+        requires = "bench"
+    )]
+    bench_seconds: f32,
+    /// Benchmark report, JSON if it ends in .json and Markdown otherwise
+    #[arg(
+        long,
+        value_name = "PATH",
+        default_value = "benchmark.md",
+        requires = "bench"
+    )]
+    bench_report: PathBuf,
+    /// Start from a .ron file, or from presets/NAME.ron, instead of the default configuration
+    #[arg(long, value_name = "FILE")]
+    preset: Option<String>,
+    /// Number of trail heads
+    #[arg(long, value_name = "N")]
+    trails: Option<u16>,
+    #[arg(long)]
+    sigma: Option<f32>,
+    #[arg(long)]
+    rho: Option<f32>,
+    #[arg(long)]
+    beta: Option<f32>,
+    /// Integration step in units of 0.0001
+    #[arg(long)]
+    dt: Option<u8>,
+    /// Physics ticks per second of a windowed run
+    #[arg(long, value_name = "HZ")]
+    refresh_rate: Option<u16>,
 }
 
-fn setup(
-    mut commands: Commands,
-    meshes: ResMut<Assets<Mesh>>,
-    simple_color_materials: ResMut<Assets<SimpleColorMaterial>>,
-    config: Res<Configuration>,
-) {
-    commands.insert_resource(Time::<Fixed>::from_hz(config.physics_refresh_rate as f64));
-
-    spawn_trail_heads(&mut commands, meshes, simple_color_materials, config);
-
-    commands.spawn((
-        Transform::from_translation(Vec3::new(1., 0., 1.) * 80.),
-        PanOrbitCamera {
-            focus: Vec3::new(0., 0., 30.),
-            ..default()
-        },
-    ));
-}
-
-fn spawn_trail_heads(
-    commands: &mut Commands,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut simple_color_materials: ResMut<Assets<SimpleColorMaterial>>,
-    config: Res<Configuration>,
-) {
-    let head_mesh = meshes.add(Sphere::new(0.3));
-    let trail_mesh = meshes.add(
-        CylinderMeshBuilder::new(0.12, 1., 32)
-            .anchor(CylinderAnchor::Bottom)
-            .without_caps()
-            .build(),
-    );
-
-    for i in 1..=config.num_of_trails {
-        let ratio = i as f32 / NUM_OF_TRAILS as f32;
-
-        let head_color = Hsla::hsl(ratio * 360., 0.7, 0.5);
-        let head_material = simple_color_materials.add(SimpleColorMaterial {
-            color: head_color.into(),
-        });
-        let trail_material = simple_color_materials.add(SimpleColorMaterial {
-            color: head_color.with_saturation(0.3).into(),
-        });
-
-        let initial_pos = i as f32 * config.initial_distance;
-        commands.spawn((
-            TrailHead,
-            Mesh3d(head_mesh.clone()),
-            MeshMaterial3d(head_material.clone()),
-            Transform::from_translation(Vec3::splat(initial_pos)),
-            TrailData {
-                mesh: trail_mesh.clone(),
-                material: trail_material.clone(),
-            },
-        ));
+impl Args {
+    /// The preset, or the default configuration, with the values given on the command line.
+    fn configuration(&self) -> Result<Configuration, String> {
+        let config = match self.preset.as_deref() {
+            Some(file) if file.ends_with(".ron") => load_preset_file(file.as_ref())?,
+            Some(name) => load_preset(name)?,
+            None => Configuration::default(),
+        };
+        config.with_overrides(&ConfigurationOverrides {
+            num_of_trails: self.trails,
+            sigma: self.sigma,
+            rho: self.rho,
+            beta: self.beta,
+            delta_t: self.dt,
+            physics_refresh_rate: self.refresh_rate,
+        })
     }
 }
 
-fn apply_physics_refresh_rate(config: Res<Configuration>, mut fixed_time: ResMut<Time<Fixed>>) {
-    fixed_time.set_timestep_hz(std::cmp::max(config.physics_refresh_rate, 1) as f64);
-}
-
-fn toggle_diagnostics(
-    mut commands: Commands,
-    q_root: Query<Entity, With<PerfUiRoot>>,
-    config: Res<Configuration>,
-) {
-    if config.show_diagnostics {
-        if q_root.get_single().is_err() {
-            commands.spawn(PerfUiDefaultEntries::default());
-        }
-    } else {
-        if let Ok(e) = q_root.get_single() {
-            commands.entity(e).despawn_recursive();
+fn main() -> ExitCode {
+    let args = Args::parse();
+    let config = match args.configuration() {
+        Ok(config) => config,
+        Err(err) => {
+            eprintln!("{err}");
+            return ExitCode::FAILURE;
         }
+    };
+
+    if args.bench {
+        return match App::new()
+            .insert_resource(config)
+            .add_plugins((
+                DefaultPlugins,
+                LorenzPlugin,
+                LorenzGuiPlugin,
+                BenchmarkPlugin {
+                    seconds: args.bench_seconds,
+                    report: args.bench_report,
+                },
+            ))
+            .run()
+        {
+            AppExit::Success => ExitCode::SUCCESS,
+            AppExit::Error(_) => ExitCode::FAILURE,
+        };
     }
-}
-
-fn rotate_camera(mut query: Query<&mut PanOrbitCamera>, config: Res<Configuration>) {
-    for mut camera in &mut query {
-        camera.target_yaw += config.camera_speed as f32 / 10_000.;
-    }
-}
-
-fn update_position(
-    mut query: Query<(&mut Transform, &TrailData), With<TrailHead>>,
-    mut commands: Commands,
-    time: Res<Time<Virtual>>,
-    config: Res<Configuration>,
-) {
-    for (mut transform, trail_data) in &mut query {
-        let old_translation = transform.translation.clone();
-
-        let dx = config.sigma * (old_translation.y - old_translation.x);
-        let dy = old_translation.x * (config.rho - old_translation.z) - old_translation.y;
-        let dz = old_translation.x * old_translation.y - config.beta * old_translation.z;
-        let dt = config.delta_t as f32 / 10000.;
-
-        let delta = Vec3::new(dx, dy, dz) * dt;
-        let new_translation = old_translation + delta;
-        transform.translation = new_translation;
 
-        commands.spawn((
-            Mesh3d(trail_data.mesh.clone()),
-            MeshMaterial3d(trail_data.material.clone()),
-            Transform::from_translation(old_translation)
-                .with_scale(Vec3::new(1., delta.length(), 1.))
-                .with_rotation(Quat::from_rotation_arc(Vec3::Y, delta.normalize())),
-            TimeOfBirth(time.elapsed_secs()),
-        ));
+    if !args.headless {
+        // Inserted first, so the plugins find it instead of the default.
+        App::new()
+            .insert_resource(config)
+            .add_plugins((DefaultPlugins, LorenzPlugin, LorenzGuiPlugin))
+            .run();
+        return ExitCode::SUCCESS;
     }
-}
-
-fn shrink_trail_segments(
-    mut query: Query<(&mut TimeOfBirth, &mut Transform)>,
-    time: Res<Time>,
-    config: Res<Configuration>,
-) {
-    query
-        .par_iter_mut()
-        .for_each(|(mut time_of_birth, mut transform)| {
-            let ratio = 1.
-                - ((time.elapsed_secs() - **time_of_birth) / (config.trail_lifetime as f32 / 10.));
-            if ratio > 0. {
-                transform.scale.x = ratio;
-                transform.scale.z = ratio;
-            } else {
-                // Set time of birth to 0, so we can clean it up later.
-                **time_of_birth = 0.
-            }
-        });
-}
-
-fn remove_old_trail_segments(query: Query<(Entity, &TimeOfBirth)>, mut commands: Commands) {
-    query.iter().for_each(|(entity, time_of_birth)| {
-        if **time_of_birth == 0. {
-            commands.entity(entity).despawn();
-        }
-    });
-}
 
-#[derive(Asset, TypePath, AsBindGroup, Debug, Clone)]
-struct SimpleColorMaterial {
-    #[uniform(0)]
-    color: LinearRgba,
-}
-
-impl Material for SimpleColorMaterial {
-    fn fragment_shader() -> ShaderRef {
-        "shaders/simple_color.wgsl".into()
+    match App::new()
+        .add_plugins((
+            MinimalPlugins,
+            LogPlugin::default(),
+            HeadlessPlugin {
+                config,
+                steps: args.steps,
+                output: args.output,
+            },
+        ))
+        .run()
+    {
+        AppExit::Success => ExitCode::SUCCESS,
+        AppExit::Error(_) => ExitCode::FAILURE,
     }
 }
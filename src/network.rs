@@ -0,0 +1,458 @@
+use std::net::{SocketAddr, UdpSocket};
+
+use bevy::{ecs::system::SystemState, prelude::*, utils::HashMap};
+use bevy_egui::egui;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    attractor::{AttractorSystem, Parameters},
+    coloring::{SegmentHistory, TrailPalette},
+    custom_system::CustomSystem,
+    emitter::Emitted,
+    gui,
+    initial_conditions::InitialConditions,
+    instances::{instance_positions, AttractorInstance, HeadGroup},
+    integrator::Integrator,
+    precision::DoublePrecision,
+    spawn_trail_segment, update_position, Configuration, HeadIndex, TrailData, TrailHead,
+};
+
+const DEFAULT_ADDRESS: &str = "127.0.0.1:7878";
+/// Seconds between the hellos a client sends to (re)register with the host.
+const HELLO_INTERVAL: f32 = 1.;
+/// Real seconds without a hello after which the host stops sending to a client, so a single
+/// spoofed hello can't make it stream to a third party for good.
+const PEER_TIMEOUT: f32 = 5. * HELLO_INTERVAL;
+/// Heads per state datagram, at most about 31 bytes each, well below the 65507 bytes UDP allows.
+const HEADS_PER_MESSAGE: usize = 1000;
+/// Jumps longer than this, e.g. when a client joins mid-run, move heads without leaving a
+/// trail segment.
+const MAX_SEGMENT_LENGTH: f32 = 5.;
+
+pub struct NetworkPlugin;
+
+impl Plugin for NetworkPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<NetworkSync>()
+            .add_systems(
+                Update,
+                (
+                    accept_clients.run_if(is_role(Role::Host)),
+                    (send_hello, receive_host_state).run_if(is_role(Role::Client)),
+                ),
+            )
+            .add_systems(
+                FixedUpdate,
+                broadcast_state
+                    .after(update_position)
+                    .run_if(is_role(Role::Host)),
+            );
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum Role {
+    /// Simulates locally without networking.
+    #[default]
+    Off,
+    /// Simulates and broadcasts the state to every client that said hello.
+    Host,
+    /// Mirrors the state received from a host instead of simulating.
+    Client,
+}
+
+#[derive(Resource)]
+pub struct NetworkSync {
+    role: Role,
+    /// Address to bind to as host, or the host's address as client.
+    address: String,
+    socket: Option<UdpSocket>,
+    /// Clients and the real time of their last hello.
+    peers: Vec<(SocketAddr, f32)>,
+    config_due: bool,
+    last_hello: f32,
+    status: Option<String>,
+}
+
+impl Default for NetworkSync {
+    fn default() -> Self {
+        Self {
+            role: Role::Off,
+            address: DEFAULT_ADDRESS.into(),
+            socket: None,
+            peers: Vec::new(),
+            config_due: false,
+            last_hello: f32::NEG_INFINITY,
+            status: None,
+        }
+    }
+}
+
+impl NetworkSync {
+    fn connect(&mut self, role: Role) -> Result<(), String> {
+        let socket = match role {
+            Role::Off => return Ok(()),
+            Role::Host => UdpSocket::bind(&self.address),
+            Role::Client => UdpSocket::bind("0.0.0.0:0").and_then(|socket| {
+                socket.connect(&self.address)?;
+                Ok(socket)
+            }),
+        }
+        .and_then(|socket| {
+            socket.set_nonblocking(true)?;
+            Ok(socket)
+        })
+        .map_err(|err| format!("{}: {err}", self.address))?;
+
+        self.role = role;
+        self.socket = Some(socket);
+        Ok(())
+    }
+
+    fn disconnect(&mut self) {
+        self.role = Role::Off;
+        self.socket = None;
+        self.peers.clear();
+        self.last_hello = f32::NEG_INFINITY;
+    }
+
+    fn send_to_peers(&self, message: &Message) {
+        let Some(socket) = self.socket.as_ref() else {
+            return;
+        };
+        let bytes = bincode::serialize(message).expect("messages are always serializable");
+        for (peer, _) in &self.peers {
+            if let Err(err) = socket.send_to(&bytes, peer) {
+                warn!("Could not send state to {peer}: {err}");
+            }
+        }
+    }
+
+    /// Drains all pending datagrams.
+    fn receive(&self) -> Vec<(Message, SocketAddr)> {
+        let Some(socket) = self.socket.as_ref() else {
+            return Vec::new();
+        };
+        let mut buffer = [0; u16::MAX as usize];
+        let mut messages = Vec::new();
+        while let Ok((length, sender)) = socket.recv_from(&mut buffer) {
+            match bincode::deserialize(&buffer[..length]) {
+                Ok(message) => messages.push((message, sender)),
+                Err(err) => warn!("Ignoring malformed datagram from {sender}: {err}"),
+            }
+        }
+        messages
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+enum Message {
+    Hello,
+    Simulation(Box<SimulationSettings>),
+    /// One chunk of the heads, the state of many heads takes several messages.
+    Heads(Vec<RemoteHead>),
+}
+
+macro_rules! simulation_settings {
+    ($($field:ident: $ty:ty),* $(,)?) => {
+        /// The part of the configuration that decides which heads exist and how they move.
+        /// Clients take it over from the host and keep their own camera and rendering
+        /// settings.
+        #[derive(Clone, PartialEq, Serialize, Deserialize)]
+        struct SimulationSettings {
+            $($field: $ty,)*
+        }
+
+        // Most of the fields are `Copy`, the clones are for the rest.
+        #[allow(clippy::clone_on_copy)]
+        impl SimulationSettings {
+            fn of(config: &Configuration) -> Self {
+                Self {
+                    $($field: config.$field.clone(),)*
+                }
+            }
+
+            fn apply_to(&self, config: &mut Configuration) {
+                $(config.$field = self.$field.clone();)*
+            }
+        }
+    };
+}
+
+simulation_settings! {
+    physics_refresh_rate: u16,
+    num_of_trails: u16,
+    initial_conditions: InitialConditions,
+    initial_distance: f32,
+    seed: u64,
+    delta_t: u8,
+    spawn_stagger: f32,
+    emitter_enabled: bool,
+    emitter_population: u16,
+    emitter_rate: f32,
+    emitter_center: Vec3,
+    emitter_radius: f32,
+    integrator: Integrator,
+    rk45_tolerance: f64,
+    compare_integrators: bool,
+    double_precision: bool,
+    compare_precision: bool,
+    attractor: AttractorSystem,
+    custom_system: CustomSystem,
+    sigma: f32,
+    rho: f32,
+    beta: f32,
+    runaway_limit: f32,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct RemoteHead {
+    group: HeadGroup,
+    index: u16,
+    integrator: Integrator,
+    double_precision: bool,
+    position: Vec3,
+}
+
+fn is_role(role: Role) -> impl Fn(Res<NetworkSync>) -> bool {
+    move |network: Res<NetworkSync>| network.role == role
+}
+
+/// Clients don't simulate, they follow the host.
+pub fn simulation_is_local(network: Res<NetworkSync>) -> bool {
+    network.role != Role::Client
+}
+
+/// Registers clients on their first hello, refreshes them on every further one and drops
+/// those that stopped saying hello.
+fn accept_clients(mut network: ResMut<NetworkSync>, time: Res<Time<Real>>) {
+    let now = time.elapsed_secs();
+    for (message, sender) in network.receive() {
+        if !matches!(message, Message::Hello) {
+            continue;
+        }
+        match network.peers.iter_mut().find(|(peer, _)| *peer == sender) {
+            Some((_, last_hello)) => *last_hello = now,
+            None => {
+                info!("Client {sender} joined");
+                network.peers.push((sender, now));
+                network.config_due = true;
+            }
+        }
+    }
+
+    network.peers.retain(|&(peer, last_hello)| {
+        let alive = now - last_hello < PEER_TIMEOUT;
+        if !alive {
+            info!("Client {peer} timed out");
+        }
+        alive
+    });
+}
+
+/// Identifies a head across host and client, as the indices of the main heads, each instance
+/// and the emitter overlap.
+type HeadKey = (HeadGroup, u16, Integrator, bool);
+
+type HeadQuery<'a> = (
+    &'a HeadIndex,
+    &'a Integrator,
+    &'a Transform,
+    Has<DoublePrecision>,
+    Option<&'a Parent>,
+    Has<Emitted>,
+);
+
+fn broadcast_state(
+    mut network: ResMut<NetworkSync>,
+    heads: Query<HeadQuery, With<TrailHead>>,
+    instances: Query<Entity, With<AttractorInstance>>,
+    config: Res<Configuration>,
+) {
+    if config.is_changed() || network.config_due {
+        network.send_to_peers(&Message::Simulation(Box::new(SimulationSettings::of(
+            &config,
+        ))));
+        network.config_due = false;
+    }
+
+    let instances = instance_positions(&instances);
+    let heads: Vec<RemoteHead> = heads
+        .iter()
+        .map(
+            |(index, integrator, transform, double_precision, parent, emitted)| RemoteHead {
+                group: HeadGroup::of(&instances, parent, emitted),
+                index: index.0,
+                integrator: *integrator,
+                double_precision,
+                position: transform.translation,
+            },
+        )
+        .collect();
+    for chunk in heads.chunks(HEADS_PER_MESSAGE) {
+        network.send_to_peers(&Message::Heads(chunk.to_vec()));
+    }
+}
+
+fn send_hello(mut network: ResMut<NetworkSync>, time: Res<Time<Real>>) {
+    if time.elapsed_secs() - network.last_hello < HELLO_INTERVAL {
+        return;
+    }
+    network.last_hello = time.elapsed_secs();
+
+    if let Some(socket) = network.socket.as_ref() {
+        let hello = bincode::serialize(&Message::Hello).expect("messages are always serializable");
+        if let Err(err) = socket.send(&hello) {
+            network.status = Some(format!("Could not reach host: {err}"));
+        }
+    }
+}
+
+/// Applies simulation changes from the host, restarting the local heads, and moves the
+/// heads to the received positions, leaving trail segments like a local simulation would.
+/// Settings are checked like an imported configuration, as any peer can send them.
+fn receive_host_state(world: &mut World) {
+    let messages = world.resource::<NetworkSync>().receive();
+
+    for (message, _) in messages {
+        match message {
+            Message::Hello => {}
+            Message::Simulation(settings) => {
+                let mut config = world.resource::<Configuration>().clone();
+                settings.apply_to(&mut config);
+                if let Err(err) = config.validate() {
+                    warn!("Ignoring invalid settings from the host: {err}");
+                    continue;
+                }
+                for change in config.clamp() {
+                    warn!("{change}");
+                }
+                if *world.resource::<Configuration>() != config {
+                    world.insert_resource(config);
+                    gui::clear(world);
+                    gui::start(world);
+                }
+            }
+            Message::Heads(remote_heads) => follow_remote_heads(world, &remote_heads),
+        }
+    }
+}
+
+fn follow_remote_heads(world: &mut World, remote_heads: &[RemoteHead]) {
+    let instances = instance_positions(
+        world
+            .query_filtered::<Entity, With<AttractorInstance>>()
+            .iter(world),
+    );
+    let remote_heads: HashMap<HeadKey, Vec3> = remote_heads
+        .iter()
+        .map(|remote| {
+            let key = (
+                remote.group,
+                remote.index,
+                remote.integrator,
+                remote.double_precision,
+            );
+            (key, remote.position)
+        })
+        .collect();
+
+    let mut system_state: SystemState<(
+        Query<
+            (
+                Entity,
+                &HeadIndex,
+                &Integrator,
+                Has<DoublePrecision>,
+                Option<&Parent>,
+                Has<Emitted>,
+                &mut Transform,
+                &TrailData,
+                &mut SegmentHistory,
+            ),
+            With<TrailHead>,
+        >,
+        Commands,
+        Res<Time<Virtual>>,
+        Res<Configuration>,
+        Res<TrailPalette>,
+    )> = SystemState::new(world);
+
+    let (mut heads, mut commands, time, config, palette) = system_state.get_mut(world);
+
+    for (
+        entity,
+        index,
+        integrator,
+        double_precision,
+        parent,
+        emitted,
+        mut transform,
+        trail_data,
+        mut history,
+    ) in &mut heads
+    {
+        let group = HeadGroup::of(&instances, parent, emitted);
+        let Some(&position) = remote_heads.get(&(group, index.0, *integrator, double_precision))
+        else {
+            continue;
+        };
+
+        let delta = position - transform.translation;
+        if delta != Vec3::ZERO && delta.length() < MAX_SEGMENT_LENGTH {
+            let material = palette.segment_material(
+                &config,
+                &Parameters::of(&config),
+                trail_data,
+                &mut history,
+                transform.translation,
+                delta,
+            );
+            spawn_trail_segment(
+                &mut commands,
+                trail_data.mesh.clone(),
+                material,
+                entity,
+                transform.translation,
+                delta,
+                time.elapsed_secs(),
+            );
+        }
+        transform.translation = position;
+    }
+
+    system_state.apply(world);
+}
+
+pub fn network_ui(world: &mut World, ui: &mut egui::Ui) {
+    let mut network = world.resource_mut::<NetworkSync>();
+
+    ui.add_enabled(
+        network.role == Role::Off,
+        egui::TextEdit::singleline(&mut network.address),
+    );
+
+    ui.horizontal(|ui| match network.role {
+        Role::Off => {
+            for (role, label) in [(Role::Host, "Host"), (Role::Client, "Join")] {
+                if ui.button(label).clicked() {
+                    network.status = network.connect(role).err();
+                }
+            }
+        }
+        role => {
+            ui.label(if role == Role::Host {
+                format!("Hosting for {} client(s)", network.peers.len())
+            } else {
+                "Following host".to_string()
+            });
+            if ui.button("Disconnect").clicked() {
+                network.disconnect();
+            }
+        }
+    });
+
+    if let Some(status) = network.status.as_ref() {
+        ui.colored_label(egui::Color32::RED, status);
+    }
+}
@@ -0,0 +1,143 @@
+use bevy::{
+    pbr::{MaterialPipeline, MaterialPipelineKey},
+    prelude::*,
+    render::{
+        mesh::MeshVertexBufferLayoutRef,
+        render_resource::{
+            AsBindGroup, CompareFunction, Face, RenderPipelineDescriptor, ShaderRef,
+            SpecializedMeshPipelineError,
+        },
+    },
+};
+
+use crate::{selection::Selection, Configuration, TrailHead, HEAD_RADIUS};
+
+const OUTLINE_RADIUS: f32 = HEAD_RADIUS * 1.5;
+
+pub struct OutlinePlugin;
+
+impl Plugin for OutlinePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(MaterialPlugin::<OutlineMaterial>::default())
+            .add_systems(Startup, setup_outline_assets)
+            .add_systems(
+                Update,
+                (
+                    attach_head_outlines,
+                    apply_outline_settings.run_if(
+                        |config: Res<Configuration>, selection: Res<Selection>| {
+                            config.is_changed() || selection.is_changed()
+                        },
+                    ),
+                ),
+            );
+    }
+}
+
+#[derive(Component)]
+struct HeadOutline;
+
+#[derive(Resource)]
+struct OutlineAssets {
+    mesh: Handle<Mesh>,
+    material: Handle<OutlineMaterial>,
+}
+
+fn setup_outline_assets(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut outline_materials: ResMut<Assets<OutlineMaterial>>,
+    config: Res<Configuration>,
+) {
+    commands.insert_resource(OutlineAssets {
+        mesh: meshes.add(Sphere::new(OUTLINE_RADIUS)),
+        material: outline_materials.add(OutlineMaterial {
+            color: config.outline_color.into(),
+            rim: (1. - (HEAD_RADIUS / OUTLINE_RADIUS).powi(2)).sqrt(),
+        }),
+    });
+}
+
+fn outline_visibility(config: &Configuration) -> Visibility {
+    if config.head_outlines {
+        Visibility::Inherited
+    } else {
+        Visibility::Hidden
+    }
+}
+
+fn attach_head_outlines(
+    mut commands: Commands,
+    heads: Query<Entity, Added<TrailHead>>,
+    outline_assets: Res<OutlineAssets>,
+    config: Res<Configuration>,
+) {
+    for head in &heads {
+        commands.entity(head).with_child((
+            HeadOutline,
+            Mesh3d(outline_assets.mesh.clone()),
+            MeshMaterial3d(outline_assets.material.clone()),
+            outline_visibility(&config),
+        ));
+    }
+}
+
+/// Shows outlines on all heads if enabled, and always on the selected one.
+fn apply_outline_settings(
+    mut outlines: Query<(&Parent, &mut Visibility), With<HeadOutline>>,
+    outline_assets: Res<OutlineAssets>,
+    mut outline_materials: ResMut<Assets<OutlineMaterial>>,
+    config: Res<Configuration>,
+    selection: Res<Selection>,
+) {
+    for (head, mut visibility) in &mut outlines {
+        if selection.0 == Some(head.get()) {
+            visibility.set_if_neq(Visibility::Inherited);
+        } else {
+            visibility.set_if_neq(outline_visibility(&config));
+        }
+    }
+
+    let color = config.outline_color.into();
+    if let Some(material) = outline_materials.get_mut(&outline_assets.material) {
+        if material.color != color {
+            material.color = color;
+        }
+    }
+}
+
+/// Draws the back faces of an enlarged sphere on top of everything, keeping only the rim
+/// outside the head's own silhouette. The head therefore stays visible as a ring even when
+/// it is buried inside a dense bundle of trails.
+#[derive(Asset, TypePath, AsBindGroup, Debug, Clone)]
+struct OutlineMaterial {
+    #[uniform(0)]
+    color: LinearRgba,
+    /// Largest |N·V| of a back face that still lies outside the head's silhouette.
+    #[uniform(1)]
+    rim: f32,
+}
+
+impl Material for OutlineMaterial {
+    fn fragment_shader() -> ShaderRef {
+        "shaders/outline.wgsl".into()
+    }
+
+    fn alpha_mode(&self) -> AlphaMode {
+        AlphaMode::Blend
+    }
+
+    fn specialize(
+        _pipeline: &MaterialPipeline<Self>,
+        descriptor: &mut RenderPipelineDescriptor,
+        _layout: &MeshVertexBufferLayoutRef,
+        _key: MaterialPipelineKey<Self>,
+    ) -> Result<(), SpecializedMeshPipelineError> {
+        descriptor.primitive.cull_mode = Some(Face::Front);
+        if let Some(depth_stencil) = descriptor.depth_stencil.as_mut() {
+            depth_stencil.depth_compare = CompareFunction::Always;
+            depth_stencil.depth_write_enabled = false;
+        }
+        Ok(())
+    }
+}
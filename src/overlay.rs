@@ -0,0 +1,107 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+use bevy_panorbit_camera::PanOrbitCamera;
+
+use crate::{Configuration, TimeOfBirth, TrailHead};
+
+/// Distance between grid lines.
+const GRID_SPACING: f32 = 10.;
+const AXES: [(Vec3, &str, Color); 3] = [
+    (Vec3::X, "x", Color::srgb(1., 0.2, 0.2)),
+    (Vec3::Y, "y", Color::srgb(0.2, 1., 0.2)),
+    (Vec3::Z, "z", Color::srgb(0.2, 0.4, 1.)),
+];
+
+pub struct OverlayPlugin;
+
+impl Plugin for OverlayPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                (draw_axes, axis_labels).run_if(|config: Res<Configuration>| config.overlay_axes),
+                draw_grid.run_if(|config: Res<Configuration>| config.overlay_grid),
+                draw_bounding_box.run_if(|config: Res<Configuration>| config.overlay_bounding_box),
+            ),
+        );
+    }
+}
+
+fn draw_axes(mut gizmos: Gizmos, config: Res<Configuration>) {
+    for (axis, _, color) in AXES {
+        gizmos
+            .arrow(
+                -axis * config.overlay_extent,
+                axis * config.overlay_extent,
+                color.with_alpha(config.overlay_opacity),
+            )
+            .with_tip_length(config.overlay_extent / 20.);
+    }
+}
+
+/// Names the axes at their tips, with the coordinate there to show the scale.
+fn axis_labels(
+    mut contexts: EguiContexts,
+    cameras: Query<(&Camera, &GlobalTransform), With<PanOrbitCamera>>,
+    config: Res<Configuration>,
+) {
+    let Ok((camera, camera_transform)) = cameras.get_single() else {
+        return;
+    };
+    let ctx = contexts.ctx_mut();
+
+    for (axis, name, color) in AXES {
+        let tip = axis * config.overlay_extent;
+        let Ok(screen) = camera.world_to_viewport(camera_transform, tip) else {
+            continue;
+        };
+        let [r, g, b, a] = color
+            .with_alpha(config.overlay_opacity)
+            .to_srgba()
+            .to_u8_array();
+        egui::Area::new(egui::Id::new(("axis_label", name)))
+            .fixed_pos([screen.x + 6., screen.y - 8.])
+            .interactable(false)
+            .show(ctx, |ui| {
+                ui.colored_label(
+                    egui::Color32::from_rgba_unmultiplied(r, g, b, a),
+                    format!("{name} = {}", config.overlay_extent),
+                );
+            });
+    }
+}
+
+/// Square grid in the xy-plane, which the attractor rises above along z.
+fn draw_grid(mut gizmos: Gizmos, config: Res<Configuration>) {
+    let cells = (2. * config.overlay_extent / GRID_SPACING).ceil().max(1.) as u32;
+    gizmos.grid(
+        Isometry3d::IDENTITY,
+        UVec2::splat(cells),
+        Vec2::splat(GRID_SPACING),
+        Color::WHITE.with_alpha(config.overlay_opacity * 0.5),
+    );
+}
+
+/// Box around every head and trail segment.
+fn draw_bounding_box(
+    mut gizmos: Gizmos,
+    heads: Query<&GlobalTransform, With<TrailHead>>,
+    segments: Query<&Transform, With<TimeOfBirth>>,
+    config: Res<Configuration>,
+) {
+    let points = heads
+        .iter()
+        .map(GlobalTransform::translation)
+        .chain(segments.iter().map(|transform| transform.translation));
+    let Some((min, max)) = points.fold(None, |bounds, point| match bounds {
+        None => Some((point, point)),
+        Some((min, max)) => Some((point.min(min), point.max(max))),
+    }) else {
+        return;
+    };
+
+    gizmos.cuboid(
+        Transform::from_translation((min + max) / 2.).with_scale(max - min),
+        Color::WHITE.with_alpha(config.overlay_opacity),
+    );
+}
@@ -0,0 +1,261 @@
+use bevy::{prelude::*, window::PrimaryWindow};
+use bevy_egui::{egui, EguiClipboard, EguiContext};
+
+use crate::{
+    camera_feel::CameraFeel,
+    explore,
+    file_dialog::{self, DialogKind},
+    gui,
+    playback::{PendingSteps, SimulationState},
+    screenshot::{take_screenshot, FrameRecording},
+    share,
+    tutorial::Tutorial,
+    views::{self, View},
+    Configuration,
+};
+
+const MAX_RESULTS: usize = 12;
+
+pub struct CommandPalettePlugin;
+
+impl Plugin for CommandPalettePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PaletteState>()
+            .insert_resource(PaletteCommands(builtin_commands()))
+            .add_systems(Update, command_palette);
+    }
+}
+
+/// An action that can be run from the command palette.
+pub struct PaletteCommand {
+    pub name: String,
+    pub run: fn(&mut World),
+}
+
+impl PaletteCommand {
+    pub fn new(name: impl Into<String>, run: fn(&mut World)) -> Self {
+        Self {
+            name: name.into(),
+            run,
+        }
+    }
+}
+
+/// All commands listed in the palette. Features can push their own.
+#[derive(Resource)]
+pub struct PaletteCommands(pub Vec<PaletteCommand>);
+
+#[derive(Resource, Default)]
+struct PaletteState {
+    open: bool,
+    query: String,
+    selected: usize,
+}
+
+fn toggle_config(world: &mut World, field: fn(&mut Configuration) -> &mut bool) {
+    let mut config = world.resource_mut::<Configuration>();
+    let value = field(&mut config);
+    *value = !*value;
+}
+
+fn builtin_commands() -> Vec<PaletteCommand> {
+    vec![
+        PaletteCommand::new("Clear", gui::clear),
+        PaletteCommand::new("Restart", |world| {
+            gui::clear(world);
+            gui::start(world);
+        }),
+        PaletteCommand::new("Explore random parameters", explore::explore),
+        PaletteCommand::new("Pause / resume", |world| {
+            world.resource_mut::<SimulationState>().toggle();
+        }),
+        PaletteCommand::new("Step one tick", |world| {
+            world.resource_mut::<PendingSteps>().0 += 1;
+        }),
+        PaletteCommand::new("Start tutorial", |world| {
+            world.resource_mut::<Tutorial>().0 = Some(0);
+        }),
+        PaletteCommand::new("Take screenshot", |world| {
+            take_screenshot(world, None);
+        }),
+        PaletteCommand::new("Save screenshot as…", |world| {
+            if let Some(path) =
+                file_dialog::pick_file(DialogKind::Save, "screenshot.png", file_dialog::PNG)
+            {
+                take_screenshot(world, Some(path.display().to_string()));
+            }
+        }),
+        PaletteCommand::new("Start / stop recording frames", |world| {
+            world.resource_mut::<FrameRecording>().toggle();
+        }),
+        PaletteCommand::new("Copy share code", |world| {
+            let code = share::encode(world.resource::<Configuration>());
+            world.resource_mut::<EguiClipboard>().set_contents(&code);
+        }),
+        PaletteCommand::new("Toggle diagnostics", |world| {
+            toggle_config(world, |config| &mut config.show_diagnostics)
+        }),
+        PaletteCommand::new("Toggle camera rotation", |world| {
+            toggle_config(world, |config| &mut config.rotate_camera)
+        }),
+        PaletteCommand::new("Toggle fly camera", |world| {
+            toggle_config(world, |config| &mut config.fly_camera)
+        }),
+        PaletteCommand::new("Cinematic camera damping", |world| {
+            CameraFeel::CINEMATIC.apply_to(&mut world.resource_mut::<Configuration>());
+        }),
+        PaletteCommand::new("Toggle lit trails", |world| {
+            toggle_config(world, |config| &mut config.lit_trails)
+        }),
+        PaletteCommand::new("Toggle head outlines", |world| {
+            toggle_config(world, |config| &mut config.head_outlines)
+        }),
+        PaletteCommand::new("Toggle annotations", |world| {
+            toggle_config(world, |config| &mut config.annotations)
+        }),
+        PaletteCommand::new("Toggle fixed point markers", |world| {
+            toggle_config(world, |config| &mut config.fixed_point_markers)
+        }),
+        PaletteCommand::new("Toggle orthographic projection", |world| {
+            toggle_config(world, |config| &mut config.orthographic)
+        }),
+        PaletteCommand::new("View x–y plane", |world| views::snap(world, View::Xy)),
+        PaletteCommand::new("View x–z plane", |world| views::snap(world, View::Xz)),
+        PaletteCommand::new("View z–y plane", |world| views::snap(world, View::Zy)),
+        PaletteCommand::new("Isometric view", |world| {
+            views::snap(world, View::Isometric)
+        }),
+        PaletteCommand::new("Toggle axes", |world| {
+            toggle_config(world, |config| &mut config.overlay_axes)
+        }),
+        PaletteCommand::new("Toggle grid", |world| {
+            toggle_config(world, |config| &mut config.overlay_grid)
+        }),
+        PaletteCommand::new("Toggle bounding box", |world| {
+            toggle_config(world, |config| &mut config.overlay_bounding_box)
+        }),
+        PaletteCommand::new("Toggle fog", |world| {
+            toggle_config(world, |config| &mut config.fog_enabled)
+        }),
+        PaletteCommand::new("Toggle slicing plane", |world| {
+            toggle_config(world, |config| &mut config.slice_enabled)
+        }),
+        PaletteCommand::new("Toggle symmetric twins", |world| {
+            toggle_config(world, |config| &mut config.symmetric_twins)
+        }),
+        PaletteCommand::new("Toggle wall shadows", |world| {
+            toggle_config(world, |config| &mut config.wall_shadows)
+        }),
+        PaletteCommand::new("Toggle emitter", |world| {
+            toggle_config(world, |config| &mut config.emitter_enabled)
+        }),
+        PaletteCommand::new("Toggle GPU particles", |world| {
+            toggle_config(world, |config| &mut config.particles_enabled)
+        }),
+        PaletteCommand::new("Toggle proximity events", |world| {
+            toggle_config(world, |config| &mut config.proximity_events)
+        }),
+    ]
+}
+
+/// Scores how well `query` matches `candidate` as a case-insensitive subsequence, or `None`
+/// if it doesn't. Consecutive characters and matches at word starts score higher.
+fn fuzzy_score(query: &str, candidate: &str) -> Option<i32> {
+    let candidate: Vec<char> = candidate.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut position = 0;
+    let mut previous_match: Option<usize> = None;
+
+    for needle in query.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        let found = position + candidate[position..].iter().position(|&c| c == needle)?;
+        score += 1;
+        if previous_match.is_some_and(|previous| previous + 1 == found) {
+            score += 5;
+        }
+        if found == 0 || !candidate[found - 1].is_alphanumeric() {
+            score += 3;
+        }
+        previous_match = Some(found);
+        position = found + 1;
+    }
+
+    // Prefer shorter names among equally good matches.
+    Some(score * 100 - candidate.len() as i32)
+}
+
+fn command_palette(world: &mut World) {
+    let keys = world.resource::<ButtonInput<KeyCode>>();
+    let toggled = keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight])
+        && keys.just_pressed(KeyCode::KeyP);
+    if toggled {
+        let mut state = world.resource_mut::<PaletteState>();
+        state.open = !state.open;
+        state.query.clear();
+        state.selected = 0;
+    }
+    if !world.resource::<PaletteState>().open {
+        return;
+    }
+
+    let Ok(egui_context) = world
+        .query_filtered::<&mut EguiContext, With<PrimaryWindow>>()
+        .get_single(world)
+    else {
+        return;
+    };
+    let mut egui_context = egui_context.clone();
+
+    world.resource_scope(|world, mut state: Mut<PaletteState>| {
+        let commands = &world.resource::<PaletteCommands>().0;
+        let mut matches: Vec<(i32, usize)> = commands
+            .iter()
+            .enumerate()
+            .filter_map(|(i, command)| Some((fuzzy_score(&state.query, &command.name)?, i)))
+            .collect();
+        matches.sort_by_key(|&(score, i)| (-score, i));
+        matches.truncate(MAX_RESULTS);
+
+        let mut chosen = None;
+        egui::Window::new("Command palette")
+            .title_bar(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_TOP, [0., 80.])
+            .show(egui_context.get_mut(), |ui| {
+                let input = ui.text_edit_singleline(&mut state.query);
+                input.request_focus();
+                if input.changed() {
+                    state.selected = 0;
+                }
+
+                ui.input(|input| {
+                    if input.key_pressed(egui::Key::ArrowDown) {
+                        state.selected = (state.selected + 1).min(matches.len().saturating_sub(1));
+                    }
+                    if input.key_pressed(egui::Key::ArrowUp) {
+                        state.selected = state.selected.saturating_sub(1);
+                    }
+                    if input.key_pressed(egui::Key::Enter) {
+                        chosen = matches.get(state.selected).map(|&(_, i)| i);
+                    }
+                    if input.key_pressed(egui::Key::Escape) {
+                        state.open = false;
+                    }
+                });
+
+                for (row, &(_, i)) in matches.iter().enumerate() {
+                    if ui
+                        .selectable_label(row == state.selected, &commands[i].name)
+                        .clicked()
+                    {
+                        chosen = Some(i);
+                    }
+                }
+            });
+
+        if let Some(i) = chosen {
+            state.open = false;
+            let run = commands[i].run;
+            run(world);
+        }
+    });
+}
@@ -0,0 +1,178 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+
+use crate::Configuration;
+
+/// Seconds between a new keyframe and the previous last one.
+const KEYFRAME_SPACING: f32 = 5.;
+
+pub struct ParameterAnimationPlugin;
+
+impl Plugin for ParameterAnimationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ParameterAnimation>()
+            .add_systems(Update, (animate_parameters, parameter_animation_ui));
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct ParameterKeyframe {
+    /// Seconds of simulated time since the start of the animation.
+    time: f32,
+    sigma: f32,
+    rho: f32,
+    beta: f32,
+}
+
+/// Keyframes that `sigma`, `rho` and `beta` are interpolated between, to morph the attractor
+/// through a bifurcation for example.
+#[derive(Resource, Default)]
+struct ParameterAnimation {
+    /// Sorted by time.
+    keyframes: Vec<ParameterKeyframe>,
+    /// Seconds into the animation while it plays.
+    position: Option<f32>,
+    looping: bool,
+}
+
+impl ParameterAnimation {
+    fn duration(&self) -> f32 {
+        self.keyframes.last().map_or(0., |keyframe| keyframe.time)
+    }
+
+    /// Linearly interpolated parameters at `time`, clamped to the ends of the animation.
+    fn sample(&self, time: f32) -> Option<ParameterKeyframe> {
+        let next = self
+            .keyframes
+            .iter()
+            .position(|keyframe| keyframe.time >= time)
+            .unwrap_or(self.keyframes.len().checked_sub(1)?);
+        let b = self.keyframes[next];
+        let a = self.keyframes[next.saturating_sub(1)];
+
+        let span = b.time - a.time;
+        let s = if span > 0. {
+            ((time - a.time) / span).clamp(0., 1.)
+        } else {
+            1.
+        };
+        let lerp = |from: f32, to: f32| from + (to - from) * s;
+        Some(ParameterKeyframe {
+            time,
+            sigma: lerp(a.sigma, b.sigma),
+            rho: lerp(a.rho, b.rho),
+            beta: lerp(a.beta, b.beta),
+        })
+    }
+}
+
+/// Follows simulated time, so pausing the simulation holds the animation as well.
+fn animate_parameters(
+    mut animation: ResMut<ParameterAnimation>,
+    mut config: ResMut<Configuration>,
+    time: Res<Time<Virtual>>,
+) {
+    let Some(position) = animation.position else {
+        return;
+    };
+    let mut position = position + time.delta_secs();
+    if position > animation.duration() {
+        if animation.looping && animation.duration() > 0. {
+            position %= animation.duration();
+        } else {
+            animation.position = None;
+            return;
+        }
+    }
+    animation.position = Some(position);
+
+    let Some(sample) = animation.sample(position) else {
+        animation.position = None;
+        return;
+    };
+    // Only write real changes, every configuration change is picked up by other systems.
+    if (config.sigma, config.rho, config.beta) != (sample.sigma, sample.rho, sample.beta) {
+        config.sigma = sample.sigma;
+        config.rho = sample.rho;
+        config.beta = sample.beta;
+    }
+}
+
+fn parameter_animation_ui(
+    mut contexts: EguiContexts,
+    mut animation: ResMut<ParameterAnimation>,
+    config: Res<Configuration>,
+) {
+    let animation = &mut *animation;
+
+    egui::Window::new("Parameter animation")
+        .default_open(false)
+        .show(contexts.ctx_mut(), |ui| {
+            ui.horizontal(|ui| {
+                match animation.position {
+                    None => {
+                        if ui
+                            .add_enabled(!animation.keyframes.is_empty(), egui::Button::new("Play"))
+                            .clicked()
+                        {
+                            animation.position = Some(0.);
+                        }
+                    }
+                    Some(position) => {
+                        if ui.button("Stop").clicked() {
+                            animation.position = None;
+                        }
+                        ui.label(format!("{position:.1} / {:.1} s", animation.duration()));
+                    }
+                }
+                ui.checkbox(&mut animation.looping, "Loop");
+            });
+
+            ui.separator();
+
+            let mut removed = None;
+            let mut changed = false;
+            egui::Grid::new("parameter_keyframes")
+                .striped(true)
+                .show(ui, |ui| {
+                    for header in ["Time (s)", "sigma", "rho", "beta"] {
+                        ui.label(header);
+                    }
+                    ui.end_row();
+
+                    for (i, keyframe) in animation.keyframes.iter_mut().enumerate() {
+                        changed |= ui
+                            .add(egui::DragValue::new(&mut keyframe.time).range(0.0..=f32::MAX))
+                            .changed();
+                        for value in [&mut keyframe.sigma, &mut keyframe.rho, &mut keyframe.beta] {
+                            changed |= ui.add(egui::DragValue::new(value).speed(0.1)).changed();
+                        }
+                        if ui.button("Remove").clicked() {
+                            removed = Some(i);
+                        }
+                        ui.end_row();
+                    }
+                });
+            if let Some(i) = removed {
+                animation.keyframes.remove(i);
+            }
+            if changed {
+                animation
+                    .keyframes
+                    .sort_by(|a, b| a.time.total_cmp(&b.time));
+            }
+
+            if ui.button("Add current parameters").clicked() {
+                let time = animation
+                    .keyframes
+                    .last()
+                    .map_or(0., |last| last.time + KEYFRAME_SPACING);
+                animation.keyframes.push(ParameterKeyframe {
+                    time,
+                    sigma: config.sigma,
+                    rho: config.rho,
+                    beta: config.beta,
+                });
+            }
+        });
+}
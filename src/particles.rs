@@ -0,0 +1,310 @@
+use bevy::{
+    pbr::NotShadowCaster,
+    prelude::*,
+    render::{
+        extract_resource::{ExtractResource, ExtractResourcePlugin},
+        mesh::PrimitiveTopology,
+        render_asset::{RenderAssetUsages, RenderAssets},
+        render_graph::{self, RenderGraph, RenderLabel},
+        render_resource::{
+            binding_types::{storage_buffer, uniform_buffer},
+            AsBindGroup, BindGroup, BindGroupEntries, BindGroupLayout, BindGroupLayoutEntries,
+            CachedComputePipelineId, ComputePassDescriptor, ComputePipelineDescriptor,
+            PipelineCache, ShaderRef, ShaderStages, UniformBuffer,
+        },
+        renderer::{RenderContext, RenderDevice, RenderQueue},
+        storage::{GpuShaderStorageBuffer, ShaderStorageBuffer},
+        view::NoFrustumCulling,
+        Render, RenderApp, RenderSet,
+    },
+};
+use rand::Rng;
+
+use crate::{attractor::AttractorSystem, Configuration};
+
+const WORKGROUP_SIZE: u32 = 256;
+/// Largest count a single one-dimensional dispatch can cover.
+pub const MAX_PARTICLES: u32 = u16::MAX as u32 * WORKGROUP_SIZE;
+
+/// Advects a large cloud of points through the flow on the GPU and renders them as additive
+/// points. This is independent of the trail heads, which stay on the CPU.
+pub struct ParticlePlugin;
+
+impl Plugin for ParticlePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins((
+            MaterialPlugin::<ParticleMaterial>::default(),
+            ExtractResourcePlugin::<ParticleAdvection>::default(),
+        ))
+        .init_resource::<ParticleAdvection>()
+        .add_systems(
+            Update,
+            (
+                sync_particle_cloud.run_if(|config: Res<Configuration>| config.is_changed()),
+                update_advection_params,
+            )
+                .chain(),
+        );
+
+        let render_app = app.sub_app_mut(RenderApp);
+        render_app
+            .init_resource::<AdvectionBindGroup>()
+            .add_systems(
+                Render,
+                prepare_advection_bind_group.in_set(RenderSet::PrepareBindGroups),
+            );
+        let mut render_graph = render_app.world_mut().resource_mut::<RenderGraph>();
+        render_graph.add_node(AdvectionLabel, AdvectionNode);
+        render_graph.add_node_edge(AdvectionLabel, bevy::render::graph::CameraDriverLabel);
+    }
+
+    fn finish(&self, app: &mut App) {
+        app.sub_app_mut(RenderApp)
+            .init_resource::<AdvectionPipeline>();
+    }
+}
+
+#[derive(Asset, TypePath, AsBindGroup, Debug, Clone)]
+pub struct ParticleMaterial {
+    #[storage(0, read_only)]
+    particles: Handle<ShaderStorageBuffer>,
+    #[uniform(1)]
+    color: LinearRgba,
+}
+
+impl Material for ParticleMaterial {
+    fn vertex_shader() -> ShaderRef {
+        "shaders/particles.wgsl".into()
+    }
+
+    fn fragment_shader() -> ShaderRef {
+        "shaders/particles.wgsl".into()
+    }
+
+    fn alpha_mode(&self) -> AlphaMode {
+        AlphaMode::Add
+    }
+}
+
+#[derive(Component)]
+struct ParticleCloud;
+
+#[derive(Clone, Copy, Default)]
+struct AdvectionParams {
+    sigma: f32,
+    rho: f32,
+    beta: f32,
+    dt: f32,
+    steps: u32,
+    count: u32,
+}
+
+/// Main world state mirrored into the render world every frame.
+#[derive(Resource, Clone, Default, ExtractResource)]
+struct ParticleAdvection {
+    buffer: Option<Handle<ShaderStorageBuffer>>,
+    params: AdvectionParams,
+}
+
+/// Spawns, resizes or removes the particle cloud to match the configuration.
+fn sync_particle_cloud(
+    mut commands: Commands,
+    mut advection: ResMut<ParticleAdvection>,
+    clouds: Query<(Entity, &MeshMaterial3d<ParticleMaterial>)>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut buffers: ResMut<Assets<ShaderStorageBuffer>>,
+    mut particle_materials: ResMut<Assets<ParticleMaterial>>,
+    config: Res<Configuration>,
+) {
+    let count = config.particle_count.min(MAX_PARTICLES);
+    let color = LinearRgba::from(config.particle_color) * config.particle_exposure;
+    // The advection shader only integrates the Lorenz equations.
+    let enabled = config.particles_enabled && config.attractor == AttractorSystem::Lorenz;
+
+    if let Ok((cloud, material)) = clouds.get_single() {
+        if enabled && count == advection.params.count {
+            if let Some(material) = particle_materials.get_mut(material) {
+                material.color = color;
+            }
+            return;
+        }
+        commands.entity(cloud).despawn();
+        advection.buffer = None;
+        advection.params.count = 0;
+    }
+    if !enabled || count == 0 {
+        return;
+    }
+
+    // Seeds fill a box around the attractor; transients die out within a few seconds.
+    let mut rng = rand::thread_rng();
+    let seeds: Vec<Vec4> = (0..count)
+        .map(|_| {
+            Vec4::new(
+                rng.gen_range(-20.0..20.),
+                rng.gen_range(-25.0..25.),
+                rng.gen_range(0.0..50.),
+                1.,
+            )
+        })
+        .collect();
+    let mut buffer = ShaderStorageBuffer::from(seeds);
+    buffer.asset_usage = RenderAssetUsages::RENDER_WORLD;
+    let buffer = buffers.add(buffer);
+
+    // One dummy vertex per particle, the vertex shader ignores its position.
+    let mut mesh = Mesh::new(
+        PrimitiveTopology::PointList,
+        RenderAssetUsages::RENDER_WORLD,
+    );
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, vec![[0.; 3]; count as usize]);
+
+    commands.spawn((
+        ParticleCloud,
+        Mesh3d(meshes.add(mesh)),
+        MeshMaterial3d(particle_materials.add(ParticleMaterial {
+            particles: buffer.clone(),
+            color,
+        })),
+        NoFrustumCulling,
+        NotShadowCaster,
+    ));
+
+    advection.buffer = Some(buffer);
+    advection.params.count = count;
+}
+
+/// Advances the particles by as many simulation steps as the trail heads take this frame.
+fn update_advection_params(
+    mut advection: ResMut<ParticleAdvection>,
+    time: Res<Time<Virtual>>,
+    config: Res<Configuration>,
+) {
+    advection.params = AdvectionParams {
+        sigma: config.sigma,
+        rho: config.rho,
+        beta: config.beta,
+        dt: config.delta_t as f32 / 10000.,
+        steps: (time.delta_secs() * config.physics_refresh_rate as f32).round() as u32,
+        count: advection.params.count,
+    };
+}
+
+#[derive(Resource)]
+struct AdvectionPipeline {
+    layout: BindGroupLayout,
+    pipeline: CachedComputePipelineId,
+}
+
+impl FromWorld for AdvectionPipeline {
+    fn from_world(world: &mut World) -> Self {
+        let layout = world.resource::<RenderDevice>().create_bind_group_layout(
+            "particle_advection",
+            &BindGroupLayoutEntries::sequential(
+                ShaderStages::COMPUTE,
+                (
+                    storage_buffer::<Vec<Vec4>>(false),
+                    uniform_buffer::<Vec4>(false),
+                    uniform_buffer::<UVec2>(false),
+                ),
+            ),
+        );
+        let shader = world
+            .resource::<AssetServer>()
+            .load("shaders/particle_advection.wgsl");
+        let pipeline =
+            world
+                .resource::<PipelineCache>()
+                .queue_compute_pipeline(ComputePipelineDescriptor {
+                    label: Some("particle_advection".into()),
+                    layout: vec![layout.clone()],
+                    push_constant_ranges: Vec::new(),
+                    shader,
+                    shader_defs: Vec::new(),
+                    entry_point: "advect".into(),
+                    zero_initialize_workgroup_memory: false,
+                });
+
+        Self { layout, pipeline }
+    }
+}
+
+/// Bind group for this frame's dispatch, `None` when there is nothing to advance.
+#[derive(Resource, Default)]
+struct AdvectionBindGroup(Option<(BindGroup, u32)>);
+
+fn prepare_advection_bind_group(
+    mut bind_group: ResMut<AdvectionBindGroup>,
+    advection: Res<ParticleAdvection>,
+    pipeline: Res<AdvectionPipeline>,
+    buffers: Res<RenderAssets<GpuShaderStorageBuffer>>,
+    render_device: Res<RenderDevice>,
+    render_queue: Res<RenderQueue>,
+) {
+    bind_group.0 = None;
+
+    let Some(buffer) = advection
+        .buffer
+        .as_ref()
+        .and_then(|buffer| buffers.get(buffer))
+    else {
+        return;
+    };
+    if advection.params.steps == 0 || advection.params.count == 0 {
+        return;
+    }
+
+    let AdvectionParams {
+        sigma,
+        rho,
+        beta,
+        dt,
+        steps,
+        count,
+    } = advection.params;
+    let mut system = UniformBuffer::from(Vec4::new(sigma, rho, beta, dt));
+    system.write_buffer(&render_device, &render_queue);
+    let mut dispatch = UniformBuffer::from(UVec2::new(steps, count));
+    dispatch.write_buffer(&render_device, &render_queue);
+
+    bind_group.0 = Some((
+        render_device.create_bind_group(
+            "particle_advection",
+            &pipeline.layout,
+            &BindGroupEntries::sequential((buffer.buffer.as_entire_binding(), &system, &dispatch)),
+        ),
+        count,
+    ));
+}
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+struct AdvectionLabel;
+
+struct AdvectionNode;
+
+impl render_graph::Node for AdvectionNode {
+    fn run(
+        &self,
+        _graph: &mut render_graph::RenderGraphContext,
+        render_context: &mut RenderContext,
+        world: &World,
+    ) -> Result<(), render_graph::NodeRunError> {
+        let Some((bind_group, count)) = world.resource::<AdvectionBindGroup>().0.as_ref() else {
+            return Ok(());
+        };
+        let Some(pipeline) = world
+            .resource::<PipelineCache>()
+            .get_compute_pipeline(world.resource::<AdvectionPipeline>().pipeline)
+        else {
+            return Ok(());
+        };
+
+        let mut pass = render_context
+            .command_encoder()
+            .begin_compute_pass(&ComputePassDescriptor::default());
+        pass.set_pipeline(pipeline);
+        pass.set_bind_group(0, bind_group, &[]);
+        pass.dispatch_workgroups(count.div_ceil(WORKGROUP_SIZE), 1, 1);
+        Ok(())
+    }
+}
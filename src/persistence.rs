@@ -0,0 +1,239 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use bevy::{
+    prelude::*,
+    window::{PrimaryWindow, WindowCloseRequested},
+};
+use bevy_egui::{egui, EguiContext};
+use bevy_panorbit_camera::PanOrbitCamera;
+use serde::{Deserialize, Serialize};
+
+use crate::{snapshot, Configuration};
+
+/// File the simulation state is written to on exit, relative to the working directory.
+pub const AUTOSAVE_PATH: &str = "autosave.snapshot";
+/// Positions, sizes and open state of the egui windows.
+const LAYOUT_PATH: &str = "egui_layout.ron";
+/// Name of the window and camera settings file in the platform's config directory.
+const SETTINGS_FILE: &str = "settings.ron";
+
+pub struct PersistencePlugin;
+
+impl Plugin for PersistencePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, (offer_resume, restore_window_settings))
+            // Closing the window despawns it in `Update`, before `AppExit` is sent, so the
+            // layout and settings are saved on the close request while the window still exists.
+            .add_systems(PreUpdate, (save_layout_on_exit, save_settings_on_exit))
+            .add_systems(Update, (restore_layout, restore_camera_settings))
+            .add_systems(
+                Last,
+                (autosave_on_exit, save_layout_on_exit, save_settings_on_exit),
+            );
+    }
+}
+
+/// Present while the user hasn't yet decided whether to resume the auto-saved session.
+#[derive(Resource)]
+pub struct ResumePrompt;
+
+/// Only an enabled autosave writes the file, and discarding deletes it, so its presence alone
+/// decides. The configuration at startup doesn't know the last session's setting.
+fn offer_resume(mut commands: Commands) {
+    if Path::new(AUTOSAVE_PATH).exists() {
+        commands.insert_resource(ResumePrompt);
+    }
+}
+
+/// Deletes the auto-saved session, so it isn't offered again at the next launch.
+pub fn discard_autosave() {
+    if let Err(err) = fs::remove_file(AUTOSAVE_PATH) {
+        error!("Could not delete {AUTOSAVE_PATH}: {err}");
+    }
+}
+
+fn autosave_on_exit(world: &mut World) {
+    if world.resource::<Events<AppExit>>().is_empty()
+        || !world.resource::<Configuration>().autosave_on_exit
+    {
+        return;
+    }
+
+    let snapshot = snapshot::capture(world);
+    match snapshot::save(&snapshot, Path::new(AUTOSAVE_PATH)) {
+        Ok(()) => info!("Saved simulation state to {AUTOSAVE_PATH}"),
+        Err(err) => error!("Could not save simulation state: {err}"),
+    }
+}
+
+/// Runs until the primary window's egui context exists, then restores the saved layout once.
+fn restore_layout(
+    mut contexts: Query<&mut EguiContext, With<PrimaryWindow>>,
+    mut restored: Local<bool>,
+) {
+    if *restored {
+        return;
+    }
+    let Ok(mut context) = contexts.get_single_mut() else {
+        return;
+    };
+    *restored = true;
+
+    let Ok(content) = fs::read_to_string(LAYOUT_PATH) else {
+        return;
+    };
+    match ron::from_str::<egui::Memory>(&content) {
+        Ok(memory) => context.get_mut().memory_mut(|current| *current = memory),
+        Err(err) => warn!("Ignoring unreadable {LAYOUT_PATH}: {err}"),
+    }
+}
+
+/// Whether the app is about to exit, either on its own or because a window is being closed.
+fn exiting(exit: &EventReader<AppExit>, close: &EventReader<WindowCloseRequested>) -> bool {
+    !exit.is_empty() || !close.is_empty()
+}
+
+fn save_layout_on_exit(
+    exit: EventReader<AppExit>,
+    close: EventReader<WindowCloseRequested>,
+    mut contexts: Query<&mut EguiContext, With<PrimaryWindow>>,
+) {
+    if !exiting(&exit, &close) {
+        return;
+    }
+    let Ok(mut context) = contexts.get_single_mut() else {
+        return;
+    };
+
+    let memory = context.get_mut().memory(|memory| memory.clone());
+    let result = ron::to_string(&memory)
+        .map_err(|err| err.to_string())
+        .and_then(|content| fs::write(LAYOUT_PATH, content).map_err(|err| err.to_string()));
+    if let Err(err) = result {
+        error!("Could not save window layout: {err}");
+    }
+}
+
+/// Window and camera state restored at the next launch, independent of the working directory.
+#[derive(Resource, Clone, Serialize, Deserialize)]
+struct Settings {
+    /// Logical size of the window.
+    window_size: Vec2,
+    /// `None` while the window manager places the window.
+    window_position: Option<IVec2>,
+    camera_focus: Vec3,
+    camera_yaw: f32,
+    camera_pitch: f32,
+    camera_radius: f32,
+    show_diagnostics: bool,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn settings_path() -> Option<PathBuf> {
+    directories::ProjectDirs::from("", "", "lorenz_system")
+        .map(|dirs| dirs.config_dir().join(SETTINGS_FILE))
+}
+
+/// The browser keeps no files.
+#[cfg(target_arch = "wasm32")]
+fn settings_path() -> Option<PathBuf> {
+    None
+}
+
+fn load_settings() -> Option<Settings> {
+    let path = settings_path()?;
+    let content = fs::read_to_string(&path).ok()?;
+    ron::from_str(&content)
+        .inspect_err(|err| warn!("Ignoring unreadable {}: {err}", path.display()))
+        .ok()
+}
+
+/// Applies the window part of the saved settings, and keeps the rest for the camera, which
+/// doesn't exist yet.
+fn restore_window_settings(
+    mut commands: Commands,
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+    mut config: ResMut<Configuration>,
+) {
+    let Some(settings) = load_settings() else {
+        return;
+    };
+    if let Ok(mut window) = windows.get_single_mut() {
+        window
+            .resolution
+            .set(settings.window_size.x, settings.window_size.y);
+        if let Some(position) = settings.window_position {
+            window.position = WindowPosition::At(position);
+        }
+    }
+    config.show_diagnostics = settings.show_diagnostics;
+    commands.insert_resource(settings);
+}
+
+/// Runs until the orbit camera exists, then moves it to the saved angle once.
+fn restore_camera_settings(
+    mut commands: Commands,
+    settings: Option<Res<Settings>>,
+    mut cameras: Query<&mut PanOrbitCamera>,
+) {
+    let Some(settings) = settings else {
+        return;
+    };
+    let Ok(mut camera) = cameras.get_single_mut() else {
+        return;
+    };
+    camera.focus = settings.camera_focus;
+    camera.target_focus = settings.camera_focus;
+    camera.yaw = Some(settings.camera_yaw);
+    camera.target_yaw = settings.camera_yaw;
+    camera.pitch = Some(settings.camera_pitch);
+    camera.target_pitch = settings.camera_pitch;
+    camera.radius = Some(settings.camera_radius);
+    camera.target_radius = settings.camera_radius;
+    camera.force_update = true;
+    commands.remove_resource::<Settings>();
+}
+
+fn save_settings_on_exit(
+    exit: EventReader<AppExit>,
+    close: EventReader<WindowCloseRequested>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    cameras: Query<&PanOrbitCamera>,
+    config: Res<Configuration>,
+) {
+    if !exiting(&exit, &close) {
+        return;
+    }
+    let (Some(path), Ok(window), Ok(camera)) =
+        (settings_path(), windows.get_single(), cameras.get_single())
+    else {
+        return;
+    };
+
+    let settings = Settings {
+        window_size: Vec2::new(window.resolution.width(), window.resolution.height()),
+        window_position: match window.position {
+            WindowPosition::At(position) => Some(position),
+            _ => None,
+        },
+        camera_focus: camera.target_focus,
+        camera_yaw: camera.target_yaw,
+        camera_pitch: camera.target_pitch,
+        camera_radius: camera.target_radius,
+        show_diagnostics: config.show_diagnostics,
+    };
+    let result = ron::to_string(&settings)
+        .map_err(|err| err.to_string())
+        .and_then(|content| {
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir).map_err(|err| err.to_string())?;
+            }
+            fs::write(&path, content).map_err(|err| err.to_string())
+        });
+    if let Err(err) = result {
+        error!("Could not save window and camera settings: {err}");
+    }
+}
@@ -0,0 +1,109 @@
+use bevy::prelude::*;
+use bevy_egui::egui;
+
+use crate::{
+    validation::{MAX_TIME_SCALE, MIN_TIME_SCALE},
+    Configuration,
+};
+
+pub struct PlaybackPlugin;
+
+impl Plugin for PlaybackPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SimulationState>()
+            .init_resource::<PendingSteps>()
+            .add_systems(
+                Update,
+                (
+                    apply_simulation_state.run_if(resource_changed::<SimulationState>),
+                    run_pending_steps,
+                )
+                    .chain(),
+            )
+            .add_systems(
+                Update,
+                apply_time_scale.run_if(|config: Res<Configuration>| config.is_changed()),
+            );
+    }
+}
+
+/// Whether the simulation advances. Pausing stops virtual time, so trails stop fading as well.
+#[derive(Resource, Default, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SimulationState {
+    #[default]
+    Running,
+    Paused,
+}
+
+impl SimulationState {
+    pub fn toggle(&mut self) {
+        *self = match self {
+            SimulationState::Running => SimulationState::Paused,
+            SimulationState::Paused => SimulationState::Running,
+        };
+    }
+}
+
+/// Physics ticks to run while paused.
+#[derive(Resource, Default)]
+pub struct PendingSteps(pub u32);
+
+fn apply_simulation_state(state: Res<SimulationState>, mut time: ResMut<Time<Virtual>>) {
+    match *state {
+        SimulationState::Running => time.unpause(),
+        SimulationState::Paused => time.pause(),
+    }
+}
+
+/// Scales virtual time, which the fixed schedule and trail fading both follow. The real frame
+/// time is capped before scaling, so a slow frame at high speed still can't run away.
+fn apply_time_scale(config: Res<Configuration>, mut time: ResMut<Time<Virtual>>) {
+    if time.relative_speed() != config.time_scale {
+        time.set_relative_speed(config.time_scale);
+    }
+}
+
+/// Runs the fixed schedule by hand, since paused virtual time never accumulates a tick.
+fn run_pending_steps(world: &mut World) {
+    let steps = std::mem::take(&mut world.resource_mut::<PendingSteps>().0);
+    if *world.resource::<SimulationState>() == SimulationState::Paused {
+        for _ in 0..steps {
+            world.run_schedule(FixedUpdate);
+        }
+    }
+}
+
+pub fn playback_ui(world: &mut World, ui: &mut egui::Ui) {
+    let state = *world.resource::<SimulationState>();
+    ui.horizontal(|ui| {
+        let label = match state {
+            SimulationState::Running => "Pause",
+            SimulationState::Paused => "Resume",
+        };
+        if ui.button(label).clicked() {
+            world.resource_mut::<SimulationState>().toggle();
+        }
+        if ui
+            .add_enabled(
+                state == SimulationState::Paused,
+                egui::Button::new("Step one tick"),
+            )
+            .clicked()
+        {
+            world.resource_mut::<PendingSteps>().0 += 1;
+        }
+    });
+
+    let mut time_scale = world.resource::<Configuration>().time_scale;
+    if ui
+        .add(
+            egui::Slider::new(&mut time_scale, MIN_TIME_SCALE..=MAX_TIME_SCALE)
+                .logarithmic(true)
+                .suffix("×")
+                .text("Speed"),
+        )
+        .changed()
+    {
+        world.resource_mut::<Configuration>().time_scale = time_scale;
+    }
+}
@@ -0,0 +1,249 @@
+use std::{collections::VecDeque, fmt::Write as _, fs, path::Path};
+
+use bevy::{prelude::*, utils::HashMap};
+use bevy_egui::{egui, EguiContexts};
+use egui_plot::{Plot, Points};
+
+use crate::{
+    event_log::{LogCategory, LogEntry},
+    file_dialog::{self, DialogKind},
+    slice::slice_normal,
+    update_position, Configuration, HeadIndex, TrailHead,
+};
+
+pub struct PoincarePlugin;
+
+impl Plugin for PoincarePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SectionCrossings>()
+            .init_resource::<PoincarePanel>()
+            .add_systems(
+                FixedUpdate,
+                capture_crossings
+                    .after(update_position)
+                    .run_if(|config: Res<Configuration>| config.slice_enabled),
+            )
+            .add_systems(
+                Update,
+                (
+                    poincare_ui,
+                    draw_crossings.run_if(|panel: Res<PoincarePanel>| panel.show_points),
+                ),
+            );
+    }
+}
+
+const POINT_COLOR: Color = Color::srgb(0.3, 1., 0.8);
+const POINT_RADIUS: f32 = 0.15;
+
+/// A head passing through the slicing plane along the plane normal.
+#[derive(Clone, Copy, Debug)]
+pub struct Crossing {
+    pub head: u16,
+    pub time: f32,
+    pub position: Vec3,
+}
+
+/// Crossings of the slicing plane, which doubles as the Poincaré section, oldest first. Once
+/// `crossing_capacity` crossings are stored the oldest ones are dropped.
+#[derive(Resource, Default)]
+pub struct SectionCrossings {
+    pub crossings: VecDeque<Crossing>,
+    /// Position of every head on the previous tick.
+    previous: HashMap<Entity, Vec3>,
+}
+
+impl SectionCrossings {
+    /// Writes the crossings as `head,t,x,y,z` rows, the reference trajectory layout.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("head,t,x,y,z\n");
+        for crossing in &self.crossings {
+            let Vec3 { x, y, z } = crossing.position;
+            writeln!(csv, "{},{},{x},{y},{z}", crossing.head, crossing.time)
+                .expect("writing to a String cannot fail");
+        }
+        csv
+    }
+}
+
+/// Records one-sided crossings, from below to above the plane, with the crossing point
+/// linearly interpolated between both ticks.
+fn capture_crossings(
+    mut section: ResMut<SectionCrossings>,
+    heads: Query<(Entity, &HeadIndex, &Transform), With<TrailHead>>,
+    mut log: EventWriter<LogEntry>,
+    time: Res<Time>,
+    config: Res<Configuration>,
+) {
+    let normal = slice_normal(&config);
+    let dt = time.delta_secs();
+    let mut current = HashMap::default();
+
+    let signed_distance = |position: Vec3| position.dot(normal) - config.slice_offset;
+
+    for (entity, index, transform) in &heads {
+        let position = transform.translation;
+        current.insert(entity, position);
+
+        let Some(&previous) = section.previous.get(&entity) else {
+            continue;
+        };
+        let (before, after) = (signed_distance(previous), signed_distance(position));
+        if before < 0. && after >= 0. {
+            let s = before / (before - after);
+            let crossing = Crossing {
+                head: index.0,
+                time: time.elapsed_secs() - (1. - s) * dt,
+                position: previous.lerp(position, s),
+            };
+            log.send(LogEntry::new(
+                LogCategory::Crossing,
+                format!(
+                    "Head {} crossed the section at {}",
+                    crossing.head, crossing.position
+                ),
+            ));
+            section.crossings.push_back(crossing);
+        }
+    }
+
+    let excess = section
+        .crossings
+        .len()
+        .saturating_sub(config.crossing_capacity as usize);
+    section.crossings.drain(..excess);
+    section.previous = current;
+}
+
+#[derive(Resource)]
+struct PoincarePanel {
+    /// Keep drawing the crossings in the scene, not just in the plot.
+    show_points: bool,
+    export_path: String,
+    status: Option<Result<String, String>>,
+}
+
+impl Default for PoincarePanel {
+    fn default() -> Self {
+        Self {
+            show_points: true,
+            export_path: "crossings.csv".into(),
+            status: None,
+        }
+    }
+}
+
+fn draw_crossings(mut gizmos: Gizmos, section: Res<SectionCrossings>) {
+    for crossing in &section.crossings {
+        gizmos
+            .sphere(
+                Isometry3d::from_translation(crossing.position),
+                POINT_RADIUS,
+                POINT_COLOR,
+            )
+            .resolution(6);
+    }
+}
+
+/// Coordinates of `point` within the plane, along two axes orthogonal to its normal.
+fn plane_coordinates(config: &Configuration, point: Vec3) -> [f64; 2] {
+    let rotation = Quat::from_rotation_arc(Vec3::Z, slice_normal(config));
+    let (u, v) = (rotation * Vec3::X, rotation * Vec3::Y);
+    [point.dot(u) as f64, point.dot(v) as f64]
+}
+
+/// Sliders for the plane, writing back only what was changed so unrelated systems don't see
+/// a configuration change every frame.
+fn plane_ui(ui: &mut egui::Ui, config: &mut ResMut<Configuration>) {
+    let mut enabled = config.slice_enabled;
+    if ui.checkbox(&mut enabled, "Capture crossings").changed() {
+        config.slice_enabled = enabled;
+    }
+
+    let (mut yaw, mut pitch, mut offset) =
+        (config.slice_yaw, config.slice_pitch, config.slice_offset);
+    let mut changed = false;
+    changed |= ui
+        .add(egui::Slider::new(&mut yaw, -180.0..=180.0).text("yaw"))
+        .changed();
+    changed |= ui
+        .add(egui::Slider::new(&mut pitch, -90.0..=90.0).text("pitch"))
+        .changed();
+    changed |= ui
+        .add(egui::Slider::new(&mut offset, -60.0..=60.0).text("offset"))
+        .changed();
+    if ui.button("z = ρ − 1").clicked() {
+        (yaw, pitch, offset) = (0., 90., config.rho - 1.);
+        changed = true;
+    }
+    if changed {
+        (config.slice_yaw, config.slice_pitch, config.slice_offset) = (yaw, pitch, offset);
+    }
+}
+
+fn poincare_ui(
+    mut contexts: EguiContexts,
+    mut section: ResMut<SectionCrossings>,
+    mut panel: ResMut<PoincarePanel>,
+    mut config: ResMut<Configuration>,
+) {
+    let panel = &mut *panel;
+
+    egui::Window::new("Poincaré section")
+        .default_open(false)
+        .show(contexts.ctx_mut(), |ui| {
+            plane_ui(ui, &mut config);
+            ui.horizontal(|ui| {
+                ui.label(format!("{} crossings", section.crossings.len()));
+                if ui.button("Clear").clicked() {
+                    section.crossings.clear();
+                }
+                ui.checkbox(&mut panel.show_points, "Show in scene");
+            });
+
+            let points: Vec<[f64; 2]> = section
+                .crossings
+                .iter()
+                .map(|crossing| plane_coordinates(&config, crossing.position))
+                .collect();
+            Plot::new("poincare_section")
+                .height(220.)
+                .data_aspect(1.)
+                .show(ui, |plot_ui| {
+                    plot_ui.points(Points::new(points).radius(1.5).name("crossings"));
+                });
+
+            ui.horizontal(|ui| {
+                file_dialog::path_field(
+                    ui,
+                    &mut panel.export_path,
+                    DialogKind::Save,
+                    file_dialog::CSV,
+                );
+                if ui
+                    .add_enabled(
+                        !section.crossings.is_empty(),
+                        egui::Button::new("Export CSV"),
+                    )
+                    .clicked()
+                {
+                    let path = Path::new(&panel.export_path);
+                    panel.status = Some(
+                        fs::write(path, section.to_csv())
+                            .map(|()| format!("Exported {}", path.display()))
+                            .map_err(|err| format!("{}: {err}", path.display())),
+                    );
+                }
+            });
+
+            match panel.status.as_ref() {
+                Some(Ok(message)) => {
+                    ui.label(message);
+                }
+                Some(Err(err)) => {
+                    ui.colored_label(egui::Color32::RED, err);
+                }
+                None => {}
+            }
+        });
+}
@@ -0,0 +1,71 @@
+use bevy::{math::DVec3, prelude::*};
+
+use crate::{
+    event_log::{LogCategory, LogEntry},
+    Configuration, SimpleColorMaterial,
+};
+
+/// Distance between a head and its double-precision twin at which they count as diverged.
+const DIVERGENCE_DISTANCE: f32 = 1.;
+
+pub struct PrecisionPlugin;
+
+impl Plugin for PrecisionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            mark_precision_divergence.run_if(|config: Res<Configuration>| config.compare_precision),
+        );
+    }
+}
+
+/// Double-precision state of a trail head. Its `Transform` only mirrors this value for
+/// rendering.
+#[derive(Component, Deref, DerefMut)]
+pub struct DoublePrecision(pub DVec3);
+
+/// Links a double-precision head to the single-precision head started from the same point.
+#[derive(Component)]
+pub struct PrecisionTwin(pub Entity);
+
+/// Marks where a pair of precision twins first drifted apart.
+#[derive(Component)]
+pub struct DivergenceMarker;
+
+fn mark_precision_divergence(
+    query: Query<(Entity, &Transform, &PrecisionTwin)>,
+    transforms: Query<&Transform>,
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut simple_color_materials: ResMut<Assets<SimpleColorMaterial>>,
+    mut log: EventWriter<LogEntry>,
+    time: Res<Time<Virtual>>,
+) {
+    for (entity, transform, twin) in &query {
+        let Ok(twin_transform) = transforms.get(twin.0) else {
+            continue;
+        };
+        if transform.translation.distance(twin_transform.translation) < DIVERGENCE_DISTANCE {
+            continue;
+        }
+
+        let midpoint = transform.translation.lerp(twin_transform.translation, 0.5);
+        let message = format!(
+            "f32 and f64 trajectories diverged at t = {:.2}s near {midpoint}",
+            time.elapsed_secs()
+        );
+        info!("{message}");
+        log.send(LogEntry::new(LogCategory::Divergence, message));
+
+        commands.entity(entity).remove::<PrecisionTwin>();
+        commands.spawn((
+            DivergenceMarker,
+            Mesh3d(meshes.add(Sphere::new(0.6))),
+            MeshMaterial3d(simple_color_materials.add(SimpleColorMaterial {
+                color: LinearRgba::new(1., 1., 1., 0.5),
+                ..default()
+            })),
+            Transform::from_translation(midpoint),
+        ));
+    }
+}
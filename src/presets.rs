@@ -0,0 +1,202 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use bevy::prelude::*;
+use bevy_egui::egui;
+use ron::ser::PrettyConfig;
+
+use crate::{gui, Configuration};
+
+/// Preset files are plain RON configurations. Fields they leave out keep their defaults, so
+/// hand-written presets only need to list what they change.
+pub const PRESETS_DIR: &str = "presets";
+
+pub struct PresetsPlugin;
+
+impl Plugin for PresetsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Presets>();
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct Presets {
+    /// File stems in the presets directory, `None` until the directory was read.
+    names: Option<Vec<String>>,
+    new_name: String,
+    status: Option<Result<String, String>>,
+}
+
+/// The browser has no presets directory, so the web build ships the bundled presets.
+#[cfg(target_arch = "wasm32")]
+const BUNDLED: &[(&str, &str)] = &[
+    (
+        "classic_lorenz",
+        include_str!("../presets/classic_lorenz.ron"),
+    ),
+    (
+        "periodic_window",
+        include_str!("../presets/periodic_window.ron"),
+    ),
+    ("rossler", include_str!("../presets/rossler.ron")),
+    (
+        "sensitive_dependence",
+        include_str!("../presets/sensitive_dependence.ron"),
+    ),
+    (
+        "transient_chaos",
+        include_str!("../presets/transient_chaos.ron"),
+    ),
+];
+
+fn path(name: &str) -> PathBuf {
+    PathBuf::from(PRESETS_DIR).join(format!("{name}.ron"))
+}
+
+#[cfg(target_arch = "wasm32")]
+fn list() -> Result<Vec<String>, String> {
+    Ok(BUNDLED.iter().map(|(name, _)| name.to_string()).collect())
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn list() -> Result<Vec<String>, String> {
+    let entries = match fs::read_dir(PRESETS_DIR) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(format!("{PRESETS_DIR}: {err}")),
+    };
+    let mut names: Vec<String> = entries
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            (path.extension()? == "ron").then(|| path.file_stem()?.to_str().map(str::to_string))?
+        })
+        .collect();
+    names.sort();
+    Ok(names)
+}
+
+#[cfg(target_arch = "wasm32")]
+fn read(name: &str) -> Result<String, String> {
+    BUNDLED
+        .iter()
+        .find(|(bundled, _)| *bundled == name)
+        .map(|(_, text)| text.to_string())
+        .ok_or_else(|| format!("{}: no such preset", path(name).display()))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn read(name: &str) -> Result<String, String> {
+    let path = path(name);
+    fs::read_to_string(&path).map_err(|err| format!("{}: {err}", path.display()))
+}
+
+pub fn load(name: &str) -> Result<Configuration, String> {
+    parse(&path(name), &read(name)?)
+}
+
+/// Loads a preset from anywhere instead of by name.
+pub fn load_file(path: &Path) -> Result<Configuration, String> {
+    let text = fs::read_to_string(path).map_err(|err| format!("{}: {err}", path.display()))?;
+    parse(path, &text)
+}
+
+fn parse(path: &Path, text: &str) -> Result<Configuration, String> {
+    let config: Configuration =
+        ron::from_str(text).map_err(|err| format!("{}: {err}", path.display()))?;
+    config.validate()?;
+    Ok(config)
+}
+
+fn save(name: &str, config: &Configuration) -> Result<(), String> {
+    if cfg!(target_arch = "wasm32") {
+        return Err("Presets can't be saved in the browser".into());
+    }
+    if name.is_empty() || name.contains(['/', '\\', '.']) {
+        return Err("Preset names must not be empty or contain '/', '\\' or '.'".into());
+    }
+    let ron = ron::ser::to_string_pretty(config, PrettyConfig::default())
+        .expect("Configuration is always serializable");
+    let path = path(name);
+    fs::create_dir_all(PRESETS_DIR)
+        .and_then(|()| fs::write(&path, ron))
+        .map_err(|err| format!("{}: {err}", path.display()))
+}
+
+fn delete(name: &str) -> Result<(), String> {
+    if cfg!(target_arch = "wasm32") {
+        return Err("Bundled presets can't be deleted".into());
+    }
+    let path = path(name);
+    fs::remove_file(&path).map_err(|err| format!("{}: {err}", path.display()))
+}
+
+pub fn presets_ui(world: &mut World, ui: &mut egui::Ui) {
+    let mut presets = world.resource_mut::<Presets>();
+    if presets.names.is_none() {
+        match list() {
+            Ok(names) => presets.names = Some(names),
+            Err(err) => {
+                presets.names = Some(Vec::new());
+                presets.status = Some(Err(err));
+            }
+        }
+    }
+
+    let names = presets.names.clone().unwrap_or_default();
+    let mut new_name = std::mem::take(&mut presets.new_name);
+
+    let (mut loaded, mut deleted, mut saved) = (None, None, false);
+    for name in names {
+        ui.horizontal(|ui| {
+            ui.label(&name);
+            if ui.button("Load").clicked() {
+                loaded = Some(name.clone());
+            }
+            if ui.button("Delete").clicked() {
+                deleted = Some(name.clone());
+            }
+        });
+    }
+
+    ui.horizontal(|ui| {
+        ui.text_edit_singleline(&mut new_name);
+        saved = ui.button("Save").clicked();
+    });
+
+    if saved {
+        let name = new_name.trim();
+        let result = save(name, world.resource::<Configuration>());
+        let mut presets = world.resource_mut::<Presets>();
+        presets.status = Some(result.map(|()| format!("Saved {name}")));
+        presets.names = None;
+    }
+    world.resource_mut::<Presets>().new_name = new_name;
+
+    if let Some(name) = deleted {
+        let mut presets = world.resource_mut::<Presets>();
+        presets.status = Some(delete(&name).map(|()| format!("Deleted {name}")));
+        presets.names = None;
+    }
+
+    if let Some(name) = loaded {
+        let result = load(&name).map(|config| {
+            world.insert_resource(config);
+            gui::clear(world);
+            gui::start(world);
+            format!("Loaded {name}")
+        });
+        world.resource_mut::<Presets>().status = Some(result);
+    }
+
+    match world.resource::<Presets>().status.as_ref() {
+        Some(Ok(message)) => {
+            ui.label(message);
+        }
+        Some(Err(err)) => {
+            ui.colored_label(egui::Color32::RED, err);
+        }
+        None => {}
+    }
+}
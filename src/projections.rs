@@ -0,0 +1,161 @@
+use bevy::{
+    prelude::*,
+    render::{
+        camera::{RenderTarget, ScalingMode},
+        render_resource::{Extent3d, TextureDimension, TextureFormat, TextureUsages},
+    },
+};
+use bevy_egui::{egui, EguiContexts};
+use bevy_panorbit_camera::PanOrbitCamera;
+
+use crate::{views::View, Configuration};
+
+/// Width and height of each projection image in pixels.
+const SIZE: u32 = 240;
+/// Distance of the projection cameras from the focus, far enough to keep the whole attractor
+/// in front of them.
+const DISTANCE: f32 = 500.;
+const VIEWS: [View; 3] = [View::Xy, View::Xz, View::Zy];
+
+pub struct ProjectionsPlugin;
+
+impl Plugin for ProjectionsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, spawn_projection_cameras)
+            .add_systems(
+                Update,
+                (
+                    update_projection_cameras,
+                    projections_ui.run_if(|config: Res<Configuration>| config.projection_views),
+                ),
+            );
+    }
+}
+
+/// An orthographic camera rendering `view` into `image`, shown in the projections window.
+#[derive(Component)]
+struct ProjectionCamera {
+    view: View,
+    image: Handle<Image>,
+}
+
+fn spawn_projection_cameras(mut commands: Commands, mut images: ResMut<Assets<Image>>) {
+    for (i, view) in VIEWS.into_iter().enumerate() {
+        let image = images.add(projection_image());
+        commands.spawn((
+            ProjectionCamera {
+                view,
+                image: image.clone(),
+            },
+            Camera3d::default(),
+            Camera {
+                // Before the main camera, so the images are ready when egui draws them.
+                order: -(i as isize) - 1,
+                target: RenderTarget::Image(image),
+                is_active: false,
+                ..default()
+            },
+            Projection::Orthographic(OrthographicProjection::default_3d()),
+        ));
+    }
+}
+
+fn projection_image() -> Image {
+    let mut image = Image::new_fill(
+        Extent3d {
+            width: SIZE,
+            height: SIZE,
+            ..default()
+        },
+        TextureDimension::D2,
+        &[0; 4],
+        TextureFormat::Bgra8UnormSrgb,
+        default(),
+    );
+    image.texture_descriptor.usage =
+        TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST | TextureUsages::RENDER_ATTACHMENT;
+    image
+}
+
+/// Points the projection cameras at the orbit camera's focus and only renders them while the
+/// window is shown.
+fn update_projection_cameras(
+    mut cameras: Query<(
+        &ProjectionCamera,
+        &mut Camera,
+        &mut Transform,
+        &mut Projection,
+    )>,
+    orbit_cameras: Query<&PanOrbitCamera>,
+    config: Res<Configuration>,
+) {
+    let focus = orbit_cameras
+        .get_single()
+        .map_or(Vec3::ZERO, |camera| camera.target_focus);
+
+    for (projection_camera, mut camera, mut transform, mut projection) in &mut cameras {
+        if camera.is_active != config.projection_views {
+            camera.is_active = config.projection_views;
+        }
+        if !config.projection_views {
+            continue;
+        }
+
+        let view_transform = projection_camera.view.transform(focus, DISTANCE);
+        if *transform != view_transform {
+            *transform = view_transform;
+        }
+        let height = config.projection_extent * 2.;
+        if !matches!(
+            &*projection,
+            Projection::Orthographic(OrthographicProjection {
+                scaling_mode: ScalingMode::FixedVertical { viewport_height },
+                ..
+            }) if *viewport_height == height
+        ) {
+            *projection = Projection::Orthographic(OrthographicProjection {
+                scaling_mode: ScalingMode::FixedVertical {
+                    viewport_height: height,
+                },
+                ..OrthographicProjection::default_3d()
+            });
+        }
+    }
+}
+
+fn projections_ui(
+    mut contexts: EguiContexts,
+    cameras: Query<&ProjectionCamera>,
+    mut config: ResMut<Configuration>,
+) {
+    let mut views: Vec<(View, Handle<Image>)> = cameras
+        .iter()
+        .map(|camera| (camera.view, camera.image.clone()))
+        .collect();
+    views.sort_by_key(|&(view, _)| VIEWS.iter().position(|&other| other == view));
+    let textures: Vec<(View, egui::TextureId)> = views
+        .into_iter()
+        .map(|(view, image)| (view, contexts.add_image(image)))
+        .collect();
+
+    let mut open = true;
+    egui::Window::new("Projections")
+        .open(&mut open)
+        .show(contexts.ctx_mut(), |ui| {
+            ui.horizontal(|ui| {
+                for (view, texture) in textures {
+                    ui.vertical(|ui| {
+                        ui.label(view.name());
+                        ui.image(egui::load::SizedTexture::new(
+                            texture,
+                            [SIZE as f32, SIZE as f32],
+                        ));
+                    });
+                }
+            });
+        });
+
+    if !open {
+        config.projection_views = false;
+    }
+}
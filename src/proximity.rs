@@ -0,0 +1,120 @@
+use std::time::Duration;
+
+use bevy::{
+    audio::{Pitch, PlaybackSettings},
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
+
+use crate::{update_position, Configuration, TrailHead};
+
+const FLASH_DURATION: f32 = 0.5;
+const BEEP_FREQUENCY: f32 = 880.;
+const BEEP_DURATION: Duration = Duration::from_millis(120);
+
+pub struct ProximityPlugin;
+
+impl Plugin for ProximityPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<HeadsApproached>()
+            .init_resource::<PairDistances>()
+            .add_systems(
+                FixedUpdate,
+                detect_approaches
+                    .after(update_position)
+                    .run_if(|config: Res<Configuration>| config.proximity_events),
+            )
+            .add_systems(Update, (react_to_approaches, fade_flashes));
+    }
+}
+
+/// Sent when two heads that were apart come closer than `proximity_distance`.
+#[derive(Event, Debug)]
+pub struct HeadsApproached {
+    pub heads: [Entity; 2],
+    pub distance: f32,
+}
+
+/// Whether each pair of heads was within range on the previous tick. Pairs seen for the first
+/// time only get recorded, so heads spawned close together don't all fire at once.
+#[derive(Resource, Default)]
+struct PairDistances(HashMap<(Entity, Entity), bool>);
+
+/// Temporarily brightens a head after a close approach.
+#[derive(Component)]
+pub struct Flash(Timer);
+
+impl Flash {
+    /// Decays from 1 to 0 over the flash duration.
+    pub fn brightness(&self) -> f32 {
+        1. - self.0.fraction()
+    }
+}
+
+fn detect_approaches(
+    heads: Query<(Entity, &Transform), With<TrailHead>>,
+    mut pairs: ResMut<PairDistances>,
+    mut events: EventWriter<HeadsApproached>,
+    config: Res<Configuration>,
+) {
+    let heads: Vec<(Entity, Vec3)> = heads
+        .iter()
+        .map(|(entity, transform)| (entity, transform.translation))
+        .collect();
+
+    for (i, &(a, position_a)) in heads.iter().enumerate() {
+        for &(b, position_b) in &heads[i + 1..] {
+            let distance = position_a.distance(position_b);
+            let near = distance < config.proximity_distance;
+            let key = (a.min(b), a.max(b));
+            if pairs.0.insert(key, near) == Some(false) && near {
+                events.send(HeadsApproached {
+                    heads: [a, b],
+                    distance,
+                });
+            }
+        }
+    }
+
+    let alive: HashSet<Entity> = heads.iter().map(|&(entity, _)| entity).collect();
+    pairs
+        .0
+        .retain(|(a, b), _| alive.contains(a) && alive.contains(b));
+}
+
+fn react_to_approaches(
+    mut commands: Commands,
+    mut events: EventReader<HeadsApproached>,
+    mut pitches: ResMut<Assets<Pitch>>,
+    config: Res<Configuration>,
+) {
+    for event in events.read() {
+        info!(
+            "Heads {} and {} approached within {:.3}",
+            event.heads[0], event.heads[1], event.distance
+        );
+
+        if config.proximity_flash {
+            for head in event.heads {
+                if let Some(mut entity) = commands.get_entity(head) {
+                    entity.insert(Flash(Timer::from_seconds(FLASH_DURATION, TimerMode::Once)));
+                }
+            }
+        }
+
+        if config.proximity_sound {
+            commands.spawn((
+                AudioPlayer(pitches.add(Pitch::new(BEEP_FREQUENCY, BEEP_DURATION))),
+                PlaybackSettings::DESPAWN,
+            ));
+        }
+    }
+}
+
+fn fade_flashes(mut commands: Commands, mut flashes: Query<(Entity, &mut Flash)>, time: Res<Time>) {
+    for (entity, mut flash) in &mut flashes {
+        if flash.0.tick(time.delta()).finished() {
+            commands.entity(entity).remove::<Flash>();
+        }
+    }
+}
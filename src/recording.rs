@@ -0,0 +1,180 @@
+use std::{collections::VecDeque, fmt::Write as _, fs, path::Path};
+
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+use serde::Serialize;
+
+use crate::{
+    clock::{advance_simulation_clock, SimulationClock},
+    file_dialog::{self, DialogKind},
+    integrator::Integrator,
+    Configuration, HeadIndex, TrailHead,
+};
+
+pub struct RecordingPlugin;
+
+impl Plugin for RecordingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TrajectoryRecording>()
+            .init_resource::<RecordingPanel>()
+            .add_systems(
+                FixedUpdate,
+                record_trajectories
+                    .after(advance_simulation_clock)
+                    .run_if(|config: Res<Configuration>| config.record_trajectories),
+            )
+            .add_systems(Update, recording_ui);
+    }
+}
+
+#[derive(Serialize)]
+pub struct TrajectorySample {
+    pub head: u16,
+    pub integrator: Integrator,
+    pub t: f64,
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+/// Every head's position at every tick while recording, oldest first. Once
+/// `recording_capacity` samples are stored the oldest ones are dropped.
+#[derive(Resource, Default)]
+pub struct TrajectoryRecording(pub VecDeque<TrajectorySample>);
+
+impl TrajectoryRecording {
+    fn to_csv(&self) -> String {
+        let mut csv = String::from("head,integrator,t,x,y,z\n");
+        for sample in &self.0 {
+            writeln!(
+                csv,
+                "{},{},{},{},{},{}",
+                sample.head,
+                sample.integrator.name(),
+                sample.t,
+                sample.x,
+                sample.y,
+                sample.z
+            )
+            .expect("writing to a String cannot fail");
+        }
+        csv
+    }
+
+    fn to_json(&self) -> String {
+        serde_json::to_string(&self.0).expect("samples are always serializable")
+    }
+
+    /// Writes JSON if the path ends in `.json`, and CSV otherwise.
+    pub fn export(&self, path: &Path) -> Result<(), String> {
+        let contents = if path
+            .extension()
+            .is_some_and(|extension| extension == "json")
+        {
+            self.to_json()
+        } else {
+            self.to_csv()
+        };
+        fs::write(path, contents).map_err(|err| format!("{}: {err}", path.display()))
+    }
+}
+
+fn record_trajectories(
+    mut recording: ResMut<TrajectoryRecording>,
+    heads: Query<(&HeadIndex, &Integrator, &Transform), With<TrailHead>>,
+    clock: Res<SimulationClock>,
+    config: Res<Configuration>,
+) {
+    for (index, integrator, transform) in &heads {
+        let Vec3 { x, y, z } = transform.translation;
+        recording.0.push_back(TrajectorySample {
+            head: index.0,
+            integrator: *integrator,
+            t: clock.simulated,
+            x,
+            y,
+            z,
+        });
+    }
+
+    let excess = recording
+        .0
+        .len()
+        .saturating_sub(config.recording_capacity as usize);
+    recording.0.drain(..excess);
+}
+
+#[derive(Resource)]
+struct RecordingPanel {
+    export_path: String,
+    status: Option<Result<String, String>>,
+}
+
+impl Default for RecordingPanel {
+    fn default() -> Self {
+        Self {
+            export_path: "trajectories.csv".into(),
+            status: None,
+        }
+    }
+}
+
+fn recording_ui(
+    mut contexts: EguiContexts,
+    mut recording: ResMut<TrajectoryRecording>,
+    mut panel: ResMut<RecordingPanel>,
+    mut config: ResMut<Configuration>,
+) {
+    let panel = &mut *panel;
+
+    egui::Window::new("Trajectory recording")
+        .default_open(false)
+        .show(contexts.ctx_mut(), |ui| {
+            let mut recording_enabled = config.record_trajectories;
+            if ui.checkbox(&mut recording_enabled, "Record").changed() {
+                config.record_trajectories = recording_enabled;
+            }
+
+            ui.horizontal(|ui| {
+                ui.label(format!(
+                    "{} / {} samples",
+                    recording.0.len(),
+                    config.recording_capacity
+                ));
+                if ui.button("Clear").clicked() {
+                    recording.0.clear();
+                }
+            });
+
+            ui.label("Export (.csv or .json)");
+            ui.horizontal(|ui| {
+                file_dialog::path_field(
+                    ui,
+                    &mut panel.export_path,
+                    DialogKind::Save,
+                    file_dialog::TRAJECTORIES,
+                );
+                if ui
+                    .add_enabled(!recording.0.is_empty(), egui::Button::new("Export"))
+                    .clicked()
+                {
+                    let path = Path::new(&panel.export_path);
+                    panel.status = Some(
+                        recording
+                            .export(path)
+                            .map(|()| format!("Exported {}", path.display())),
+                    );
+                }
+            });
+
+            match panel.status.as_ref() {
+                Some(Ok(message)) => {
+                    ui.label(message);
+                }
+                Some(Err(err)) => {
+                    ui.colored_label(egui::Color32::RED, err);
+                }
+                None => {}
+            }
+        });
+}
@@ -0,0 +1,133 @@
+use std::collections::VecDeque;
+
+use bevy::{math::DVec3, prelude::*};
+use bevy_egui::egui;
+
+use crate::{
+    coloring::SegmentHistory, network, playback::SimulationState, precision::DoublePrecision,
+    tube::TubeTrail, update_position, Configuration, TimeOfBirth, TrailHead,
+};
+
+pub struct RewindPlugin;
+
+impl Plugin for RewindPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RewindHistory>().add_systems(
+            FixedUpdate,
+            record_frame
+                .after(update_position)
+                .run_if(|config: Res<Configuration>| config.rewind_ticks > 0)
+                .run_if(network::simulation_is_local),
+        );
+    }
+}
+
+/// The state of every head after each of the last `rewind_ticks` physics ticks.
+#[derive(Resource, Default)]
+pub struct RewindHistory {
+    frames: VecDeque<RewindFrame>,
+    /// The frame shown while scrubbing. Frames after it are dropped once the simulation
+    /// continues, and the heads replay from there.
+    cursor: Option<usize>,
+}
+
+struct RewindFrame {
+    /// Virtual time of the tick, to tell which trail segments already existed.
+    time: f32,
+    heads: Vec<(Entity, Vec3, Option<DVec3>)>,
+}
+
+fn record_frame(
+    heads: Query<(Entity, &Transform, Option<&DoublePrecision>), With<TrailHead>>,
+    mut history: ResMut<RewindHistory>,
+    time: Res<Time<Virtual>>,
+    config: Res<Configuration>,
+) {
+    if let Some(cursor) = history.cursor.take() {
+        history.frames.truncate(cursor + 1);
+    }
+
+    history.frames.push_back(RewindFrame {
+        time: time.elapsed_secs(),
+        heads: heads
+            .iter()
+            .map(|(entity, transform, double_precision)| {
+                (
+                    entity,
+                    transform.translation,
+                    double_precision.map(|state| **state),
+                )
+            })
+            .collect(),
+    });
+    let excess = history
+        .frames
+        .len()
+        .saturating_sub(config.rewind_ticks as usize);
+    history.frames.drain(..excess);
+}
+
+/// Moves the heads back to the frame at `index` and removes the parts of the trails that
+/// were left after it.
+fn show_frame(world: &mut World, index: usize) {
+    let mut history = world.resource_mut::<RewindHistory>();
+    history.cursor = Some(index);
+    let frame = &history.frames[index];
+    let (time, heads) = (frame.time, frame.heads.clone());
+
+    for (entity, position, double_precision) in heads {
+        // Heads retired by the emitter since then stay gone.
+        let Ok(mut head) = world.get_entity_mut(entity) else {
+            continue;
+        };
+        if let Some(mut transform) = head.get_mut::<Transform>() {
+            transform.translation = position;
+        }
+        if let (Some(mut state), Some(double_precision)) =
+            (head.get_mut::<DoublePrecision>(), double_precision)
+        {
+            **state = double_precision;
+        }
+        // The recorded directions belong to the future now.
+        head.insert(SegmentHistory::default());
+    }
+
+    let later_segments: Vec<Entity> = world
+        .query::<(Entity, &TimeOfBirth)>()
+        .iter(world)
+        .filter(|(_, time_of_birth)| ***time_of_birth > time)
+        .map(|(entity, _)| entity)
+        .collect();
+    for segment in later_segments {
+        world.despawn(segment);
+    }
+    for mut tube in world.query::<&mut TubeTrail>().iter_mut(world) {
+        tube.truncate_after(time);
+    }
+}
+
+/// Timeline slider over the recorded ticks, only movable while paused and only back from the
+/// shown tick.
+pub fn rewind_ui(world: &mut World, ui: &mut egui::Ui) {
+    let history = world.resource::<RewindHistory>();
+    let Some(last) = history.frames.len().checked_sub(1) else {
+        return;
+    };
+    let latest = history.frames[last].time;
+    let times: Vec<f32> = history.frames.iter().map(|frame| frame.time).collect();
+    let mut index = history.cursor.unwrap_or(last);
+    // The trails after the shown frame are gone, so only resuming the simulation moves forward
+    // again.
+    let newest_shown = index;
+    let paused = *world.resource::<SimulationState>() == SimulationState::Paused;
+
+    let response = ui.add_enabled(
+        paused,
+        egui::Slider::new(&mut index, 0..=newest_shown)
+            .text("Timeline")
+            .custom_formatter(|index, _| format!("{:+.2} s", times[index as usize] - latest)),
+    );
+    if response.changed() {
+        show_frame(world, index);
+    }
+}
@@ -0,0 +1,123 @@
+use std::fs;
+
+use bevy::{
+    prelude::*,
+    render::view::screenshot::{save_to_disk, Screenshot},
+};
+use bevy_egui::egui;
+
+use crate::file_dialog::{self, DialogKind};
+
+pub struct CapturePlugin;
+
+impl Plugin for CapturePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<FrameRecording>().add_systems(
+            Last,
+            capture_frame.run_if(|recording: Res<FrameRecording>| recording.active),
+        );
+    }
+}
+
+/// Captures the primary window to `path`, or to a PNG named after the elapsed time, and
+/// returns the path. The file is written a few frames later, once the capture is read back.
+pub fn take_screenshot(world: &mut World, path: Option<String>) -> String {
+    let path = path.unwrap_or_else(|| {
+        let elapsed = world.resource::<Time<Real>>().elapsed_secs();
+        format!("screenshot-{elapsed:.0}.png")
+    });
+    world
+        .spawn(Screenshot::primary_window())
+        .observe(save_to_disk(path.clone()));
+    path
+}
+
+/// Saves every rendered frame as a numbered PNG, to be assembled into a video with e.g.
+/// `ffmpeg -i frame-%05d.png`.
+#[derive(Resource)]
+pub struct FrameRecording {
+    active: bool,
+    directory: String,
+    next_frame: u32,
+    status: Option<Result<String, String>>,
+}
+
+impl Default for FrameRecording {
+    fn default() -> Self {
+        Self {
+            active: false,
+            directory: "frames".into(),
+            next_frame: 0,
+            status: None,
+        }
+    }
+}
+
+impl FrameRecording {
+    /// Starts numbering from zero again, creating the directory if needed.
+    pub fn start(&mut self) {
+        self.status = Some(
+            fs::create_dir_all(&self.directory)
+                .map(|()| format!("Recording to {}", self.directory))
+                .map_err(|err| format!("{}: {err}", self.directory)),
+        );
+        self.active = self.status.as_ref().is_some_and(Result::is_ok);
+        self.next_frame = 0;
+    }
+
+    pub fn stop(&mut self) {
+        self.active = false;
+        self.status = Some(Ok(format!(
+            "Saved {} frames to {}",
+            self.next_frame, self.directory
+        )));
+    }
+
+    pub fn toggle(&mut self) {
+        if self.active {
+            self.stop();
+        } else {
+            self.start();
+        }
+    }
+}
+
+fn capture_frame(mut commands: Commands, mut recording: ResMut<FrameRecording>) {
+    let path = format!(
+        "{}/frame-{:05}.png",
+        recording.directory, recording.next_frame
+    );
+    commands
+        .spawn(Screenshot::primary_window())
+        .observe(save_to_disk(path));
+    recording.next_frame += 1;
+}
+
+pub fn capture_ui(world: &mut World, ui: &mut egui::Ui) {
+    if ui.button("Screenshot").clicked() {
+        take_screenshot(world, None);
+    }
+
+    let mut recording = world.resource_mut::<FrameRecording>();
+    ui.add_enabled_ui(!recording.active, |ui| {
+        ui.label("Frame directory");
+        file_dialog::path_field(ui, &mut recording.directory, DialogKind::Folder, &[]);
+    });
+    let mut active = recording.active;
+    if ui.toggle_value(&mut active, "Record frames").changed() {
+        recording.toggle();
+    }
+    if recording.active {
+        ui.label(format!("{} frames", recording.next_frame));
+    }
+
+    match recording.status.as_ref() {
+        Some(Ok(message)) => {
+            ui.label(message);
+        }
+        Some(Err(err)) => {
+            ui.colored_label(egui::Color32::RED, err);
+        }
+        None => {}
+    }
+}
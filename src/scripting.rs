@@ -0,0 +1,199 @@
+use std::sync::{Arc, Mutex};
+
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+use rhai::{Dynamic, Engine, Scope, AST};
+
+use crate::{
+    clock::{advance_simulation_clock, SimulationClock},
+    gui,
+    playback::SimulationState,
+    screenshot::take_screenshot,
+    update_position, Configuration,
+};
+
+/// Operations a single tick of a script may run before it is stopped, so an endless loop
+/// can't freeze the app.
+const MAX_OPERATIONS: u64 = 100_000;
+const EXAMPLE_SCRIPT: &str = "// Runs every tick. t is the simulated time, sigma, rho and beta
+// can be read and assigned.
+rho = 28.0 + 10.0 * sin(t);
+";
+
+pub struct ScriptingPlugin;
+
+impl Plugin for ScriptingPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Script::new())
+            .add_systems(
+                FixedUpdate,
+                run_script
+                    .after(advance_simulation_clock)
+                    .before(update_position)
+                    .run_if(|script: Res<Script>| script.ast.is_some()),
+            )
+            .add_systems(Update, script_ui);
+    }
+}
+
+/// Something a script asked for, run once the script returns.
+#[derive(Clone, Copy)]
+enum ScriptAction {
+    Clear,
+    /// Adds another set of heads next to the existing ones.
+    SpawnHeads,
+    Restart,
+    Pause,
+    Screenshot,
+}
+
+/// A rhai script that runs every physics tick. Besides the parameters it only reaches the
+/// functions registered in [`Script::new`], rhai itself has no access to files or the
+/// network.
+#[derive(Resource)]
+struct Script {
+    engine: Engine,
+    source: String,
+    /// The compiled script while it runs.
+    ast: Option<AST>,
+    actions: Arc<Mutex<Vec<ScriptAction>>>,
+    error: Option<String>,
+}
+
+impl Script {
+    fn new() -> Self {
+        let actions = Arc::new(Mutex::new(Vec::new()));
+        let mut engine = Engine::new();
+        engine.set_max_operations(MAX_OPERATIONS);
+        for (name, action) in [
+            ("clear", ScriptAction::Clear),
+            ("spawn_heads", ScriptAction::SpawnHeads),
+            ("restart", ScriptAction::Restart),
+            ("pause", ScriptAction::Pause),
+            ("screenshot", ScriptAction::Screenshot),
+        ] {
+            let actions = actions.clone();
+            engine.register_fn(name, move || actions.lock().unwrap().push(action));
+        }
+
+        Self {
+            engine,
+            source: EXAMPLE_SCRIPT.into(),
+            ast: None,
+            actions,
+            error: None,
+        }
+    }
+
+    fn start(&mut self) {
+        self.actions.lock().unwrap().clear();
+        match self.engine.compile(&self.source) {
+            Ok(ast) => {
+                self.ast = Some(ast);
+                self.error = None;
+            }
+            Err(err) => self.error = Some(err.to_string()),
+        }
+    }
+}
+
+/// Runs one tick of the script with the parameters as variables and applies what it assigned.
+fn run_script(world: &mut World) {
+    let t = world.resource::<SimulationClock>().simulated;
+    let config = world.resource::<Configuration>();
+    let parameters = [
+        ("sigma", config.sigma),
+        ("rho", config.rho),
+        ("beta", config.beta),
+    ];
+
+    let mut scope = Scope::new();
+    scope.push_constant("t", t);
+    for (name, value) in parameters {
+        scope.push(name, value as f64);
+    }
+
+    let mut script = world.resource_mut::<Script>();
+    let Some(ast) = script.ast.take() else {
+        return;
+    };
+    if let Err(err) = script.engine.run_ast_with_scope(&mut scope, &ast) {
+        script.error = Some(err.to_string());
+        return;
+    }
+    script.ast = Some(ast);
+    let actions = std::mem::take(&mut *script.actions.lock().unwrap());
+
+    // Scripts may assign integers as well.
+    let assigned = |name: &str| {
+        scope
+            .get(name)
+            .and_then(|value: &Dynamic| {
+                value
+                    .as_float()
+                    .ok()
+                    .or(value.as_int().ok().map(|int| int as f64))
+            })
+            .map(|value| value as f32)
+    };
+    let (sigma, rho, beta) = (assigned("sigma"), assigned("rho"), assigned("beta"));
+    let mut config = world.resource_mut::<Configuration>();
+    for (value, assigned) in [
+        (&mut config.sigma, sigma),
+        (&mut config.rho, rho),
+        (&mut config.beta, beta),
+    ] {
+        if let Some(assigned) = assigned.filter(|assigned| assigned != value) {
+            *value = assigned;
+        }
+    }
+
+    for action in actions {
+        match action {
+            ScriptAction::Clear => gui::clear(world),
+            ScriptAction::SpawnHeads => gui::start(world),
+            ScriptAction::Restart => {
+                gui::clear(world);
+                gui::start(world);
+            }
+            ScriptAction::Pause => {
+                *world.resource_mut::<SimulationState>() = SimulationState::Paused
+            }
+            ScriptAction::Screenshot => {
+                take_screenshot(world, None);
+            }
+        }
+    }
+}
+
+fn script_ui(mut contexts: EguiContexts, mut script: ResMut<Script>) {
+    egui::Window::new("Script")
+        .default_open(false)
+        .show(contexts.ctx_mut(), |ui| {
+            ui.label(
+                "Available functions: clear(), spawn_heads(), restart(), pause() and screenshot(), \
+                 plus rhai's math.",
+            );
+            ui.add_enabled(
+                script.ast.is_none(),
+                egui::TextEdit::multiline(&mut script.source)
+                    .code_editor()
+                    .desired_rows(8)
+                    .desired_width(f32::INFINITY),
+            );
+
+            ui.horizontal(|ui| {
+                if script.ast.is_none() {
+                    if ui.button("Run").clicked() {
+                        script.start();
+                    }
+                } else if ui.button("Stop").clicked() {
+                    script.ast = None;
+                }
+            });
+
+            if let Some(err) = &script.error {
+                ui.colored_label(egui::Color32::RED, err);
+            }
+        });
+}
@@ -0,0 +1,104 @@
+use std::mem::size_of;
+
+use bevy::{
+    diagnostic::{Diagnostic, DiagnosticPath, Diagnostics, DiagnosticsStore, RegisterDiagnostic},
+    prelude::*,
+};
+use bevy_egui::egui;
+
+use crate::{
+    remove_old_trail_segments, Configuration, SegmentOf, SimpleColorMaterial, TimeOfBirth,
+};
+
+pub const SEGMENT_COUNT: DiagnosticPath = DiagnosticPath::const_new("trail_segments");
+/// Approximate memory taken by the segments, in KiB.
+pub const SEGMENT_MEMORY: DiagnosticPath = DiagnosticPath::const_new("trail_segment_memory");
+
+/// Components of a segment in the main world plus, roughly, its mesh uniform and batching data
+/// in the render world.
+const BYTES_PER_SEGMENT: usize = size_of::<(
+    Transform,
+    GlobalTransform,
+    Mesh3d,
+    MeshMaterial3d<SimpleColorMaterial>,
+    TimeOfBirth,
+    SegmentOf,
+    Visibility,
+    InheritedVisibility,
+    ViewVisibility,
+)>() + 160;
+
+pub struct SegmentBudgetPlugin;
+
+impl Plugin for SegmentBudgetPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_diagnostic(Diagnostic::new(SEGMENT_COUNT))
+            .register_diagnostic(Diagnostic::new(SEGMENT_MEMORY).with_suffix(" KiB"))
+            .add_systems(
+                Update,
+                (
+                    evict_excess_segments
+                        .after(remove_old_trail_segments)
+                        .run_if(|config: Res<Configuration>| config.max_segments > 0),
+                    measure_segments,
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// Despawns the oldest segments beyond `max_segments`, however long their lifetime.
+fn evict_excess_segments(
+    segments: Query<(Entity, &TimeOfBirth)>,
+    mut commands: Commands,
+    config: Res<Configuration>,
+) {
+    let excess = segments
+        .iter()
+        .len()
+        .saturating_sub(config.max_segments as usize);
+    if excess == 0 {
+        return;
+    }
+
+    let mut by_age: Vec<(f32, Entity)> = segments
+        .iter()
+        .map(|(entity, time_of_birth)| (**time_of_birth, entity))
+        .collect();
+    by_age.select_nth_unstable_by(excess - 1, |a, b| a.0.total_cmp(&b.0));
+    for &(_, entity) in &by_age[..excess] {
+        commands.entity(entity).despawn();
+    }
+}
+
+fn measure_segments(segments: Query<(), With<TimeOfBirth>>, mut diagnostics: Diagnostics) {
+    let count = segments.iter().len();
+    diagnostics.add_measurement(&SEGMENT_COUNT, || count as f64);
+    diagnostics.add_measurement(&SEGMENT_MEMORY, || {
+        (count * BYTES_PER_SEGMENT) as f64 / 1024.
+    });
+}
+
+/// Segment count against the budget, shown with the other diagnostics.
+pub fn segment_budget_ui(world: &mut World, ui: &mut egui::Ui) {
+    let config = world.resource::<Configuration>();
+    if !config.show_diagnostics {
+        return;
+    }
+    let store = world.resource::<DiagnosticsStore>();
+    let latest = |path| {
+        store
+            .get(path)
+            .and_then(Diagnostic::value)
+            .unwrap_or_default()
+    };
+    let budget = match config.max_segments {
+        0 => "unlimited".to_string(),
+        max => max.to_string(),
+    };
+    ui.label(format!(
+        "Trail segments: {:.0} of {budget}, about {:.1} MiB",
+        latest(&SEGMENT_COUNT),
+        latest(&SEGMENT_MEMORY) / 1024.
+    ));
+}
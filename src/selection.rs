@@ -0,0 +1,210 @@
+use bevy::prelude::*;
+use bevy_egui::egui;
+
+use crate::{
+    camera_follow::{self, CameraFollowed},
+    coloring::TrailColoring,
+    proximity::Flash,
+    tube::TubeTrail,
+    Configuration, HeadIndex, SegmentOf, SimpleColorMaterial, TimeOfBirth, TrailData, TrailHead,
+};
+
+/// Brightening applied to the selected head and its trail.
+const HIGHLIGHT: f32 = 0.4;
+/// Darkening applied to all other heads and trails while something is selected.
+const DIM: f32 = -0.75;
+
+pub struct SelectionPlugin;
+
+impl Plugin for SelectionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Selection>().add_systems(
+            Update,
+            (
+                (clear_stale_selection, apply_selection_emphasis).chain(),
+                apply_trail_visibility,
+            ),
+        );
+    }
+}
+
+/// The currently selected trail head, if any.
+#[derive(Resource, Default)]
+pub struct Selection(pub Option<Entity>);
+
+fn clear_stale_selection(mut selection: ResMut<Selection>, heads: Query<(), With<TrailHead>>) {
+    if selection.0.is_some_and(|head| !heads.contains(head)) {
+        selection.0 = None;
+    }
+}
+
+/// Hides a head together with every segment it left.
+#[derive(Component)]
+pub struct TrailHidden;
+
+/// Stops a head where it is, it neither moves nor leaves segments until unfrozen.
+#[derive(Component)]
+pub struct Frozen;
+
+/// Expires every segment `head` left, the rest of the scene stays.
+pub fn clear_trail(world: &mut World, head: Entity) {
+    for (owner, mut time_of_birth) in world
+        .query::<(&SegmentOf, &mut TimeOfBirth)>()
+        .iter_mut(world)
+    {
+        if owner.0 == head {
+            // Picked up by `remove_old_trail_segments` like any expired segment.
+            **time_of_birth = 0.;
+        }
+    }
+    for mut tube in world.query::<&mut TubeTrail>().iter_mut(world) {
+        if tube.head() == head {
+            tube.clear();
+        }
+    }
+}
+
+/// Shows or hides the segments of heads whose [`TrailHidden`] marker changed, and hides new
+/// segments of hidden heads right away.
+pub fn apply_trail_visibility(
+    mut heads: Query<(&mut Visibility, Has<TrailHidden>), With<TrailHead>>,
+    mut segments: Query<(Ref<SegmentOf>, &mut Visibility), Without<TrailHead>>,
+    hidden_now: Query<Entity, Added<TrailHidden>>,
+    mut shown_now: RemovedComponents<TrailHidden>,
+) {
+    let toggled: Vec<Entity> = hidden_now.iter().chain(shown_now.read()).collect();
+    let visibility = |hidden: bool| {
+        if hidden {
+            Visibility::Hidden
+        } else {
+            Visibility::Inherited
+        }
+    };
+
+    if toggled.is_empty() && !heads.iter().any(|(_, hidden)| hidden) {
+        return;
+    }
+
+    for &head in &toggled {
+        if let Ok((mut head_visibility, hidden)) = heads.get_mut(head) {
+            *head_visibility = visibility(hidden);
+        }
+    }
+
+    for (owner, mut segment_visibility) in &mut segments {
+        if !owner.is_added() && !toggled.contains(&owner.0) {
+            continue;
+        }
+        let hidden = heads.get(owner.0).is_ok_and(|(_, hidden)| hidden);
+        segment_visibility.set_if_neq(visibility(hidden));
+    }
+}
+
+/// Keeps the emphasis uniform of every head and trail material in sync with the selection.
+/// Flashing heads are brightened on top of that. Segments colored from the shared
+/// [`TrailPalette`](crate::coloring::TrailPalette) belong to no single head and stay as they are.
+fn apply_selection_emphasis(
+    heads: Query<(
+        Entity,
+        &MeshMaterial3d<SimpleColorMaterial>,
+        &TrailData,
+        Option<&Flash>,
+    )>,
+    selection: Res<Selection>,
+    mut simple_color_materials: ResMut<Assets<SimpleColorMaterial>>,
+) {
+    for (head, head_material, trail_data, flash) in &heads {
+        let emphasis = match selection.0 {
+            None => 0.,
+            Some(selected) if selected == head => HIGHLIGHT,
+            Some(_) => DIM,
+        };
+        let emphasis = Vec4::new(
+            emphasis.max(flash.map_or(0., Flash::brightness)),
+            0.,
+            0.,
+            0.,
+        );
+
+        for id in [head_material.id(), trail_data.material.id()] {
+            if simple_color_materials
+                .get(id)
+                .is_some_and(|material| material.emphasis != emphasis)
+            {
+                if let Some(material) = simple_color_materials.get_mut(id) {
+                    material.emphasis = emphasis;
+                }
+            }
+        }
+    }
+}
+
+/// Combo box for choosing the selected head.
+pub fn selection_ui(world: &mut World, ui: &mut egui::Ui) {
+    let mut heads: Vec<(Entity, u16)> = world
+        .query_filtered::<(Entity, &HeadIndex), With<TrailHead>>()
+        .iter(world)
+        .map(|(entity, index)| (entity, index.0))
+        .collect();
+    heads.sort_by_key(|&(entity, index)| (index, entity));
+
+    let label = |(entity, index): (Entity, u16)| format!("Head {index} ({entity})");
+    let mut selection = world.resource_mut::<Selection>();
+    let selected_text = selection
+        .0
+        .and_then(|selected| heads.iter().find(|&&(entity, _)| entity == selected))
+        .map_or("None".to_string(), |&head| label(head));
+
+    egui::ComboBox::from_label("Selected head")
+        .selected_text(selected_text)
+        .show_ui(ui, |ui| {
+            ui.selectable_value(&mut selection.0, None, "None");
+            for head in heads {
+                ui.selectable_value(&mut selection.0, Some(head.0), label(head));
+            }
+        });
+
+    let Some(selected) = selection.0 else {
+        return;
+    };
+    // The emitter, an auto-clear, a script or the network can despawn the head before
+    // `clear_stale_selection` notices.
+    if world.get_entity(selected).is_err() {
+        world.resource_mut::<Selection>().0 = None;
+        return;
+    }
+
+    if world.resource::<Configuration>().trail_coloring != TrailColoring::PerHead {
+        ui.label("Gradient-colored trails are shared by all heads and aren't emphasized");
+    }
+
+    let mut hidden = world.get::<TrailHidden>(selected).is_some();
+    if ui.checkbox(&mut hidden, "Hide trail").changed() {
+        if let Ok(mut head) = world.get_entity_mut(selected) {
+            if hidden {
+                head.insert(TrailHidden);
+            } else {
+                head.remove::<TrailHidden>();
+            }
+        }
+    }
+
+    let mut frozen = world.get::<Frozen>(selected).is_some();
+    if ui.checkbox(&mut frozen, "Freeze").changed() {
+        if let Ok(mut head) = world.get_entity_mut(selected) {
+            if frozen {
+                head.insert(Frozen);
+            } else {
+                head.remove::<Frozen>();
+            }
+        }
+    }
+    if ui.button("Clear trail").clicked() {
+        clear_trail(world, selected);
+    }
+
+    let mut followed = world.get::<CameraFollowed>(selected).is_some();
+    if ui.checkbox(&mut followed, "Follow with camera").changed() {
+        camera_follow::follow(world, followed.then_some(selected));
+    }
+}
@@ -0,0 +1,68 @@
+use std::fmt;
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use ron::ser::PrettyConfig;
+
+use crate::Configuration;
+
+/// Encodes the full configuration into a compact, URL-safe base64 string that can be pasted
+/// into a chat and decoded again with [`decode`].
+pub fn encode(config: &Configuration) -> String {
+    let bytes = bincode::serialize(config).expect("Configuration is always serializable");
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+pub fn decode(code: &str) -> Result<Configuration, ImportError> {
+    let bytes = URL_SAFE_NO_PAD
+        .decode(code.trim())
+        .map_err(ImportError::Base64)?;
+    bincode::deserialize(&bytes).map_err(ImportError::Bincode)
+}
+
+pub fn to_ron(config: &Configuration) -> String {
+    ron::ser::to_string_pretty(config, PrettyConfig::default())
+        .expect("Configuration is always serializable")
+}
+
+pub fn to_json(config: &Configuration) -> String {
+    serde_json::to_string_pretty(config).expect("Configuration is always serializable")
+}
+
+/// Parses a configuration given as JSON, RON or share code and validates it.
+///
+/// The format is guessed from the text itself: JSON objects start with `{`, RON structs
+/// contain a `(`, and everything else is treated as a share code.
+pub fn import(text: &str) -> Result<Configuration, ImportError> {
+    let text = text.trim();
+    let config = if text.starts_with('{') {
+        serde_json::from_str(text).map_err(ImportError::Json)?
+    } else if text.contains('(') {
+        ron::from_str(text).map_err(ImportError::Ron)?
+    } else {
+        decode(text)?
+    };
+
+    config.validate().map_err(ImportError::Invalid)?;
+    Ok(config)
+}
+
+#[derive(Debug)]
+pub enum ImportError {
+    Base64(base64::DecodeError),
+    Bincode(bincode::Error),
+    Json(serde_json::Error),
+    Ron(ron::error::SpannedError),
+    Invalid(String),
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImportError::Base64(err) => write!(f, "share code is not valid base64: {err}"),
+            ImportError::Bincode(err) => write!(f, "share code is malformed: {err}"),
+            ImportError::Json(err) => write!(f, "invalid JSON: {err}"),
+            ImportError::Ron(err) => write!(f, "invalid RON: {err}"),
+            ImportError::Invalid(reason) => write!(f, "invalid configuration: {reason}"),
+        }
+    }
+}
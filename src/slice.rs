@@ -0,0 +1,80 @@
+use bevy::prelude::*;
+
+use crate::{Configuration, SimpleColorMaterial};
+
+/// Half-width of the band around the plane in which segments are highlighted.
+const HIGHLIGHT_HALF_WIDTH: f32 = 0.25;
+/// Brightness factor applied to geometry beyond the plane when it isn't clipped.
+const DIM_FACTOR: f32 = 0.2;
+const PLANE_SIZE: f32 = 60.;
+
+pub struct SlicePlugin;
+
+impl Plugin for SlicePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, apply_slice_plane).add_systems(
+            Update,
+            draw_slice_plane.run_if(|config: Res<Configuration>| config.slice_enabled),
+        );
+    }
+}
+
+pub fn slice_normal(config: &Configuration) -> Vec3 {
+    let (yaw, pitch) = (
+        config.slice_yaw.to_radians(),
+        config.slice_pitch.to_radians(),
+    );
+    Vec3::new(
+        pitch.cos() * yaw.cos(),
+        pitch.cos() * yaw.sin(),
+        pitch.sin(),
+    )
+}
+
+/// Keeps the slicing uniforms of every material in sync with the configuration. Only
+/// materials that are out of date are touched, so newly spawned heads are picked up without
+/// re-uploading everything each frame.
+fn apply_slice_plane(
+    mut simple_color_materials: ResMut<Assets<SimpleColorMaterial>>,
+    config: Res<Configuration>,
+) {
+    let (slice_plane, slice_settings) = if config.slice_enabled {
+        (
+            slice_normal(&config).extend(config.slice_offset),
+            Vec4::new(
+                HIGHLIGHT_HALF_WIDTH,
+                DIM_FACTOR,
+                if config.slice_clip { 1. } else { 0. },
+                0.,
+            ),
+        )
+    } else {
+        (Vec4::ZERO, Vec4::ZERO)
+    };
+
+    let outdated: Vec<_> = simple_color_materials
+        .iter()
+        .filter(|(_, material)| {
+            material.slice_plane != slice_plane || material.slice_settings != slice_settings
+        })
+        .map(|(id, _)| id)
+        .collect();
+
+    for id in outdated {
+        if let Some(material) = simple_color_materials.get_mut(id) {
+            material.slice_plane = slice_plane;
+            material.slice_settings = slice_settings;
+        }
+    }
+}
+
+fn draw_slice_plane(mut gizmos: Gizmos, config: Res<Configuration>) {
+    let normal = slice_normal(&config);
+    let rotation = Quat::from_rotation_arc(Vec3::Z, normal);
+
+    gizmos.rect(
+        Isometry3d::new(normal * config.slice_offset, rotation),
+        Vec2::splat(PLANE_SIZE),
+        LinearRgba::new(1., 1., 1., 0.3),
+    );
+}
@@ -0,0 +1,372 @@
+use std::{fs, path::Path};
+
+use bevy::{math::DVec3, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    clock::SimulationClock,
+    coloring::TrailPalette,
+    emitter::Emitted,
+    instances::{instance_positions, AttractorInstance, HeadGroup},
+    integrator::Integrator,
+    precision::DoublePrecision,
+    tube::{tube_bundle, TubePoint, TubeTrail},
+    Configuration, HeadIndex, SegmentOf, SimpleColorMaterial, TimeOfBirth, TrailData, TrailHead,
+};
+
+/// Complete runtime state of the simulation.
+#[derive(Serialize, Deserialize)]
+pub struct Snapshot {
+    pub config: Configuration,
+    /// Virtual time at which the snapshot was taken, in seconds.
+    pub elapsed: f32,
+    /// Integrated time and physics ticks of the [`SimulationClock`], zero without one.
+    simulated: f64,
+    ticks: u64,
+    heads: Vec<HeadState>,
+    segments: Vec<SegmentState>,
+    tubes: Vec<TubeState>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct HeadState {
+    group: HeadGroup,
+    index: u16,
+    integrator: Integrator,
+    position: Vec3,
+    double_precision: Option<DVec3>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SegmentState {
+    translation: Vec3,
+    rotation: Quat,
+    scale: Vec3,
+    /// Seconds between the segment's birth and the snapshot.
+    age: f32,
+    material: SegmentMaterial,
+    /// Position of the head that left the segment in [`Snapshot::heads`].
+    owner: Option<usize>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct TubeState {
+    /// Position of the head whose trail material the tube is drawn in, in [`Snapshot::heads`].
+    material: usize,
+    /// Position of the head the tube follows in [`Snapshot::heads`], `None` once it has
+    /// been left to fade.
+    owner: Option<usize>,
+    points: Vec<TubePointState>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct TubePointState {
+    position: Vec3,
+    /// Seconds between the head passing the point and the snapshot.
+    age: f32,
+    normal: Vec3,
+}
+
+#[derive(Serialize, Deserialize)]
+enum SegmentMaterial {
+    /// Trail material of the head at this position in [`Snapshot::heads`].
+    Head(usize),
+    /// Entry of the shared [`TrailPalette`].
+    Palette(usize),
+}
+
+impl HeadState {
+    fn matches(
+        &self,
+        group: HeadGroup,
+        index: &HeadIndex,
+        integrator: &Integrator,
+        f64: bool,
+    ) -> bool {
+        self.group == group
+            && self.index == index.0
+            && self.integrator == *integrator
+            && self.double_precision.is_some() == f64
+    }
+}
+
+type HeadQuery<'a> = (
+    Entity,
+    &'a HeadIndex,
+    &'a Integrator,
+    &'a Transform,
+    Option<&'a DoublePrecision>,
+    &'a TrailData,
+    Option<&'a Parent>,
+    Has<Emitted>,
+);
+
+type HeadQueryMut<'a> = (
+    Entity,
+    &'a HeadIndex,
+    &'a Integrator,
+    &'a mut Transform,
+    Option<&'a mut DoublePrecision>,
+    &'a TrailData,
+    Option<&'a Parent>,
+    Has<Emitted>,
+);
+
+/// Captures heads, trail segments and tubes, elapsed time and configuration.
+pub fn capture(world: &mut World) -> Snapshot {
+    let elapsed = world.resource::<Time<Virtual>>().elapsed_secs();
+    let instances = instance_positions(
+        world
+            .query_filtered::<Entity, With<AttractorInstance>>()
+            .iter(world),
+    );
+
+    let mut heads_query = world.query_filtered::<HeadQuery, With<TrailHead>>();
+    let mut heads: Vec<_> = heads_query
+        .iter(world)
+        .map(
+            |(
+                entity,
+                index,
+                integrator,
+                transform,
+                double_precision,
+                trail_data,
+                parent,
+                emitted,
+            )| {
+                let group = HeadGroup::of(&instances, parent, emitted);
+                (
+                    entity,
+                    group,
+                    index,
+                    integrator,
+                    transform,
+                    double_precision,
+                    trail_data,
+                )
+            },
+        )
+        .collect();
+    heads.sort_by_key(|(_, group, index, integrator, _, double_precision, _)| {
+        (
+            *group,
+            index.0,
+            **integrator as u8,
+            double_precision.is_some(),
+        )
+    });
+    let head_entities: Vec<_> = heads.iter().map(|(entity, ..)| *entity).collect();
+    let trail_materials: Vec<_> = heads
+        .iter()
+        .map(|(.., trail_data)| trail_data.material.id())
+        .collect();
+    let head_states = heads
+        .into_iter()
+        .map(
+            |(_, group, index, integrator, transform, double_precision, _)| HeadState {
+                group,
+                index: index.0,
+                integrator: *integrator,
+                position: transform.translation,
+                double_precision: double_precision.map(|state| **state),
+            },
+        )
+        .collect();
+
+    let palette = world.resource::<TrailPalette>();
+    let palette_materials: Vec<_> = palette.iter().map(Handle::id).collect();
+    let segments = world
+        .query::<(
+            &Transform,
+            &TimeOfBirth,
+            &MeshMaterial3d<SimpleColorMaterial>,
+            Option<&SegmentOf>,
+        )>()
+        .iter(world)
+        // Segments with a time of birth of 0 are expired and about to be despawned.
+        .filter(|(_, time_of_birth, ..)| ***time_of_birth != 0.)
+        .filter_map(|(transform, time_of_birth, material, owner)| {
+            let material =
+                if let Some(head) = trail_materials.iter().position(|id| *id == material.id()) {
+                    SegmentMaterial::Head(head)
+                } else {
+                    SegmentMaterial::Palette(
+                        palette_materials
+                            .iter()
+                            .position(|id| *id == material.id())?,
+                    )
+                };
+            Some(SegmentState {
+                translation: transform.translation,
+                rotation: transform.rotation,
+                scale: transform.scale,
+                age: elapsed - **time_of_birth,
+                material,
+                owner: owner
+                    .and_then(|owner| head_entities.iter().position(|&head| head == owner.0)),
+            })
+        })
+        .collect();
+
+    let tubes = world
+        .query::<(&TubeTrail, &MeshMaterial3d<SimpleColorMaterial>)>()
+        .iter(world)
+        .filter_map(|(tube, material)| {
+            Some(TubeState {
+                material: trail_materials.iter().position(|id| *id == material.id())?,
+                owner: head_entities.iter().position(|&head| head == tube.head()),
+                points: tube
+                    .points()
+                    .map(|point| TubePointState {
+                        position: point.position,
+                        age: elapsed - point.born,
+                        normal: point.normal,
+                    })
+                    .collect(),
+            })
+        })
+        .collect();
+
+    let (simulated, ticks) = world
+        .get_resource::<SimulationClock>()
+        .map_or((0., 0), |clock| (clock.simulated, clock.ticks));
+
+    Snapshot {
+        config: world.resource::<Configuration>().clone(),
+        elapsed,
+        simulated,
+        ticks,
+        heads: head_states,
+        segments,
+        tubes,
+    }
+}
+
+/// Moves freshly spawned heads to their saved state and respawns the saved trail segments and
+/// tubes. Expects the heads for `snapshot.config` to already exist.
+pub fn restore(world: &mut World, snapshot: &Snapshot) {
+    let now = world.resource::<Time<Virtual>>().elapsed_secs();
+    if let Some(mut clock) = world.get_resource_mut::<SimulationClock>() {
+        clock.reset_to(snapshot.simulated, snapshot.ticks);
+    }
+
+    let instances = instance_positions(
+        world
+            .query_filtered::<Entity, With<AttractorInstance>>()
+            .iter(world),
+    );
+    let mut heads_query = world.query_filtered::<HeadQueryMut, With<TrailHead>>();
+    let mut trail_data = vec![None; snapshot.heads.len()];
+    for (entity, index, integrator, mut transform, double_precision, data, parent, emitted) in
+        heads_query.iter_mut(world)
+    {
+        let group = HeadGroup::of(&instances, parent, emitted);
+        let Some(slot) = snapshot
+            .heads
+            .iter()
+            .position(|head| head.matches(group, index, integrator, double_precision.is_some()))
+        else {
+            continue;
+        };
+        let saved = &snapshot.heads[slot];
+        transform.translation = saved.position;
+        if let (Some(mut state), Some(saved_state)) = (double_precision, saved.double_precision) {
+            **state = saved_state;
+        }
+        trail_data[slot] = Some((entity, data.mesh.clone(), data.material.clone()));
+    }
+
+    let tubes: Vec<_> = snapshot
+        .tubes
+        .iter()
+        .filter_map(|tube| {
+            let material = trail_data.get(tube.material)?.as_ref()?.2.clone();
+            let head = tube
+                .owner
+                .and_then(|owner| trail_data.get(owner)?.as_ref())
+                .map_or(Entity::PLACEHOLDER, |(head, ..)| *head);
+            let points = tube.points.iter().map(|point| TubePoint {
+                position: point.position,
+                born: now - point.age,
+                normal: point.normal,
+            });
+            Some((TubeTrail::new(head, points), material))
+        })
+        .collect();
+    for (tube, material) in tubes {
+        let bundle = tube_bundle(tube, &mut world.resource_mut::<Assets<Mesh>>(), material);
+        world.spawn(bundle);
+    }
+
+    let Some(mesh) = trail_data
+        .iter()
+        .flatten()
+        .map(|(_, mesh, _)| mesh.clone())
+        .next()
+    else {
+        return;
+    };
+    let palette: Vec<_> = world.resource::<TrailPalette>().iter().cloned().collect();
+
+    let segments: Vec<_> = snapshot
+        .segments
+        .iter()
+        .filter_map(|segment| {
+            let material = match segment.material {
+                SegmentMaterial::Head(head) => trail_data.get(head)?.as_ref()?.2.clone(),
+                SegmentMaterial::Palette(entry) => palette.get(entry)?.clone(),
+            };
+            let owner = segment
+                .owner
+                .and_then(|owner| trail_data.get(owner)?.as_ref())
+                .map(|(head, ..)| SegmentOf(*head));
+            Some((
+                (
+                    Mesh3d(mesh.clone()),
+                    MeshMaterial3d(material),
+                    Transform {
+                        translation: segment.translation,
+                        rotation: segment.rotation,
+                        scale: segment.scale,
+                    },
+                    TimeOfBirth(now - segment.age),
+                ),
+                owner,
+            ))
+        })
+        .collect();
+    for (segment, owner) in segments {
+        let mut segment = world.spawn(segment);
+        if let Some(owner) = owner {
+            segment.insert(owner);
+        }
+    }
+}
+
+/// Writes the snapshot as RON if the path ends in `.ron`, and as compact binary otherwise.
+pub fn save(snapshot: &Snapshot, path: &Path) -> Result<(), String> {
+    let bytes = if is_ron(path) {
+        ron::to_string(snapshot)
+            .map_err(|err| err.to_string())?
+            .into_bytes()
+    } else {
+        bincode::serialize(snapshot).map_err(|err| err.to_string())?
+    };
+    fs::write(path, bytes).map_err(|err| format!("{}: {err}", path.display()))
+}
+
+pub fn load(path: &Path) -> Result<Snapshot, String> {
+    let bytes = fs::read(path).map_err(|err| format!("{}: {err}", path.display()))?;
+    let snapshot: Snapshot = if is_ron(path) {
+        ron::de::from_bytes(&bytes).map_err(|err| err.to_string())?
+    } else {
+        bincode::deserialize(&bytes).map_err(|err| err.to_string())?
+    };
+    snapshot.config.validate()?;
+    Ok(snapshot)
+}
+
+fn is_ron(path: &Path) -> bool {
+    path.extension().is_some_and(|extension| extension == "ron")
+}
@@ -0,0 +1,179 @@
+use std::{
+    f32::consts::{FRAC_PI_4, TAU},
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use bevy::{
+    audio::{AddAudioSource, Decodable, Source},
+    prelude::*,
+};
+
+use crate::{selection::Selection, Configuration, HeadIndex, TrailHead};
+
+const SAMPLE_RATE: u32 = 44_100;
+/// Fraction of the way to a new pitch, gain or pan covered per sample. Smooths the jumps
+/// between frames, which would otherwise click.
+const SMOOTHING: f32 = 0.002;
+
+pub struct SonificationPlugin;
+
+impl Plugin for SonificationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_audio_source::<Tone>()
+            .add_systems(Update, (toggle_tone, follow_head).chain());
+    }
+}
+
+/// Target pitch in Hz, gain and pan from -1 (left) to 1 (right), shared with the audio
+/// thread as `f32` bits.
+#[derive(Default)]
+struct ToneState {
+    frequency: AtomicU32,
+    gain: AtomicU32,
+    pan: AtomicU32,
+}
+
+impl ToneState {
+    fn set(&self, frequency: f32, gain: f32, pan: f32) {
+        self.frequency.store(frequency.to_bits(), Ordering::Relaxed);
+        self.gain.store(gain.to_bits(), Ordering::Relaxed);
+        self.pan.store(pan.to_bits(), Ordering::Relaxed);
+    }
+
+    fn get(&self) -> [f32; 3] {
+        [&self.frequency, &self.gain, &self.pan]
+            .map(|value| f32::from_bits(value.load(Ordering::Relaxed)))
+    }
+}
+
+/// A sine tone that follows the values of its [`ToneState`] while it plays.
+#[derive(Asset, TypePath)]
+struct Tone(Arc<ToneState>);
+
+impl Decodable for Tone {
+    type DecoderItem = f32;
+    type Decoder = ToneDecoder;
+
+    fn decoder(&self) -> Self::Decoder {
+        ToneDecoder {
+            state: self.0.clone(),
+            current: [0.; 3],
+            phase: 0.,
+            right: None,
+        }
+    }
+}
+
+struct ToneDecoder {
+    state: Arc<ToneState>,
+    /// Smoothed frequency, gain and pan.
+    current: [f32; 3],
+    phase: f32,
+    /// The right channel of the current frame, after its left one was returned.
+    right: Option<f32>,
+}
+
+impl Iterator for ToneDecoder {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        if let Some(right) = self.right.take() {
+            return Some(right);
+        }
+
+        for (current, target) in self.current.iter_mut().zip(self.state.get()) {
+            *current += (target - *current) * SMOOTHING;
+        }
+        let [frequency, gain, pan] = self.current;
+        self.phase = (self.phase + frequency / SAMPLE_RATE as f32).fract();
+        let sample = (self.phase * TAU).sin() * gain;
+
+        // Equal-power panning keeps the loudness constant across the stereo field.
+        let angle = (pan.clamp(-1., 1.) + 1.) * FRAC_PI_4;
+        self.right = Some(sample * angle.sin());
+        Some(sample * angle.cos())
+    }
+}
+
+impl Source for ToneDecoder {
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        2
+    }
+
+    fn sample_rate(&self) -> u32 {
+        SAMPLE_RATE
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+}
+
+/// The entity playing the tone, and the state it follows.
+#[derive(Component)]
+struct Sonification(Arc<ToneState>);
+
+fn toggle_tone(
+    mut commands: Commands,
+    players: Query<Entity, With<Sonification>>,
+    mut tones: ResMut<Assets<Tone>>,
+    config: Res<Configuration>,
+) {
+    match (config.sonification, players.get_single()) {
+        (true, Err(_)) => {
+            let state = Arc::new(ToneState::default());
+            commands.spawn((
+                Sonification(state.clone()),
+                AudioPlayer(tones.add(Tone(state))),
+                PlaybackSettings::LOOP,
+            ));
+        }
+        (false, Ok(player)) => commands.entity(player).despawn(),
+        _ => {}
+    }
+}
+
+/// The selected head, or the first one, sets the pitch with its height and the pan with its
+/// x coordinate. The pitch is exponential in the height, so equal steps sound equal.
+fn follow_head(
+    players: Query<&Sonification>,
+    heads: Query<(Entity, &HeadIndex, &Transform), With<TrailHead>>,
+    selection: Res<Selection>,
+    config: Res<Configuration>,
+) {
+    let Ok(Sonification(state)) = players.get_single() else {
+        return;
+    };
+    let head = selection
+        .0
+        .and_then(|selected| heads.get(selected).ok())
+        .or_else(|| {
+            heads
+                .iter()
+                .min_by_key(|(entity, index, _)| (index.0, *entity))
+        });
+    let Some((_, _, transform)) = head else {
+        state.set(config.sonification_low_pitch, 0., 0.);
+        return;
+    };
+    let position = transform.translation;
+
+    let range = config.sonification_z_max - config.sonification_z_min;
+    let height = if range > 0. {
+        (position.z - config.sonification_z_min) / range
+    } else {
+        0.
+    };
+    let octaves = (config.sonification_high_pitch / config.sonification_low_pitch).log2();
+    let frequency = config.sonification_low_pitch * (height.clamp(0., 1.) * octaves).exp2();
+    let pan = position.x / config.sonification_x_extent;
+    state.set(frequency, config.sonification_gain, pan);
+}
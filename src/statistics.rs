@@ -0,0 +1,233 @@
+use std::{collections::VecDeque, fmt::Write as _, fs, path::Path};
+
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+
+use crate::{
+    file_dialog::{self, DialogKind},
+    selection::Selection,
+    update_position, TrailHead,
+};
+
+pub struct StatisticsPlugin;
+
+impl Plugin for StatisticsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SampleHistory>()
+            .init_resource::<StatisticsPanel>()
+            .add_systems(FixedUpdate, record_samples.after(update_position))
+            .add_systems(Update, statistics_ui);
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SampleSource {
+    #[default]
+    SelectedHead,
+    Ensemble,
+}
+
+/// Head positions of the most recent physics ticks, oldest first. Each tick holds one sample
+/// for the selected head or one per head for the ensemble.
+#[derive(Resource)]
+pub struct SampleHistory {
+    pub source: SampleSource,
+    /// Number of ticks kept.
+    pub capacity: usize,
+    pub ticks: VecDeque<Vec<Vec3>>,
+    tracked: Option<Entity>,
+}
+
+impl Default for SampleHistory {
+    fn default() -> Self {
+        Self {
+            source: SampleSource::SelectedHead,
+            capacity: 1200,
+            ticks: VecDeque::new(),
+            tracked: None,
+        }
+    }
+}
+
+fn record_samples(
+    mut history: ResMut<SampleHistory>,
+    heads: Query<&Transform, With<TrailHead>>,
+    selection: Res<Selection>,
+) {
+    let tracked = match history.source {
+        SampleSource::SelectedHead => selection.0,
+        SampleSource::Ensemble => None,
+    };
+    if history.tracked != tracked {
+        history.tracked = tracked;
+        history.ticks.clear();
+    }
+
+    let samples: Vec<Vec3> = match history.source {
+        SampleSource::SelectedHead => tracked
+            .and_then(|head| heads.get(head).ok())
+            .map(|transform| transform.translation)
+            .into_iter()
+            .collect(),
+        SampleSource::Ensemble => heads
+            .iter()
+            .map(|transform| transform.translation)
+            .collect(),
+    };
+    if samples.is_empty() {
+        return;
+    }
+
+    history.ticks.push_back(samples);
+    while history.ticks.len() > history.capacity {
+        history.ticks.pop_front();
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Moments {
+    mean: Vec3,
+    variance: Vec3,
+    min: Vec3,
+    max: Vec3,
+}
+
+impl Moments {
+    fn of<'a>(samples: impl Iterator<Item = &'a Vec3> + Clone) -> Option<Self> {
+        let count = samples.clone().count();
+        if count == 0 {
+            return None;
+        }
+        let mean = samples.clone().sum::<Vec3>() / count as f32;
+        Some(Self {
+            mean,
+            variance: samples
+                .clone()
+                .map(|sample| (*sample - mean).powf(2.))
+                .sum::<Vec3>()
+                / count as f32,
+            min: samples.clone().fold(Vec3::INFINITY, |min, &x| min.min(x)),
+            max: samples.fold(Vec3::NEG_INFINITY, |max, &x| max.max(x)),
+        })
+    }
+
+    fn to_csv(self) -> String {
+        let mut csv = String::from("axis,mean,variance,min,max\n");
+        for (axis, i) in [("x", 0), ("y", 1), ("z", 2)] {
+            writeln!(
+                csv,
+                "{axis},{},{},{},{}",
+                self.mean[i], self.variance[i], self.min[i], self.max[i]
+            )
+            .expect("writing to a String cannot fail");
+        }
+        csv
+    }
+}
+
+#[derive(Resource)]
+struct StatisticsPanel {
+    export_path: String,
+    status: Option<Result<String, String>>,
+}
+
+impl Default for StatisticsPanel {
+    fn default() -> Self {
+        Self {
+            export_path: "statistics.csv".into(),
+            status: None,
+        }
+    }
+}
+
+fn statistics_ui(
+    mut contexts: EguiContexts,
+    mut history: ResMut<SampleHistory>,
+    mut panel: ResMut<StatisticsPanel>,
+    time: Res<Time<Fixed>>,
+) {
+    let panel = &mut *panel;
+
+    egui::Window::new("Statistics")
+        .default_open(false)
+        .show(contexts.ctx_mut(), |ui| {
+            ui.horizontal(|ui| {
+                ui.radio_value(
+                    &mut history.source,
+                    SampleSource::SelectedHead,
+                    "Selected head",
+                );
+                ui.radio_value(&mut history.source, SampleSource::Ensemble, "Ensemble");
+            });
+            ui.add(
+                egui::Slider::new(&mut history.capacity, 10..=10_000)
+                    .logarithmic(true)
+                    .text("window (ticks)"),
+            );
+            ui.label(format!(
+                "{} ticks, {:.1} s",
+                history.ticks.len(),
+                history.ticks.len() as f64 * time.timestep().as_secs_f64()
+            ));
+
+            let Some(moments) = Moments::of(history.ticks.iter().flatten()) else {
+                ui.label(match history.source {
+                    SampleSource::SelectedHead => "Select a head to collect samples.",
+                    SampleSource::Ensemble => "No samples yet.",
+                });
+                return;
+            };
+
+            egui::Grid::new("moments")
+                .num_columns(5)
+                .striped(true)
+                .show(ui, |ui| {
+                    for header in ["", "mean", "variance", "min", "max"] {
+                        ui.strong(header);
+                    }
+                    ui.end_row();
+
+                    for (axis, i) in [("x", 0), ("y", 1), ("z", 2)] {
+                        ui.label(axis);
+                        for value in [
+                            moments.mean[i],
+                            moments.variance[i],
+                            moments.min[i],
+                            moments.max[i],
+                        ] {
+                            ui.label(format!("{value:.3}"));
+                        }
+                        ui.end_row();
+                    }
+                });
+
+            ui.separator();
+
+            ui.horizontal(|ui| {
+                file_dialog::path_field(
+                    ui,
+                    &mut panel.export_path,
+                    DialogKind::Save,
+                    file_dialog::CSV,
+                );
+                if ui.button("Export CSV").clicked() {
+                    let path = Path::new(&panel.export_path);
+                    panel.status = Some(
+                        fs::write(path, moments.to_csv())
+                            .map(|()| format!("Exported {}", path.display()))
+                            .map_err(|err| format!("{}: {err}", path.display())),
+                    );
+                }
+            });
+
+            match panel.status.as_ref() {
+                Some(Ok(message)) => {
+                    ui.label(message);
+                }
+                Some(Err(err)) => {
+                    ui.colored_label(egui::Color32::RED, err);
+                }
+                None => {}
+            }
+        });
+}
@@ -0,0 +1,265 @@
+use bevy::{
+    core_pipeline::{bloom::Bloom, oit::OrderIndependentTransparencySettings},
+    pbr::DistanceFog,
+    prelude::*,
+    render::{
+        camera::{RenderTarget, Viewport},
+        render_resource::{
+            AsBindGroup, Extent3d, ShaderRef, TextureDimension, TextureFormat, TextureUsages,
+        },
+    },
+    window::PrimaryWindow,
+};
+use bevy_panorbit_camera::PanOrbitCamera;
+use serde::{Deserialize, Serialize};
+
+use crate::Configuration;
+
+pub struct StereoPlugin;
+
+impl Plugin for StereoPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(UiMaterialPlugin::<AnaglyphMaterial>::default())
+            .add_systems(
+                PostUpdate,
+                (
+                    build_stereo_rig.run_if(|config: Res<Configuration>| config.is_changed()),
+                    update_eyes,
+                )
+                    .chain()
+                    .before(TransformSystem::TransformPropagate),
+            );
+    }
+}
+
+/// How the scene is split into a view per eye.
+#[derive(Reflect, Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StereoMode {
+    #[default]
+    Off,
+    /// Left eye on the left half of the window, right eye on the right half, for parallel
+    /// viewing or a 3D projector.
+    SideBySide,
+    /// Both eyes combined into one image for red-cyan glasses.
+    Anaglyph,
+}
+
+/// Camera of one eye, a child of the orbit camera. `side` is -1 for the left eye and 1 for the
+/// right one.
+#[derive(Component)]
+struct StereoEye {
+    side: f32,
+}
+
+/// The 2D camera and full-window node that combine the eyes in anaglyph mode.
+#[derive(Component)]
+struct AnaglyphComposite;
+
+#[derive(Asset, TypePath, AsBindGroup, Clone)]
+struct AnaglyphMaterial {
+    #[texture(0)]
+    #[sampler(1)]
+    left: Handle<Image>,
+    #[texture(2)]
+    #[sampler(3)]
+    right: Handle<Image>,
+}
+
+impl UiMaterial for AnaglyphMaterial {
+    fn fragment_shader() -> ShaderRef {
+        "shaders/anaglyph.wgsl".into()
+    }
+}
+
+/// Replaces the orbit camera by a camera per eye while a stereo mode is on. The eyes copy the
+/// orbit camera's glow, fog and transparency settings when they are spawned.
+#[allow(clippy::too_many_arguments)]
+fn build_stereo_rig(
+    mut commands: Commands,
+    mut main_cameras: Query<
+        (
+            Entity,
+            &mut Camera,
+            &Msaa,
+            Option<&Bloom>,
+            Option<&DistanceFog>,
+            Option<&OrderIndependentTransparencySettings>,
+        ),
+        With<PanOrbitCamera>,
+    >,
+    eyes: Query<Entity, With<StereoEye>>,
+    composites: Query<Entity, With<AnaglyphComposite>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mut images: ResMut<Assets<Image>>,
+    mut materials: ResMut<Assets<AnaglyphMaterial>>,
+    config: Res<Configuration>,
+    mut built: Local<StereoMode>,
+) {
+    if *built == config.stereo {
+        return;
+    }
+    *built = config.stereo;
+
+    for entity in eyes.iter().chain(&composites) {
+        commands.entity(entity).despawn_recursive();
+    }
+    let Ok((main, mut main_camera, msaa, bloom, fog, oit)) = main_cameras.get_single_mut() else {
+        return;
+    };
+    main_camera.is_active = config.stereo == StereoMode::Off;
+
+    let targets = match config.stereo {
+        StereoMode::Off => return,
+        StereoMode::SideBySide => [RenderTarget::default(), RenderTarget::default()],
+        StereoMode::Anaglyph => {
+            let size = windows
+                .get_single()
+                .map_or(UVec2::ONE, |window| window.physical_size().max(UVec2::ONE));
+            let left = images.add(eye_image(size));
+            let right = images.add(eye_image(size));
+
+            let camera = commands
+                .spawn((
+                    AnaglyphComposite,
+                    Camera2d,
+                    Camera {
+                        order: 3,
+                        ..default()
+                    },
+                ))
+                .id();
+            commands.spawn((
+                AnaglyphComposite,
+                Node {
+                    width: Val::Percent(100.),
+                    height: Val::Percent(100.),
+                    ..default()
+                },
+                MaterialNode(materials.add(AnaglyphMaterial {
+                    left: left.clone(),
+                    right: right.clone(),
+                })),
+                TargetCamera(camera),
+            ));
+            [left.into(), right.into()]
+        }
+    };
+
+    for ((side, order), target) in [(-1., 1), (1., 2)].into_iter().zip(targets) {
+        // Side by side, clearing also wipes the other half of the window, which the left eye
+        // already cleared.
+        let clear_color = if config.stereo == StereoMode::SideBySide && order == 2 {
+            ClearColorConfig::None
+        } else {
+            main_camera.clear_color.clone()
+        };
+        let mut eye = commands.spawn((
+            StereoEye { side },
+            Camera3d::default(),
+            Camera {
+                order,
+                target,
+                hdr: main_camera.hdr,
+                clear_color,
+                ..default()
+            },
+            *msaa,
+        ));
+        if let Some(bloom) = bloom {
+            eye.insert(bloom.clone());
+        }
+        if let Some(fog) = fog {
+            eye.insert(fog.clone());
+        }
+        if let Some(oit) = oit {
+            eye.insert(oit.clone());
+        }
+        eye.set_parent(main);
+    }
+}
+
+fn eye_image(size: UVec2) -> Image {
+    let mut image = Image::new_fill(
+        Extent3d {
+            width: size.x,
+            height: size.y,
+            ..default()
+        },
+        TextureDimension::D2,
+        &[0; 4],
+        TextureFormat::Bgra8UnormSrgb,
+        default(),
+    );
+    image.texture_descriptor.usage =
+        TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST | TextureUsages::RENDER_ATTACHMENT;
+    image
+}
+
+/// Offsets the eyes by half the eye separation each and turns them in so their views meet at
+/// the convergence distance, where the scene appears at screen depth. Also keeps the eyes'
+/// projection, viewports and anaglyph images in sync with the orbit camera and the window.
+fn update_eyes(
+    mut eyes: Query<(&StereoEye, &mut Transform, &mut Camera, &mut Projection)>,
+    main_cameras: Query<&Projection, (With<PanOrbitCamera>, Without<StereoEye>)>,
+    composites: Query<&MaterialNode<AnaglyphMaterial>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mut images: ResMut<Assets<Image>>,
+    mut materials: ResMut<Assets<AnaglyphMaterial>>,
+    config: Res<Configuration>,
+) {
+    let (Ok(main_projection), Ok(window)) = (main_cameras.get_single(), windows.get_single())
+    else {
+        return;
+    };
+    let window_size = window.physical_size().max(UVec2::ONE);
+
+    for (eye, mut transform, mut camera, mut projection) in &mut eyes {
+        let offset = Vec3::X * eye.side * config.stereo_eye_separation / 2.;
+        *transform = Transform::from_translation(offset)
+            .looking_at(Vec3::NEG_Z * config.stereo_convergence.max(0.01), Vec3::Y);
+        if *projection != *main_projection {
+            *projection = main_projection.clone();
+        }
+
+        if config.stereo == StereoMode::SideBySide {
+            let half = UVec2::new(window_size.x / 2, window_size.y).max(UVec2::ONE);
+            let position = UVec2::new(if eye.side < 0. { 0 } else { half.x }, 0);
+            if camera
+                .viewport
+                .as_ref()
+                .map(|viewport| (viewport.physical_position, viewport.physical_size))
+                != Some((position, half))
+            {
+                camera.viewport = Some(Viewport {
+                    physical_position: position,
+                    physical_size: half,
+                    ..default()
+                });
+            }
+        }
+    }
+
+    // Follows window resizes. Touching the material makes it pick up the resized images.
+    for node in &composites {
+        let Some(material) = materials.get(&node.0) else {
+            continue;
+        };
+        let eye_images = [material.left.clone(), material.right.clone()];
+        if eye_images.iter().any(|handle| {
+            images
+                .get(handle)
+                .is_some_and(|image| image.size() != window_size)
+        }) {
+            for handle in &eye_images {
+                if let Some(image) = images.get_mut(handle) {
+                    image.resize(Extent3d {
+                        width: window_size.x,
+                        height: window_size.y,
+                        ..default()
+                    });
+                }
+            }
+            materials.get_mut(&node.0);
+        }
+    }
+}
@@ -0,0 +1,68 @@
+use bevy::prelude::*;
+
+use crate::{
+    attractor::AttractorSystem, Configuration, SimpleColorMaterial, TimeOfBirth, TrailHead,
+    HEAD_RADIUS,
+};
+
+/// How much of the original color the mirrored trails keep, the rest is gray.
+const SATURATION: f32 = 0.35;
+const ALPHA: f32 = 0.5;
+
+pub struct SymmetryPlugin;
+
+impl Plugin for SymmetryPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            draw_symmetric_twins.run_if(|config: Res<Configuration>| {
+                config.symmetric_twins && has_mirror_symmetry(config.attractor)
+            }),
+        );
+    }
+}
+
+/// Whether the equations of `attractor` are invariant under [`mirror`]. Of the built-in
+/// systems only Lorenz and Chen are, and nothing is known about custom equations.
+pub fn has_mirror_symmetry(attractor: AttractorSystem) -> bool {
+    matches!(
+        attractor,
+        AttractorSystem::Lorenz | AttractorSystem::Chen { .. }
+    )
+}
+
+/// The Lorenz equations are invariant under (x, y, z) → (−x, −y, z), so the mirror image of a
+/// trajectory is a trajectory as well.
+fn mirror(point: Vec3) -> Vec3 {
+    Vec3::new(-point.x, -point.y, point.z)
+}
+
+fn muted(color: LinearRgba) -> Color {
+    let gray = LinearRgba::gray(color.red * 0.2126 + color.green * 0.7152 + color.blue * 0.0722);
+    Color::from(gray.mix(&color, SATURATION)).with_alpha(ALPHA)
+}
+
+/// Draws the mirrored trails from the existing segments instead of integrating them.
+fn draw_symmetric_twins(
+    mut gizmos: Gizmos,
+    segments: Query<(&Transform, &MeshMaterial3d<SimpleColorMaterial>), With<TimeOfBirth>>,
+    heads: Query<&Transform, With<TrailHead>>,
+    simple_color_materials: Res<Assets<SimpleColorMaterial>>,
+) {
+    for (transform, material) in &segments {
+        let Some(material) = simple_color_materials.get(material) else {
+            continue;
+        };
+        let start = transform.translation;
+        let end = start + transform.rotation * Vec3::new(0., transform.scale.y, 0.);
+        gizmos.line(mirror(start), mirror(end), muted(material.color));
+    }
+
+    for transform in &heads {
+        gizmos.sphere(
+            Isometry3d::from_translation(mirror(transform.translation)),
+            HEAD_RADIUS,
+            Color::WHITE.with_alpha(ALPHA),
+        );
+    }
+}
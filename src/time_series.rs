@@ -0,0 +1,125 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+use egui_plot::{Line, Plot, PlotPoints};
+
+use crate::{
+    extensions::{Visualization, VisualizationSet},
+    selection::Selection,
+    update_position, HeadIndex, TrailHead,
+};
+
+/// Samples kept for the plots, a bit over a minute at the default refresh rate.
+const CAPACITY: usize = 8000;
+
+pub struct TimeSeriesPanel;
+
+impl Visualization for TimeSeriesPanel {
+    fn name(&self) -> &'static str {
+        "Time series"
+    }
+
+    fn build(&self, app: &mut App, set: VisualizationSet) {
+        app.init_resource::<TimeSeries>()
+            .add_systems(
+                FixedUpdate,
+                sample_selected_head.after(update_position).in_set(set),
+            )
+            .add_systems(Update, time_series_ui.in_set(set));
+    }
+}
+
+/// Recent positions of the selected head.
+#[derive(Resource, Default)]
+struct TimeSeries {
+    head: Option<Entity>,
+    /// Simulated seconds and position.
+    samples: VecDeque<(f64, Vec3)>,
+}
+
+fn sample_selected_head(
+    heads: Query<&Transform, With<TrailHead>>,
+    mut series: ResMut<TimeSeries>,
+    selection: Res<Selection>,
+    time: Res<Time<Virtual>>,
+) {
+    if series.head != selection.0 {
+        series.head = selection.0;
+        series.samples.clear();
+    }
+    let Some(transform) = selection.0.and_then(|head| heads.get(head).ok()) else {
+        return;
+    };
+
+    if series.samples.len() == CAPACITY {
+        series.samples.pop_front();
+    }
+    series
+        .samples
+        .push_back((time.elapsed_secs_f64(), transform.translation));
+}
+
+fn time_series_ui(
+    mut contexts: EguiContexts,
+    mut selection: ResMut<Selection>,
+    series: Res<TimeSeries>,
+    heads: Query<(Entity, &HeadIndex), With<TrailHead>>,
+) {
+    egui::Window::new("Time series")
+        .default_open(false)
+        .show(contexts.ctx_mut(), |ui| {
+            let mut heads: Vec<(Entity, u16)> = heads
+                .iter()
+                .map(|(entity, index)| (entity, index.0))
+                .collect();
+            heads.sort_by_key(|&(entity, index)| (index, entity));
+
+            let label = |(entity, index): (Entity, u16)| format!("Head {index} ({entity})");
+            let selected_text = selection
+                .0
+                .and_then(|selected| heads.iter().find(|&&(entity, _)| entity == selected))
+                .map_or("None".to_string(), |&head| label(head));
+            // Shares the selection with the control window, so changing it here highlights
+            // the head in the scene too.
+            egui::ComboBox::from_label("Plotted head")
+                .selected_text(selected_text)
+                .show_ui(ui, |ui| {
+                    for head in heads {
+                        ui.selectable_value(&mut selection.0, Some(head.0), label(head));
+                    }
+                });
+
+            if series.samples.len() < 2 {
+                ui.label("Select a head to plot its coordinates.");
+                return;
+            }
+
+            let coordinate = |axis: usize| -> PlotPoints {
+                series
+                    .samples
+                    .iter()
+                    .map(|&(t, position)| [t, position[axis] as f64])
+                    .collect()
+            };
+            for (axis, name) in ["x", "y", "z"].into_iter().enumerate() {
+                Plot::new(("time_series", name))
+                    .height(100.)
+                    .y_axis_label(name)
+                    .show(ui, |plot_ui| plot_ui.line(Line::new(coordinate(axis))));
+            }
+
+            ui.label("Phase plot");
+            let phase: PlotPoints = series
+                .samples
+                .iter()
+                .map(|&(_, position)| [position.x as f64, position.z as f64])
+                .collect();
+            Plot::new("phase_plot_xz")
+                .height(200.)
+                .data_aspect(1.)
+                .x_axis_label("x")
+                .y_axis_label("z")
+                .show(ui, |plot_ui| plot_ui.line(Line::new(phase)));
+        });
+}
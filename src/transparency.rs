@@ -0,0 +1,38 @@
+use bevy::{core_pipeline::oit::OrderIndependentTransparencySettings, prelude::*};
+
+use crate::Configuration;
+
+pub struct TransparencyPlugin;
+
+impl Plugin for TransparencyPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            apply_transparency.run_if(|config: Res<Configuration>| config.is_changed()),
+        );
+    }
+}
+
+/// Blends the fragments of translucent trails in depth order per pixel rather than sorting
+/// whole segments, which flickers where segments overlap. Needs multisampling off.
+fn apply_transparency(
+    mut commands: Commands,
+    cameras: Query<(Entity, Has<OrderIndependentTransparencySettings>), With<Camera3d>>,
+    config: Res<Configuration>,
+) {
+    for (camera, enabled) in &cameras {
+        if enabled == config.order_independent_transparency {
+            continue;
+        }
+        if config.order_independent_transparency {
+            commands
+                .entity(camera)
+                .insert((OrderIndependentTransparencySettings::default(), Msaa::Off));
+        } else {
+            commands
+                .entity(camera)
+                .remove::<OrderIndependentTransparencySettings>()
+                .insert(Msaa::default());
+        }
+    }
+}
@@ -0,0 +1,350 @@
+use std::collections::VecDeque;
+
+use bevy::{
+    prelude::*,
+    render::{
+        mesh::{Indices, PrimitiveTopology},
+        render_asset::RenderAssetUsages,
+    },
+    utils::HashMap,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    attractor::Parameters,
+    instances::{instance_parameters, AttractorInstance},
+    selection::Frozen,
+    update_position, BirthDelay, Configuration, SimpleColorMaterial, TrailData, TrailHead,
+};
+
+const TUBE_SIDES: usize = 8;
+/// How many times the distance the velocity covers in one tick a head may move before the
+/// move counts as a jump. Higher order integrators stray a little from the straight step.
+const JUMP_FACTOR: f32 = 4.;
+
+pub struct TubePlugin;
+
+impl Plugin for TubePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            extend_tubes.after(update_position).run_if(uses_tubes),
+        )
+        .add_systems(
+            Update,
+            (
+                rebuild_tubes.run_if(uses_tubes),
+                remove_tubes.run_if(|config: Res<Configuration>| {
+                    config.is_changed() && config.trail_style == TrailStyle::Segments
+                }),
+            ),
+        );
+    }
+}
+
+/// How trails are drawn.
+#[derive(Reflect, Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TrailStyle {
+    /// A cylinder per physics tick, which leaves small gaps at sharp turns.
+    #[default]
+    Segments,
+    /// One continuous tube mesh per head.
+    Tube,
+    /// One continuous flat strip per head, cheaper than a tube.
+    Ribbon,
+}
+
+fn uses_tubes(config: Res<Configuration>) -> bool {
+    config.trail_style != TrailStyle::Segments
+}
+
+/// The continuous trail of `head`, in world space. Its mesh is rebuilt from these points
+/// whenever they change, or every frame while tapering.
+#[derive(Component)]
+pub struct TubeTrail {
+    head: Entity,
+    points: VecDeque<TubePoint>,
+}
+
+#[derive(Clone)]
+pub struct TubePoint {
+    pub position: Vec3,
+    /// Virtual time at which the head passed this point.
+    pub born: f32,
+    /// Fixed when the point is added, so the trail doesn't twist as its tail fades away.
+    pub normal: Vec3,
+}
+
+impl TubeTrail {
+    pub fn new(head: Entity, points: impl IntoIterator<Item = TubePoint>) -> Self {
+        Self {
+            head,
+            points: points.into_iter().collect(),
+        }
+    }
+
+    /// Appends `position`, carrying the previous normal along with its tangential part
+    /// removed. Consecutive frames barely twist that way and the joins stay smooth.
+    fn push(&mut self, position: Vec3, born: f32) {
+        let last = self.points.back();
+        let Some(tangent) = last.map_or(Some(Vec3::Z), |last| {
+            (position - last.position).try_normalize()
+        }) else {
+            return;
+        };
+        let normal = last
+            .and_then(|last| last.normal.reject_from(tangent).try_normalize())
+            .unwrap_or_else(|| tangent.any_orthonormal_vector());
+        self.points.push_back(TubePoint {
+            position,
+            born,
+            normal,
+        });
+    }
+
+    fn last_position(&self) -> Option<Vec3> {
+        self.points.back().map(|point| point.position)
+    }
+
+    pub fn head(&self) -> Entity {
+        self.head
+    }
+
+    pub fn points(&self) -> impl Iterator<Item = &TubePoint> {
+        self.points.iter()
+    }
+
+    pub fn clear(&mut self) {
+        self.points.clear();
+    }
+
+    /// Drops the points added after `time`.
+    pub fn truncate_after(&mut self, time: f32) {
+        while self.points.back().is_some_and(|point| point.born > time) {
+            self.points.pop_back();
+        }
+    }
+}
+
+fn extend_tubes(
+    heads: Query<
+        (Entity, &Transform, &TrailData, Option<&Parent>),
+        (With<TrailHead>, Without<BirthDelay>, Without<Frozen>),
+    >,
+    instances: Query<(Entity, &AttractorInstance, &Transform), Without<TrailHead>>,
+    mut tubes: Query<&mut TubeTrail>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut commands: Commands,
+    time: Res<Time<Virtual>>,
+    config: Res<Configuration>,
+) {
+    let instances = instance_parameters(&instances);
+    let main_parameters = Parameters::of(&config);
+    let dt = config.delta_t as f32 / 10000.;
+    let mut tube_of: HashMap<Entity, Mut<TubeTrail>> =
+        tubes.iter_mut().map(|tube| (tube.head, tube)).collect();
+
+    for (head, transform, trail_data, parent) in &heads {
+        // Tubes are in world space, heads of an instance in the instance's coordinates.
+        let (parameters, offset) = match parent.and_then(|parent| instances.get(&parent.get())) {
+            Some((parameters, offset)) => (parameters, *offset),
+            None => (&main_parameters, Vec3::ZERO),
+        };
+        let position = transform.translation + offset;
+
+        if let Some(tube) = tube_of.get_mut(&head) {
+            // A respawned head jumps back to its initial condition. Joining the old tail to
+            // it would draw a line straight through the attractor, so the old tube is left
+            // to fade on its own and a fresh one starts at the new position.
+            let jumped = tube.last_position().is_some_and(|last| {
+                let speed = [last - offset, transform.translation]
+                    .map(|position| {
+                        config
+                            .attractor
+                            .velocity(&config, parameters, position)
+                            .length()
+                    })
+                    .into_iter()
+                    .fold(0., f32::max);
+                last.distance(position) > JUMP_FACTOR * speed * dt
+            });
+            if !jumped {
+                tube.push(position, time.elapsed_secs());
+                continue;
+            }
+            tube.head = Entity::PLACEHOLDER;
+        }
+
+        let mut tube = TubeTrail::new(head, []);
+        tube.push(position, time.elapsed_secs());
+        commands.spawn(tube_bundle(tube, &mut meshes, trail_data.material.clone()));
+    }
+}
+
+/// Everything to draw `tube`. The mesh starts out empty and is built on the next rebuild.
+pub fn tube_bundle(
+    tube: TubeTrail,
+    meshes: &mut Assets<Mesh>,
+    material: Handle<SimpleColorMaterial>,
+) -> impl Bundle {
+    (
+        tube,
+        Mesh3d(meshes.add(empty_mesh())),
+        MeshMaterial3d(material),
+        Transform::IDENTITY,
+    )
+}
+
+fn rebuild_tubes(
+    mut tubes: Query<(Entity, &mut TubeTrail, &Mesh3d)>,
+    heads: Query<(), With<TrailHead>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut commands: Commands,
+    time: Res<Time<Virtual>>,
+    config: Res<Configuration>,
+) {
+    let lifetime = config.trail_lifetime as f32 / 10.;
+    let now = time.elapsed_secs();
+
+    for (entity, mut tube, mesh) in &mut tubes {
+        while tube
+            .points
+            .front()
+            .is_some_and(|point| now - point.born >= lifetime)
+        {
+            tube.points.pop_front();
+        }
+        // A tube outlives its head until it has faded away completely.
+        if tube.points.is_empty() && heads.get(tube.head).is_err() {
+            commands.entity(entity).despawn();
+            meshes.remove(&mesh.0);
+            continue;
+        }
+        // Without a taper the mesh only changes shape when points come or go.
+        if !(config.trail_taper || config.is_changed() || tube.is_changed()) {
+            continue;
+        }
+
+        if let Some(mesh) = meshes.get_mut(&mesh.0) {
+            // Same radius and taper as the segments, so switching styles keeps the look.
+            let radius = |point: &TubePoint| {
+                let taper = if config.trail_taper {
+                    1. - (now - point.born) / lifetime
+                } else {
+                    1.
+                };
+                config.trail_radius * taper
+            };
+            *mesh = match config.trail_style {
+                TrailStyle::Ribbon => ribbon_mesh(&tube.points, radius),
+                _ => tube_mesh(&tube.points, radius),
+            };
+        }
+    }
+}
+
+fn remove_tubes(
+    tubes: Query<(Entity, &Mesh3d), With<TubeTrail>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut commands: Commands,
+) {
+    for (entity, mesh) in &tubes {
+        commands.entity(entity).despawn();
+        meshes.remove(&mesh.0);
+    }
+}
+
+fn empty_mesh() -> Mesh {
+    Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::RENDER_WORLD | RenderAssetUsages::MAIN_WORLD,
+    )
+    .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, Vec::<[f32; 3]>::new())
+    .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, Vec::<[f32; 3]>::new())
+    .with_inserted_indices(Indices::U32(Vec::new()))
+}
+
+/// The normal and binormal at every point, around the tangent averaged over both
+/// neighbours so joins bisect the turn.
+fn frames(points: &VecDeque<TubePoint>) -> impl Iterator<Item = (Vec3, Vec3)> + '_ {
+    (0..points.len()).map(|i| {
+        let before = points[i.saturating_sub(1)].position;
+        let after = points[(i + 1).min(points.len() - 1)].position;
+        let normal = points[i].normal;
+        let Some(tangent) = (after - before).try_normalize() else {
+            return (normal, normal.any_orthonormal_vector());
+        };
+        let normal = normal
+            .reject_from(tangent)
+            .try_normalize()
+            .unwrap_or(normal);
+        (normal, tangent.cross(normal))
+    })
+}
+
+/// Rings of `TUBE_SIDES` vertices around every point, joined by quads.
+fn tube_mesh(points: &VecDeque<TubePoint>, radius: impl Fn(&TubePoint) -> f32) -> Mesh {
+    if points.len() < 2 {
+        return empty_mesh();
+    }
+
+    let mut positions = Vec::with_capacity(points.len() * TUBE_SIDES);
+    let mut normals = Vec::with_capacity(points.len() * TUBE_SIDES);
+    for (point, (normal, binormal)) in points.iter().zip(frames(points)) {
+        let radius = radius(point);
+        for side in 0..TUBE_SIDES {
+            let angle = side as f32 / TUBE_SIDES as f32 * std::f32::consts::TAU;
+            let outward = normal * angle.cos() + binormal * angle.sin();
+            positions.push((point.position + outward * radius).to_array());
+            normals.push(outward.to_array());
+        }
+    }
+
+    let mut indices = Vec::with_capacity((points.len() - 1) * TUBE_SIDES * 6);
+    for ring in 0..points.len() as u32 - 1 {
+        let (this, next) = (ring * TUBE_SIDES as u32, (ring + 1) * TUBE_SIDES as u32);
+        for side in 0..TUBE_SIDES as u32 {
+            let following = (side + 1) % TUBE_SIDES as u32;
+            indices.extend([
+                this + side,
+                this + following,
+                next + side,
+                next + side,
+                this + following,
+                next + following,
+            ]);
+        }
+    }
+
+    empty_mesh()
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, normals)
+        .with_inserted_indices(Indices::U32(indices))
+}
+
+/// A strip two radii wide along the binormal, with both windings so it shows from either side.
+fn ribbon_mesh(points: &VecDeque<TubePoint>, radius: impl Fn(&TubePoint) -> f32) -> Mesh {
+    if points.len() < 2 {
+        return empty_mesh();
+    }
+
+    let mut positions = Vec::with_capacity(points.len() * 2);
+    let mut normals = Vec::with_capacity(points.len() * 2);
+    for (point, (normal, binormal)) in points.iter().zip(frames(points)) {
+        let offset = binormal * radius(point);
+        positions.push((point.position - offset).to_array());
+        positions.push((point.position + offset).to_array());
+        normals.extend([normal.to_array(); 2]);
+    }
+
+    let mut indices = Vec::with_capacity((points.len() - 1) * 12);
+    for i in 0..points.len() as u32 - 1 {
+        let (a, b, c, d) = (2 * i, 2 * i + 1, 2 * i + 2, 2 * i + 3);
+        indices.extend([a, b, c, c, b, d, a, c, b, c, d, b]);
+    }
+
+    empty_mesh()
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, normals)
+        .with_inserted_indices(Indices::U32(indices))
+}
@@ -0,0 +1,127 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+
+pub struct TutorialPlugin;
+
+impl Plugin for TutorialPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Tutorial>().add_systems(
+            Update,
+            tutorial_ui.run_if(|tutorial: Res<Tutorial>| tutorial.0.is_some()),
+        );
+    }
+}
+
+/// Current step of the guided tour, `None` while it isn't running.
+#[derive(Resource, Default)]
+pub struct Tutorial(pub Option<usize>);
+
+struct Step {
+    title: &'static str,
+    text: &'static str,
+    /// Title of the window to highlight.
+    window: Option<&'static str>,
+}
+
+const STEPS: &[Step] = &[
+    Step {
+        title: "Welcome",
+        text: "Each sphere is a point moving through the Lorenz system. They all start almost \
+               at the same place, yet soon end up on completely different parts of the \
+               butterfly-shaped attractor. That sensitivity to initial conditions is chaos.",
+        window: None,
+    },
+    Step {
+        title: "Moving the camera",
+        text: "Drag with the left mouse button to orbit, with the right one to pan, and use \
+               the scroll wheel to zoom.",
+        window: None,
+    },
+    Step {
+        title: "Control window",
+        text: "Clear removes all heads and trails, Start spawns a fresh set of heads. Below \
+               are tools for sharing the current setup, loading reference trajectories and \
+               saving the whole simulation.",
+        window: Some("Control"),
+    },
+    Step {
+        title: "Parameters",
+        text: "sigma, rho and beta define the system. Try lowering rho below 24: the \
+               trajectories stop wandering and settle into one of two fixed points. The \
+               number of heads, their spacing and the step size apply on the next Start.",
+        window: Some("Configuration"),
+    },
+    Step {
+        title: "Following a single head",
+        text: "Pick a head in the Selected head box of the control window to highlight it. \
+               While paused, it can be dragged along the arrows that appear.",
+        window: Some("Control"),
+    },
+    Step {
+        title: "Statistics",
+        text: "Expand this window to see the mean, variance and range of the selected head's \
+               coordinates over the last few seconds.",
+        window: Some("Statistics"),
+    },
+    Step {
+        title: "Poincaré section",
+        text: "Enable the slicing plane in the parameters, then every time a head passes \
+               through it the crossing point is recorded here.",
+        window: Some("Poincaré section"),
+    },
+    Step {
+        title: "Command palette",
+        text: "Press Ctrl+P anywhere to search all actions by name. That's it, have fun!",
+        window: None,
+    },
+];
+
+fn tutorial_ui(mut contexts: EguiContexts, mut tutorial: ResMut<Tutorial>) {
+    let Some(index) = tutorial.0 else {
+        return;
+    };
+    let step = &STEPS[index.min(STEPS.len() - 1)];
+    let ctx = contexts.ctx_mut();
+
+    if let Some(rect) = step
+        .window
+        .and_then(|window| ctx.memory(|memory| memory.area_rect(egui::Id::new(window))))
+    {
+        ctx.layer_painter(egui::LayerId::new(
+            egui::Order::Foreground,
+            egui::Id::new("tutorial_highlight"),
+        ))
+        .rect_stroke(
+            rect.expand(4.),
+            6.,
+            egui::Stroke::new(3., egui::Color32::from_rgb(255, 200, 0)),
+        );
+    }
+
+    egui::Window::new(format!("Tutorial ({}/{})", index + 1, STEPS.len()))
+        .id(egui::Id::new("tutorial"))
+        .collapsible(false)
+        .anchor(egui::Align2::CENTER_BOTTOM, [0., -40.])
+        .show(ctx, |ui| {
+            ui.heading(step.title);
+            ui.label(step.text);
+            ui.horizontal(|ui| {
+                if ui
+                    .add_enabled(index > 0, egui::Button::new("Previous"))
+                    .clicked()
+                {
+                    tutorial.0 = Some(index - 1);
+                }
+                if index + 1 < STEPS.len() {
+                    if ui.button("Next").clicked() {
+                        tutorial.0 = Some(index + 1);
+                    }
+                    if ui.button("Skip").clicked() {
+                        tutorial.0 = None;
+                    }
+                } else if ui.button("Finish").clicked() {
+                    tutorial.0 = None;
+                }
+            });
+        });
+}
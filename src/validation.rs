@@ -0,0 +1,274 @@
+use bevy::prelude::*;
+use bevy_egui::egui;
+
+use crate::{
+    attractor::AttractorSystem,
+    coloring::TrailColoring,
+    event_log::{LogCategory, LogEntry},
+    initial_conditions::InitialConditions,
+    particles::MAX_PARTICLES,
+    symmetry,
+    tube::TrailStyle,
+    Configuration,
+};
+
+/// More heads than this make the per-tick work and the number of segment entities explode.
+pub const MAX_TRAILS: u16 = 2000;
+pub const MAX_REFRESH_RATE: u16 = 2000;
+/// Range of the simulation speed relative to real time. Fast speeds run many physics ticks per
+/// frame.
+pub const MIN_TIME_SCALE: f32 = 0.1;
+pub const MAX_TIME_SCALE: f32 = 100.;
+/// Arrows along each axis of the vector field grid, cubed for the total.
+pub const MAX_FIELD_DENSITY: u8 = 32;
+/// Proximity checks compare every pair of heads, which gets slow beyond this.
+const PROXIMITY_HEAD_LIMIT: u16 = 500;
+const MAX_SMOOTHNESS: f32 = 0.99;
+
+pub struct ValidationPlugin;
+
+impl Plugin for ValidationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ConfigConflicts>().add_systems(
+            PostUpdate,
+            sanitize_configuration.run_if(|config: Res<Configuration>| config.is_changed()),
+        );
+    }
+}
+
+/// Combinations of settings that are allowed but probably not what the user wants.
+#[derive(Resource, Default)]
+pub struct ConfigConflicts(pub Vec<String>);
+
+impl Configuration {
+    /// Pulls every value the simulation can't run with back into range and returns a
+    /// description of each change.
+    pub fn clamp(&mut self) -> Vec<String> {
+        let defaults = Configuration::default();
+        let mut changes = Vec::new();
+        let mut clamp_int = |name: &str, value: &mut u16, min: u16, max: u16| {
+            let clamped = (*value).clamp(min, max);
+            if clamped != *value {
+                changes.push(format!("{name} clamped from {value} to {clamped}"));
+                *value = clamped;
+            }
+        };
+        clamp_int("num_of_trails", &mut self.num_of_trails, 1, MAX_TRAILS);
+        clamp_int("trail_lifetime", &mut self.trail_lifetime, 1, u16::MAX);
+        clamp_int(
+            "physics_refresh_rate",
+            &mut self.physics_refresh_rate,
+            1,
+            MAX_REFRESH_RATE,
+        );
+        clamp_int(
+            "emitter_population",
+            &mut self.emitter_population,
+            0,
+            MAX_TRAILS,
+        );
+
+        if self.delta_t == 0 {
+            changes.push("delta_t raised from 0 to 1".into());
+            self.delta_t = 1;
+        }
+        if !(2..=MAX_FIELD_DENSITY).contains(&self.vector_field_density) {
+            let clamped = self.vector_field_density.clamp(2, MAX_FIELD_DENSITY);
+            changes.push(format!(
+                "vector_field_density clamped from {} to {clamped}",
+                self.vector_field_density
+            ));
+            self.vector_field_density = clamped;
+        }
+        if !(MIN_TIME_SCALE..=MAX_TIME_SCALE).contains(&self.time_scale) {
+            let clamped = if self.time_scale.is_nan() {
+                defaults.time_scale
+            } else {
+                self.time_scale.clamp(MIN_TIME_SCALE, MAX_TIME_SCALE)
+            };
+            changes.push(format!(
+                "time_scale clamped from {} to {clamped}",
+                self.time_scale
+            ));
+            self.time_scale = clamped;
+        }
+        if self.particle_count > MAX_PARTICLES {
+            changes.push(format!("particle_count lowered to {MAX_PARTICLES}"));
+            self.particle_count = MAX_PARTICLES;
+        }
+
+        let mut reset_non_finite = |name: &str, value: &mut f32, default: f32| {
+            if !value.is_finite() {
+                changes.push(format!("{name} reset to {default}"));
+                *value = default;
+            }
+        };
+        reset_non_finite("sigma", &mut self.sigma, defaults.sigma);
+        reset_non_finite("rho", &mut self.rho, defaults.rho);
+        reset_non_finite("beta", &mut self.beta, defaults.beta);
+        reset_non_finite(
+            "initial_distance",
+            &mut self.initial_distance,
+            defaults.initial_distance,
+        );
+
+        let mut clamp_non_negative = |name: &str, value: &mut f32| {
+            if value.is_nan() || *value < 0. {
+                changes.push(format!("{name} raised from {value} to 0"));
+                *value = 0.;
+            }
+        };
+        clamp_non_negative("fly_speed", &mut self.fly_speed);
+        clamp_non_negative("orbit_sensitivity", &mut self.orbit_sensitivity);
+        clamp_non_negative("zoom_sensitivity", &mut self.zoom_sensitivity);
+        clamp_non_negative("auto_clear_interval", &mut self.auto_clear_interval);
+        clamp_non_negative("spawn_stagger", &mut self.spawn_stagger);
+        clamp_non_negative("emitter_rate", &mut self.emitter_rate);
+        clamp_non_negative("emitter_radius", &mut self.emitter_radius);
+        clamp_non_negative("proximity_distance", &mut self.proximity_distance);
+        clamp_non_negative("particle_exposure", &mut self.particle_exposure);
+        clamp_non_negative("density_volume_opacity", &mut self.density_volume_opacity);
+        clamp_non_negative("trail_radius", &mut self.trail_radius);
+        clamp_non_negative("overlay_extent", &mut self.overlay_extent);
+        clamp_non_negative("stereo_eye_separation", &mut self.stereo_eye_separation);
+
+        // Smoothness of 1 or more never reaches the target, the orbit camera would freeze.
+        for (name, value) in [
+            ("orbit_smoothness", &mut self.orbit_smoothness),
+            ("pan_smoothness", &mut self.pan_smoothness),
+            ("zoom_smoothness", &mut self.zoom_smoothness),
+            ("follow_smoothness", &mut self.follow_smoothness),
+        ] {
+            let clamped = if value.is_nan() {
+                0.
+            } else {
+                value.clamp(0., MAX_SMOOTHNESS)
+            };
+            if clamped != *value {
+                changes.push(format!("{name} clamped from {value} to {clamped}"));
+                *value = clamped;
+            }
+        }
+
+        if self.runaway_limit.is_nan() || self.runaway_limit <= 0. {
+            changes.push(format!("runaway_limit reset to {}", defaults.runaway_limit));
+            self.runaway_limit = defaults.runaway_limit;
+        }
+        // The pitch mapping divides by these, and an empty projection view can't be rendered.
+        for (name, value, default) in [
+            (
+                "sonification_low_pitch",
+                &mut self.sonification_low_pitch,
+                defaults.sonification_low_pitch,
+            ),
+            (
+                "sonification_high_pitch",
+                &mut self.sonification_high_pitch,
+                defaults.sonification_high_pitch,
+            ),
+            (
+                "sonification_x_extent",
+                &mut self.sonification_x_extent,
+                defaults.sonification_x_extent,
+            ),
+            (
+                "projection_extent",
+                &mut self.projection_extent,
+                defaults.projection_extent,
+            ),
+        ] {
+            if value.is_nan() || *value <= 0. {
+                changes.push(format!("{name} reset to {default}"));
+                *value = default;
+            }
+        }
+        // A tolerance of 0 would spend every substep on each tick.
+        if self.rk45_tolerance.is_nan() || self.rk45_tolerance <= 0. {
+            changes.push(format!(
+                "rk45_tolerance reset to {}",
+                defaults.rk45_tolerance
+            ));
+            self.rk45_tolerance = defaults.rk45_tolerance;
+        }
+
+        changes
+    }
+
+    fn conflicts(&self) -> Vec<String> {
+        let mut conflicts = Vec::new();
+        if self.fog_enabled && self.fog_end <= self.fog_start {
+            conflicts.push("fog_end should be larger than fog_start".into());
+        }
+        if self.emitter_enabled && (self.emitter_population == 0 || self.emitter_rate == 0.) {
+            conflicts.push("The emitter needs a population and rate above 0 to emit".into());
+        }
+        if self.proximity_events && self.num_of_trails > PROXIMITY_HEAD_LIMIT {
+            conflicts.push(format!(
+                "Proximity events get slow with more than {PROXIMITY_HEAD_LIMIT} heads"
+            ));
+        }
+        if self.particles_enabled && self.attractor != AttractorSystem::Lorenz {
+            conflicts.push("GPU particles only follow the Lorenz system and stay hidden".into());
+        }
+        if self.symmetric_twins && !symmetry::has_mirror_symmetry(self.attractor) {
+            conflicts.push(format!(
+                "{} is not symmetric under (x, y, z) → (−x, −y, z), twins stay hidden",
+                self.attractor.name()
+            ));
+        }
+        if self.trail_style != TrailStyle::Segments && self.trail_coloring != TrailColoring::PerHead
+        {
+            conflicts.push("Tubes and ribbons are always colored per head".into());
+        }
+        if self.initial_conditions == InitialConditions::Custom(Vec::new()) {
+            conflicts.push("Custom initial conditions without points spawn no heads".into());
+        }
+        if self.compare_precision && self.double_precision {
+            conflicts
+                .push("compare_precision has no f32 heads to compare with double_precision".into());
+        }
+        if self.sonification && self.sonification_z_max <= self.sonification_z_min {
+            conflicts.push("sonification_z_max should be larger than sonification_z_min".into());
+        }
+        if self.attractor == AttractorSystem::Custom && !self.custom_system.is_valid() {
+            conflicts.push("The custom system has invalid equations, its heads stand still".into());
+        }
+        if self.environment_map_enabled
+            && (self.environment_diffuse_map.is_empty() || self.environment_specular_map.is_empty())
+        {
+            conflicts.push("environment_map_enabled needs both environment map paths".into());
+        }
+        if self.environment_as_background && !self.environment_map_enabled {
+            conflicts.push("environment_as_background needs environment_map_enabled".into());
+        }
+        conflicts
+    }
+}
+
+/// Runs after the inspector and every other editor had their say this frame, so no invalid
+/// value reaches the next physics tick.
+fn sanitize_configuration(
+    mut config: ResMut<Configuration>,
+    mut conflicts: ResMut<ConfigConflicts>,
+    mut log: EventWriter<LogEntry>,
+) {
+    // Only write back when something actually changed, to not retrigger change detection.
+    let mut clamped = config.clone();
+    let changes = clamped.clamp();
+    if !changes.is_empty() {
+        *config = clamped;
+    }
+    for change in changes {
+        warn!("{change}");
+        log.send(LogEntry::new(LogCategory::Parameters, change).notify());
+    }
+
+    conflicts.0 = config.conflicts();
+}
+
+/// Lists the current conflicts, if any.
+pub fn conflicts_ui(world: &mut World, ui: &mut egui::Ui) {
+    for conflict in &world.resource::<ConfigConflicts>().0 {
+        ui.colored_label(egui::Color32::YELLOW, conflict);
+    }
+}
@@ -0,0 +1,134 @@
+use bevy::prelude::*;
+
+use crate::{
+    attractor::AttractorSystem, coloring::ColorScheme, velocity, Configuration, SimpleColorMaterial,
+};
+
+/// Arrows in the slowest to fastest color buckets. Arrows of a bucket share their material, so
+/// they are drawn in a single instanced batch.
+const COLOR_BUCKETS: usize = 8;
+/// Length of the fastest arrow relative to the grid spacing.
+const ARROW_LENGTH: f32 = 0.9;
+const ARROW_RADIUS: f32 = 0.15;
+
+pub struct VectorFieldPlugin;
+
+impl Plugin for VectorFieldPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            rebuild_vector_field.run_if(|config: Res<Configuration>| config.is_changed()),
+        );
+    }
+}
+
+#[derive(Component)]
+struct VectorFieldArrow;
+
+/// Everything the arrows depend on, to skip rebuilding them for unrelated changes.
+#[derive(Clone, PartialEq)]
+struct FieldKey {
+    enabled: bool,
+    density: u8,
+    region: (Vec3, Vec3),
+    attractor: AttractorSystem,
+    parameters: [f32; 3],
+    color_scheme: ColorScheme,
+}
+
+impl FieldKey {
+    fn of(config: &Configuration) -> Self {
+        Self {
+            enabled: config.vector_field,
+            density: config.vector_field_density,
+            region: (config.vector_field_min, config.vector_field_max),
+            attractor: config.attractor,
+            parameters: [config.sigma, config.rho, config.beta],
+            color_scheme: config.color_scheme,
+        }
+    }
+}
+
+/// Evaluates the vector field at the grid points and respawns one arrow per point, pointing
+/// along the velocity. Length and color grow with the speed; the length with its square root,
+/// so the slow regions near the equilibria stay visible next to the fast outer loops.
+fn rebuild_vector_field(
+    arrows: Query<Entity, With<VectorFieldArrow>>,
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut simple_color_materials: ResMut<Assets<SimpleColorMaterial>>,
+    mut last: Local<Option<FieldKey>>,
+    mut mesh: Local<Option<Handle<Mesh>>>,
+    config: Res<Configuration>,
+) {
+    let key = FieldKey::of(&config);
+    if last.as_ref() == Some(&key) {
+        return;
+    }
+    *last = Some(key);
+
+    for arrow in &arrows {
+        commands.entity(arrow).despawn();
+    }
+    if !config.vector_field {
+        return;
+    }
+
+    let (min, max) = (
+        config.vector_field_min.min(config.vector_field_max),
+        config.vector_field_min.max(config.vector_field_max),
+    );
+    let density = config.vector_field_density.max(2) as usize;
+    let spacing = (max - min) / (density - 1) as f32;
+    let points: Vec<(Vec3, Vec3)> = (0..density.pow(3))
+        .map(|i| {
+            let cell = Vec3::new(
+                (i % density) as f32,
+                (i / density % density) as f32,
+                (i / density / density) as f32,
+            );
+            let point = min + cell * spacing;
+            (point, velocity(&config, point))
+        })
+        .filter(|(_, velocity)| velocity.is_finite())
+        .collect();
+    let fastest = points
+        .iter()
+        .map(|(_, velocity)| velocity.length())
+        .fold(0., f32::max);
+    if fastest <= 0. {
+        return;
+    }
+
+    let mesh = mesh
+        .get_or_insert_with(|| meshes.add(Cone::new(ARROW_RADIUS, 1.)))
+        .clone();
+    let materials: Vec<Handle<SimpleColorMaterial>> = (0..COLOR_BUCKETS)
+        .map(|bucket| {
+            let ratio = bucket as f32 / (COLOR_BUCKETS - 1) as f32;
+            simple_color_materials.add(SimpleColorMaterial {
+                color: config.color_scheme.gradient(ratio).into(),
+                ..default()
+            })
+        })
+        .collect();
+    let max_length = spacing.min_element() * ARROW_LENGTH;
+
+    for (point, velocity) in points {
+        let Some(direction) = velocity.try_normalize() else {
+            continue;
+        };
+        let ratio = (velocity.length() / fastest).sqrt();
+        let length = max_length * ratio;
+        let bucket = (ratio * (COLOR_BUCKETS - 1) as f32).round() as usize;
+        commands.spawn((
+            VectorFieldArrow,
+            Mesh3d(mesh.clone()),
+            MeshMaterial3d(materials[bucket].clone()),
+            // The cone is centered on its origin, so its base sits on the grid point.
+            Transform::from_translation(point + direction * length / 2.)
+                .with_rotation(Quat::from_rotation_arc(Vec3::Y, direction))
+                .with_scale(Vec3::new(1., length, 1.)),
+        ));
+    }
+}
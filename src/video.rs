@@ -0,0 +1,213 @@
+use std::{
+    io::Write,
+    process::{Child, ChildStdin, Command, Stdio},
+    thread,
+    time::Duration,
+};
+
+use bevy::{
+    prelude::*,
+    render::{
+        render_resource::TextureFormat,
+        view::screenshot::{Screenshot, ScreenshotCaptured},
+    },
+    time::TimeUpdateStrategy,
+};
+use bevy_egui::egui;
+
+use crate::file_dialog::{self, DialogKind};
+
+pub struct VideoExportPlugin;
+
+impl Plugin for VideoExportPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<VideoExport>().add_systems(
+            Last,
+            (
+                capture_video_frame.run_if(|export: Res<VideoExport>| export.recording),
+                update_time_strategy,
+            ),
+        );
+    }
+}
+
+/// Pipes every rendered frame to an `ffmpeg` process, which picks the codec from the
+/// extension of `path`. While recording, each frame advances time by exactly one video frame,
+/// so the video plays at the right speed no matter how long rendering takes.
+#[derive(Resource)]
+pub struct VideoExport {
+    path: String,
+    fps: u32,
+    recording: bool,
+    /// Started with the size and format of the first captured frame.
+    encoder: Option<Encoder>,
+    frames: u32,
+    status: Option<Result<String, String>>,
+}
+
+struct Encoder {
+    process: Child,
+    input: ChildStdin,
+    size: UVec2,
+}
+
+impl Default for VideoExport {
+    fn default() -> Self {
+        Self {
+            path: "lorenz.mp4".into(),
+            fps: 60,
+            recording: false,
+            encoder: None,
+            frames: 0,
+            status: None,
+        }
+    }
+}
+
+impl VideoExport {
+    fn start(&mut self) {
+        self.recording = true;
+        self.frames = 0;
+        self.status = Some(Ok(format!("Recording to {}", self.path)));
+    }
+
+    /// Closes the pipe and lets `ffmpeg` finish the file in the background.
+    fn stop(&mut self) {
+        self.recording = false;
+        let Some(Encoder {
+            mut process, input, ..
+        }) = self.encoder.take()
+        else {
+            return;
+        };
+        drop(input);
+        let path = self.path.clone();
+        thread::spawn(move || match process.wait() {
+            Ok(status) if status.success() => info!("Saved video to {path}"),
+            Ok(status) => error!("ffmpeg failed writing {path}: {status}"),
+            Err(err) => error!("ffmpeg failed writing {path}: {err}"),
+        });
+        self.status = Some(Ok(format!("Saved {} frames to {}", self.frames, self.path)));
+    }
+
+    fn fail(&mut self, err: String) {
+        self.stop();
+        self.status = Some(Err(err));
+    }
+
+    fn spawn_encoder(&self, size: UVec2, format: TextureFormat) -> Result<Encoder, String> {
+        let pixel_format = match format {
+            TextureFormat::Bgra8Unorm | TextureFormat::Bgra8UnormSrgb => "bgra",
+            TextureFormat::Rgba8Unorm | TextureFormat::Rgba8UnormSrgb => "rgba",
+            format => return Err(format!("Can't encode frames in {format:?}")),
+        };
+        let mut process = Command::new("ffmpeg")
+            .args(["-y", "-loglevel", "error"])
+            .args(["-f", "rawvideo", "-pix_fmt", pixel_format])
+            .args(["-s", &format!("{}x{}", size.x, size.y)])
+            .args(["-r", &self.fps.to_string(), "-i", "-"])
+            // Most players need yuv420p, which only works with even dimensions.
+            .args([
+                "-vf",
+                "pad=ceil(iw/2)*2:ceil(ih/2)*2",
+                "-pix_fmt",
+                "yuv420p",
+            ])
+            .arg(&self.path)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .spawn()
+            .map_err(|err| format!("Could not start ffmpeg: {err}"))?;
+        let input = process.stdin.take().expect("stdin is piped");
+        Ok(Encoder {
+            process,
+            input,
+            size,
+        })
+    }
+}
+
+fn capture_video_frame(mut commands: Commands) {
+    commands
+        .spawn(Screenshot::primary_window())
+        .observe(write_frame);
+}
+
+/// Steps time by one video frame while recording, and goes back to real time once recording
+/// stops, whether from the button or because `ffmpeg` failed.
+fn update_time_strategy(
+    export: Res<VideoExport>,
+    mut was_recording: Local<bool>,
+    mut strategy: ResMut<TimeUpdateStrategy>,
+) {
+    if export.recording == *was_recording {
+        return;
+    }
+    *was_recording = export.recording;
+    *strategy = if export.recording {
+        TimeUpdateStrategy::ManualDuration(Duration::from_secs_f64(1. / export.fps as f64))
+    } else {
+        TimeUpdateStrategy::Automatic
+    };
+}
+
+fn write_frame(trigger: Trigger<ScreenshotCaptured>, mut export: ResMut<VideoExport>) {
+    // Captures still in flight when recording stopped.
+    if !export.recording {
+        return;
+    }
+    let image = &trigger.event().0;
+    let size = image.size();
+
+    if export.encoder.is_none() {
+        match export.spawn_encoder(size, image.texture_descriptor.format) {
+            Ok(encoder) => export.encoder = Some(encoder),
+            Err(err) => return export.fail(err),
+        }
+    }
+    let encoder = export.encoder.as_mut().expect("encoder was just started");
+    // The video keeps the size it started with, frames of a resized window are dropped.
+    if encoder.size != size {
+        return;
+    }
+    if let Err(err) = encoder.input.write_all(&image.data) {
+        return export.fail(format!("ffmpeg stopped: {err}"));
+    }
+    export.frames += 1;
+}
+
+/// Output file, frame rate and a button to start and stop recording. Needs `ffmpeg` on the
+/// `PATH`.
+pub fn video_ui(world: &mut World, ui: &mut egui::Ui) {
+    let mut export = world.resource_mut::<VideoExport>();
+
+    ui.add_enabled_ui(!export.recording, |ui| {
+        ui.label("Video file");
+        file_dialog::path_field(ui, &mut export.path, DialogKind::Save, file_dialog::VIDEO);
+        ui.horizontal(|ui| {
+            ui.label("Frame rate");
+            ui.add(egui::DragValue::new(&mut export.fps).range(1..=240));
+        });
+    });
+    let mut recording = export.recording;
+    if ui.toggle_value(&mut recording, "Record video").changed() {
+        if recording {
+            export.start();
+        } else {
+            export.stop();
+        }
+    }
+    if export.recording {
+        ui.label(format!("{} frames", export.frames));
+    }
+
+    match export.status.as_ref() {
+        Some(Ok(message)) => {
+            ui.label(message);
+        }
+        Some(Err(err)) => {
+            ui.colored_label(egui::Color32::RED, err);
+        }
+        None => {}
+    }
+}
@@ -0,0 +1,115 @@
+use std::f32::consts::{FRAC_PI_2, FRAC_PI_4};
+
+use bevy::{prelude::*, render::camera::ScalingMode};
+use bevy_egui::egui;
+use bevy_panorbit_camera::PanOrbitCamera;
+
+use crate::Configuration;
+
+pub struct ViewsPlugin;
+
+impl Plugin for ViewsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            apply_projection.run_if(|config: Res<Configuration>| config.is_changed()),
+        );
+    }
+}
+
+/// Standard orientations of the orbit camera, which keeps the y-axis up.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum View {
+    /// Looking down the z-axis, x to the right and y up.
+    Xy,
+    /// Looking along the y-axis, x to the right and z up, as in most plots of the attractor.
+    Xz,
+    /// Looking along the x-axis, z to the right and y up.
+    Zy,
+    Isometric,
+}
+
+impl View {
+    pub const ALL: [View; 4] = [View::Xy, View::Xz, View::Zy, View::Isometric];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            View::Xy => "x–y",
+            View::Xz => "x–z",
+            View::Zy => "z–y",
+            View::Isometric => "Isometric",
+        }
+    }
+
+    /// Where the orbit camera ends up in this view at `distance` from `focus`.
+    pub fn transform(self, focus: Vec3, distance: f32) -> Transform {
+        let (yaw, pitch) = self.yaw_pitch();
+        let rotation = Quat::from_rotation_y(yaw) * Quat::from_rotation_x(-pitch);
+        Transform::from_translation(focus + rotation * Vec3::Z * distance).with_rotation(rotation)
+    }
+
+    fn yaw_pitch(self) -> (f32, f32) {
+        match self {
+            View::Xy => (0., 0.),
+            View::Xz => (0., -FRAC_PI_2),
+            View::Zy => (-FRAC_PI_2, 0.),
+            // Every axis at the same angle to the screen.
+            View::Isometric => (FRAC_PI_4, (1. / 2_f32.sqrt()).atan()),
+        }
+    }
+}
+
+/// Turns the orbit camera to `view` at once, keeping its focus and distance.
+pub fn snap(world: &mut World, view: View) {
+    let (yaw, pitch) = view.yaw_pitch();
+    for mut camera in world.query::<&mut PanOrbitCamera>().iter_mut(world) {
+        camera.target_yaw = yaw;
+        camera.target_pitch = pitch;
+        camera.yaw = Some(yaw);
+        camera.pitch = Some(pitch);
+        camera.force_update = true;
+    }
+}
+
+/// Switches the orbit camera between perspective and orthographic projection. The orbit
+/// camera zooms an orthographic projection by scaling it with its radius, so the height is per
+/// unit of distance and matches what the perspective projection shows at the focus.
+fn apply_projection(
+    mut cameras: Query<(&mut Projection, &mut PanOrbitCamera)>,
+    config: Res<Configuration>,
+) {
+    for (mut projection, mut camera) in &mut cameras {
+        let orthographic = matches!(*projection, Projection::Orthographic(_));
+        if orthographic == config.orthographic {
+            continue;
+        }
+        *projection = if config.orthographic {
+            let fov = PerspectiveProjection::default().fov;
+            Projection::Orthographic(OrthographicProjection {
+                scaling_mode: ScalingMode::FixedVertical {
+                    viewport_height: 2. * (fov / 2.).tan(),
+                },
+                ..OrthographicProjection::default_3d()
+            })
+        } else {
+            Projection::Perspective(default())
+        };
+        camera.force_update = true;
+    }
+}
+
+/// The projection toggle and a button per standard view.
+pub fn views_ui(world: &mut World, ui: &mut egui::Ui) {
+    let mut orthographic = world.resource::<Configuration>().orthographic;
+    if ui.checkbox(&mut orthographic, "Orthographic").changed() {
+        world.resource_mut::<Configuration>().orthographic = orthographic;
+    }
+
+    ui.horizontal(|ui| {
+        for view in View::ALL {
+            if ui.button(view.name()).clicked() {
+                snap(world, view);
+            }
+        }
+    });
+}
@@ -0,0 +1,61 @@
+use bevy::prelude::*;
+use bevy_panorbit_camera::PanOrbitCamera;
+
+use crate::{Configuration, TimeOfBirth};
+
+pub struct WallShadowsPlugin;
+
+impl Plugin for WallShadowsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            draw_wall_shadows.run_if(|config: Res<Configuration>| config.wall_shadows),
+        );
+    }
+}
+
+/// Draws the box and the flattened trails on the floor and on the two side walls facing away
+/// from the camera, like the classic xy/xz/yz phase portraits. Segments are drawn as gizmo
+/// lines, which is cheaper than spawning three extra entities per segment.
+fn draw_wall_shadows(
+    mut gizmos: Gizmos,
+    segments: Query<&Transform, With<TimeOfBirth>>,
+    cameras: Query<&GlobalTransform, With<PanOrbitCamera>>,
+    config: Res<Configuration>,
+) {
+    let (min, max) = (
+        config.wall_box_min.min(config.wall_box_max),
+        config.wall_box_min.max(config.wall_box_max),
+    );
+    let center = (min + max) / 2.;
+    let eye = cameras
+        .get_single()
+        .map_or(Vec3::ZERO, GlobalTransform::translation);
+
+    gizmos.cuboid(
+        Transform::from_translation(center).with_scale(max - min),
+        config.wall_shadow_color.with_alpha(0.3),
+    );
+
+    // Pick the wall on the far side of the box for x and y, the floor is always at min z.
+    let far = |axis: usize| {
+        if eye[axis] > center[axis] {
+            min[axis]
+        } else {
+            max[axis]
+        }
+    };
+    let walls = [(0, far(0)), (1, far(1)), (2, min.z)];
+
+    for transform in &segments {
+        let start = transform.translation;
+        let end = start + transform.rotation * Vec3::new(0., transform.scale.y, 0.);
+        for (axis, position) in walls {
+            let project = |mut point: Vec3| {
+                point[axis] = position;
+                point.clamp(min, max)
+            };
+            gizmos.line(project(start), project(end), config.wall_shadow_color);
+        }
+    }
+}